@@ -0,0 +1,11 @@
+fn main() {
+    // The gRPC service is generated from the proto with a bundled protoc, so
+    // building it needs nothing installed
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("bundled protoc");
+        // SAFETY: the build script is single-threaded
+        unsafe { std::env::set_var("PROTOC", protoc) };
+        tonic_prost_build::compile_protos("proto/monitor.proto").expect("compile proto");
+    }
+}
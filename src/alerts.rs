@@ -0,0 +1,1460 @@
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration};
+use zenoh::key_expr::keyexpr;
+use zenoh::sample::Locality;
+
+use crate::notify::{Event, NotifierHub};
+use crate::silences::Silences;
+use crate::{TopicCache, TopicData, Topics, ZenohSession, get_timestamp};
+
+/// The condition a rule watches, with separate assert and clear thresholds.
+///
+/// Keeping the clear threshold on the healthy side of the assert threshold
+/// gives a hysteresis band, so a topic hovering around a single value does
+/// not flip the alert on every evaluation.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Fires when the rate drops below `assert_hz` and clears above `clear_hz`
+    RateBelow { assert_hz: f64, clear_hz: f64 },
+    /// Fires when the rate rises above `assert_hz` and clears below `clear_hz`
+    RateAbove { assert_hz: f64, clear_hz: f64 },
+    /// Fires when the latest payload is smaller than `assert_bytes` and clears at or above `clear_bytes`
+    SizeBelow { assert_bytes: u64, clear_bytes: u64 },
+    /// Fires when the latest payload is larger than `assert_bytes` and clears at or below `clear_bytes`
+    SizeAbove { assert_bytes: u64, clear_bytes: u64 },
+    /// Fires when the latest payload is `assert_factor` times larger or smaller than
+    /// the rolling average and clears once back within `clear_factor`
+    SizeDeviation {
+        assert_factor: f64,
+        clear_factor: f64,
+    },
+    /// Fires after `assert_count` consecutive zero-length payloads and clears on the next non-empty one
+    EmptyStreak { assert_count: u64 },
+    /// Fires when the rolling fraction of failed decodes reaches `assert_fraction`
+    /// and clears only after `clear_success_streak` consecutive successful decodes
+    DecodeErrorRate {
+        assert_fraction: f64,
+        clear_success_streak: u64,
+    },
+}
+
+impl AlertCondition {
+    /// Returns `(value, asserting, clearing)` for the given topic
+    fn check(&self, topic: &TopicData, now: u64) -> (f64, bool, bool) {
+        match *self {
+            AlertCondition::RateBelow {
+                assert_hz,
+                clear_hz,
+            } => {
+                let hz = effective_hz(topic, now);
+                (hz, hz < assert_hz, hz > clear_hz)
+            }
+            AlertCondition::RateAbove {
+                assert_hz,
+                clear_hz,
+            } => {
+                let hz = effective_hz(topic, now);
+                (hz, hz > assert_hz, hz < clear_hz)
+            }
+            AlertCondition::SizeBelow {
+                assert_bytes,
+                clear_bytes,
+            } => {
+                let size = topic.last_data_size_bytes;
+                (size as f64, size < assert_bytes, size >= clear_bytes)
+            }
+            AlertCondition::SizeAbove {
+                assert_bytes,
+                clear_bytes,
+            } => {
+                let size = topic.last_data_size_bytes;
+                (size as f64, size > assert_bytes, size <= clear_bytes)
+            }
+            AlertCondition::SizeDeviation {
+                assert_factor,
+                clear_factor,
+            } => {
+                let deviation = size_deviation(topic);
+                (
+                    deviation,
+                    deviation >= assert_factor,
+                    deviation <= clear_factor,
+                )
+            }
+            AlertCondition::EmptyStreak { assert_count } => (
+                topic.empty_streak as f64,
+                topic.empty_streak >= assert_count,
+                topic.empty_streak == 0,
+            ),
+            AlertCondition::DecodeErrorRate {
+                assert_fraction,
+                clear_success_streak,
+            } => (
+                topic.decode_error_fraction,
+                topic.decode_error_fraction >= assert_fraction,
+                topic.decode_success_streak >= clear_success_streak,
+            ),
+        }
+    }
+
+    /// Name of the measured value, as published with alerts
+    fn metric(&self) -> &'static str {
+        match self {
+            AlertCondition::RateBelow { .. } | AlertCondition::RateAbove { .. } => "hz",
+            AlertCondition::SizeBelow { .. } | AlertCondition::SizeAbove { .. } => "size_bytes",
+            AlertCondition::SizeDeviation { .. } => "size_deviation",
+            AlertCondition::EmptyStreak { .. } => "empty_streak",
+            AlertCondition::DecodeErrorRate { .. } => "decode_error_fraction",
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            AlertCondition::RateBelow {
+                assert_hz,
+                clear_hz,
+            } => clear_hz >= assert_hz,
+            AlertCondition::RateAbove {
+                assert_hz,
+                clear_hz,
+            } => clear_hz <= assert_hz,
+            AlertCondition::SizeBelow {
+                assert_bytes,
+                clear_bytes,
+            } => clear_bytes >= assert_bytes,
+            AlertCondition::SizeAbove {
+                assert_bytes,
+                clear_bytes,
+            } => clear_bytes <= assert_bytes,
+            AlertCondition::SizeDeviation {
+                assert_factor,
+                clear_factor,
+            } => clear_factor >= 1.0 && assert_factor >= clear_factor,
+            AlertCondition::EmptyStreak { assert_count } => assert_count > 0,
+            AlertCondition::DecodeErrorRate {
+                assert_fraction,
+                clear_success_streak,
+            } => assert_fraction > 0.0 && assert_fraction <= 1.0 && clear_success_streak > 0,
+        }
+    }
+}
+
+/// How urgent an alert is, used by notifiers to filter what they deliver
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// A single alert rule applied to every topic matching `key_expr`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AlertRule {
+    pub name: &'static str,
+    pub key_expr: &'static str,
+    pub severity: Severity,
+    pub condition: AlertCondition,
+    /// How long the assert condition must hold before the alert fires
+    pub assert_dwell_ms: u64,
+    /// How long the clear condition must hold before the alert resolves
+    pub clear_dwell_ms: u64,
+}
+
+impl AlertRule {
+    fn matches(&self, key: &str) -> bool {
+        key_matches(self.key_expr, key)
+    }
+}
+
+pub fn key_matches(pattern: &str, key: &str) -> bool {
+    match (keyexpr::new(pattern), keyexpr::new(key)) {
+        (Ok(pattern), Ok(key)) => pattern.includes(key),
+        _ => false,
+    }
+}
+
+/// One input of a composite rule
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeMember {
+    /// Alerting when any rule is firing on a topic matching the key expression,
+    /// or when no cached topic matches it at all
+    Topics(&'static str),
+    /// Alerting when the named rule (single-topic or composite) is firing
+    Rule(&'static str),
+}
+
+/// How many members must be alerting for a composite rule to assert
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    All,
+    Any,
+    AtLeast(usize),
+}
+
+impl Aggregation {
+    fn is_met(&self, alerting: usize, total: usize) -> bool {
+        match *self {
+            Aggregation::All => alerting == total,
+            Aggregation::Any => alerting > 0,
+            Aggregation::AtLeast(k) => alerting >= k,
+        }
+    }
+}
+
+/// A rule over other rules or topic sets, evaluated after the single-topic rules each tick
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CompositeRule {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub members: &'static [CompositeMember],
+    pub aggregation: Aggregation,
+    pub assert_dwell_ms: u64,
+    pub clear_dwell_ms: u64,
+}
+
+/// Either kind of rule, as carried by alerts and transitions
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(untagged)]
+pub enum AnyRule {
+    Topic(AlertRule),
+    Composite(CompositeRule),
+}
+
+impl AnyRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnyRule::Topic(rule) => rule.name,
+            AnyRule::Composite(rule) => rule.name,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            AnyRule::Topic(rule) => rule.severity,
+            AnyRule::Composite(rule) => rule.severity,
+        }
+    }
+
+    /// What `value` measures; composite values count the members alerting
+    pub fn metric(&self) -> &'static str {
+        match self {
+            AnyRule::Topic(rule) => rule.condition.metric(),
+            AnyRule::Composite(_) => "members_alerting",
+        }
+    }
+
+    fn dwell_ms(&self) -> (u64, u64) {
+        match self {
+            AnyRule::Topic(rule) => (rule.assert_dwell_ms, rule.clear_dwell_ms),
+            AnyRule::Composite(rule) => (rule.assert_dwell_ms, rule.clear_dwell_ms),
+        }
+    }
+}
+
+/// Whether a composite member was alerting, and on which topics
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberStatus {
+    pub member: CompositeMember,
+    pub alerting: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub firing_on: Vec<String>,
+}
+
+/// Per-alert state machine.
+///
+/// `Pending` and `Resolving` are the dwell states: the condition has been
+/// observed but has not yet held for long enough to change the outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum AlertState {
+    Ok,
+    Pending { since: u64 },
+    Firing { since: u64 },
+    Resolving { since: u64, fired_at: u64 },
+}
+
+impl AlertState {
+    /// Advances the state machine, returning `Some` when the alert fires or resolves
+    fn step(
+        self,
+        asserting: bool,
+        clearing: bool,
+        (assert_dwell_ms, clear_dwell_ms): (u64, u64),
+        now: u64,
+    ) -> (AlertState, Option<Transition>) {
+        match self {
+            AlertState::Ok if asserting => {
+                if assert_dwell_ms == 0 {
+                    (AlertState::Firing { since: now }, Some(Transition::Fired))
+                } else {
+                    (AlertState::Pending { since: now }, None)
+                }
+            }
+            AlertState::Ok => (AlertState::Ok, None),
+            AlertState::Pending { .. } if !asserting => (AlertState::Ok, None),
+            AlertState::Pending { since } => {
+                if now.saturating_sub(since) >= assert_dwell_ms {
+                    (AlertState::Firing { since: now }, Some(Transition::Fired))
+                } else {
+                    (self, None)
+                }
+            }
+            AlertState::Firing { since } if clearing => {
+                if clear_dwell_ms == 0 {
+                    (AlertState::Ok, Some(Transition::Resolved))
+                } else {
+                    (
+                        AlertState::Resolving {
+                            since: now,
+                            fired_at: since,
+                        },
+                        None,
+                    )
+                }
+            }
+            AlertState::Firing { .. } => (self, None),
+            AlertState::Resolving { fired_at, .. } if !clearing => {
+                (AlertState::Firing { since: fired_at }, None)
+            }
+            AlertState::Resolving { since, .. } => {
+                if now.saturating_sub(since) >= clear_dwell_ms {
+                    (AlertState::Ok, Some(Transition::Resolved))
+                } else {
+                    (self, None)
+                }
+            }
+        }
+    }
+
+    fn dwell_remaining_ms(
+        &self,
+        (assert_dwell_ms, clear_dwell_ms): (u64, u64),
+        now: u64,
+    ) -> Option<u64> {
+        match *self {
+            AlertState::Pending { since } => {
+                Some(assert_dwell_ms.saturating_sub(now.saturating_sub(since)))
+            }
+            AlertState::Resolving { since, .. } => {
+                Some(clear_dwell_ms.saturating_sub(now.saturating_sub(since)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Firing, including while waiting to resolve
+    pub fn is_firing(&self) -> bool {
+        matches!(
+            self,
+            AlertState::Firing { .. } | AlertState::Resolving { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Fired,
+    Resolved,
+}
+
+/// Snapshot of the topic's metrics at the time of a transition
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStats {
+    pub estimated_hz: Option<f64>,
+    pub last_data_size_bytes: u64,
+    pub avg_data_size_bytes: f64,
+    pub received_timestamp: u64,
+    pub decode_error_fraction: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_decode_error: Option<String>,
+}
+
+impl From<&TopicData> for AlertStats {
+    fn from(topic: &TopicData) -> Self {
+        Self {
+            estimated_hz: topic.estimated_hz,
+            last_data_size_bytes: topic.last_data_size_bytes,
+            avg_data_size_bytes: topic.avg_data_size_bytes,
+            received_timestamp: topic.received_timestamp,
+            decode_error_fraction: topic.decode_error_fraction,
+            last_decode_error: topic.last_decode_error.clone(),
+        }
+    }
+}
+
+/// An accepted state change, emitted once per fire/resolve.
+///
+/// Composite alerts use the rule name as `key_expr`, carry no topic stats and
+/// list their members instead; their `value` is the number of members alerting.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertTransition {
+    /// `INSTANCE_NAME`, so consumers of several monitors can tell them apart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<&'static str>,
+    pub rule: AnyRule,
+    pub key_expr: String,
+    pub transition: Transition,
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<AlertStats>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<MemberStatus>,
+    pub timestamp: u64,
+    /// Acknowledged or silenced, so notifiers skip it
+    #[serde(skip)]
+    pub suppressed: bool,
+}
+
+/// Body of `POST /api/alerts/{id}/ack`
+#[derive(Debug, Default, Deserialize)]
+pub struct AckRequest {
+    pub by: Option<String>,
+}
+
+/// Who acknowledged an alert and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ack {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    #[serde(default)]
+    pub at: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Alert {
+    id: u64,
+    state: AlertState,
+    value: f64,
+    /// Cleared when the alert returns to `Ok`
+    ack: Option<Ack>,
+}
+
+#[derive(Debug, Clone)]
+struct CompositeAlert {
+    id: u64,
+    state: AlertState,
+    value: f64,
+    ack: Option<Ack>,
+    members: Vec<MemberStatus>,
+}
+
+/// Alert as reported by `/api/alerts`, with the rule's effective thresholds
+#[derive(Debug, Serialize)]
+pub struct AlertView {
+    /// Used by `POST /api/alerts/{id}/ack`; stable until the topic leaves the cache
+    pub id: u64,
+    pub rule: AnyRule,
+    pub key_expr: String,
+    #[serde(flatten)]
+    pub state: AlertState,
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dwell_remaining_ms: Option<u64>,
+    /// Member conditions of a composite alert
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<MemberStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged: Option<Ack>,
+    /// Id of the silence covering this alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub silenced_by: Option<u64>,
+}
+
+/// A recorded transition, as listed by `/api/alerts/history`
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertHistoryEntry {
+    pub rule: &'static str,
+    pub key_expr: String,
+    pub state: Transition,
+    pub value: f64,
+    pub timestamp: u64,
+}
+
+/// Retention limits for the alert history; whichever is hit first applies
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HistoryRetention {
+    pub max_entries: usize,
+    pub max_age_ms: u64,
+}
+
+/// Filters and pagination for `/api/alerts/history`
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Key expression matched against the alert's topic
+    pub topic: Option<String>,
+    pub rule: Option<String>,
+    pub state: Option<Transition>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+const HISTORY_DEFAULT_LIMIT: usize = 100;
+const HISTORY_MAX_LIMIT: usize = 1000;
+
+/// One page of alert history, newest first
+#[derive(Debug, Serialize)]
+pub struct HistoryPage {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub entries: Vec<AlertHistoryEntry>,
+}
+
+/// Holds the configured rules and the state of every (rule, topic) pair
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    alerts: BTreeMap<(usize, String), Alert>,
+    /// Composite rules in dependency order, so a referenced rule is always evaluated first
+    composites: Vec<CompositeRule>,
+    composite_alerts: Vec<CompositeAlert>,
+    next_alert_id: u64,
+    history: VecDeque<AlertHistoryEntry>,
+    retention: HistoryRetention,
+    silences: Silences,
+    /// Named in every transition, from `Config::instance_name`
+    instance: Option<&'static str>,
+}
+
+pub type AlertStore = Arc<RwLock<AlertEngine>>;
+
+impl AlertEngine {
+    pub fn new(
+        rules: &[AlertRule],
+        composites: &[CompositeRule],
+        retention: HistoryRetention,
+        silences: Silences,
+        instance: Option<&'static str>,
+    ) -> Self {
+        let rules: Vec<AlertRule> = rules
+            .iter()
+            .filter(|rule| {
+                let valid = rule.condition.is_valid() && keyexpr::new(rule.key_expr).is_ok();
+                if !valid {
+                    warn!("Ignoring invalid alert rule '{}'", rule.name);
+                }
+                valid
+            })
+            .copied()
+            .collect();
+
+        let composites = order_composites(&rules, composites);
+        let composite_alerts: Vec<CompositeAlert> = (1..)
+            .zip(&composites)
+            .map(|(id, _)| CompositeAlert {
+                id,
+                state: AlertState::Ok,
+                value: 0.0,
+                ack: None,
+                members: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            rules,
+            alerts: BTreeMap::new(),
+            next_alert_id: composite_alerts.len() as u64 + 1,
+            composites,
+            composite_alerts,
+            history: VecDeque::new(),
+            retention,
+            silences,
+            instance,
+        }
+    }
+
+    /// Evaluates every rule against the current cache.
+    /// Alerts whose topic is no longer cached are dropped.
+    pub fn evaluate(&mut self, topics: &Topics, now: u64) -> Vec<AlertTransition> {
+        let mut transitions = Vec::new();
+
+        self.alerts.retain(|(_, key), _| topics.contains_key(key));
+        self.silences.expire(now);
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            for (key, topic) in topics.iter().filter(|(key, _)| rule.matches(key)) {
+                let (value, asserting, clearing) = rule.condition.check(topic, now);
+                let alert = self.alerts.entry((index, key.clone())).or_insert_with(|| {
+                    self.next_alert_id += 1;
+                    Alert {
+                        id: self.next_alert_id - 1,
+                        state: AlertState::Ok,
+                        value,
+                        ack: None,
+                    }
+                });
+
+                let (state, transition) = alert.state.step(
+                    asserting,
+                    clearing,
+                    (rule.assert_dwell_ms, rule.clear_dwell_ms),
+                    now,
+                );
+                alert.state = state;
+                alert.value = value;
+
+                if let Some(transition) = transition {
+                    transitions.push(AlertTransition {
+                        instance: self.instance,
+                        rule: AnyRule::Topic(*rule),
+                        key_expr: key.clone(),
+                        transition,
+                        value,
+                        stats: Some(AlertStats::from(&topic.data)),
+                        members: Vec::new(),
+                        timestamp: now,
+                        suppressed: alert.ack.is_some()
+                            || self.silences.matching(rule.name, key, now).is_some(),
+                    });
+                }
+                if state == AlertState::Ok {
+                    alert.ack = None;
+                }
+            }
+        }
+
+        let mut composite_firing = HashMap::new();
+        for index in 0..self.composites.len() {
+            let rule = self.composites[index];
+            let members: Vec<MemberStatus> = rule
+                .members
+                .iter()
+                .map(|member| self.member_status(*member, topics, &composite_firing))
+                .collect();
+            let alerting = members.iter().filter(|m| m.alerting).count();
+            let asserting = rule.aggregation.is_met(alerting, members.len());
+
+            let alert = &mut self.composite_alerts[index];
+            let (state, transition) = alert.state.step(
+                asserting,
+                !asserting,
+                (rule.assert_dwell_ms, rule.clear_dwell_ms),
+                now,
+            );
+            alert.state = state;
+            alert.value = alerting as f64;
+            alert.members = members;
+            composite_firing.insert(rule.name, state.is_firing());
+
+            if let Some(transition) = transition {
+                transitions.push(AlertTransition {
+                    instance: self.instance,
+                    rule: AnyRule::Composite(rule),
+                    key_expr: rule.name.to_string(),
+                    transition,
+                    value: alert.value,
+                    stats: None,
+                    members: alert.members.clone(),
+                    timestamp: now,
+                    suppressed: alert.ack.is_some()
+                        || self.silences.matching(rule.name, rule.name, now).is_some(),
+                });
+            }
+            if state == AlertState::Ok {
+                alert.ack = None;
+            }
+        }
+
+        self.record(&transitions, now);
+        transitions
+    }
+
+    /// Evaluates one composite member against the single-topic alerts and
+    /// the composites already evaluated this tick
+    fn member_status(
+        &self,
+        member: CompositeMember,
+        topics: &Topics,
+        composite_firing: &HashMap<&'static str, bool>,
+    ) -> MemberStatus {
+        let firing_on = |matches: &dyn Fn(usize, &str) -> bool| -> Vec<String> {
+            self.alerts
+                .iter()
+                .filter(|((index, key), alert)| alert.state.is_firing() && matches(*index, key))
+                .map(|((_, key), _)| key.clone())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        };
+
+        match member {
+            CompositeMember::Topics(pattern) => {
+                let firing_on = firing_on(&|_, key| key_matches(pattern, key));
+                let present = topics.keys().any(|key| key_matches(pattern, key));
+                MemberStatus {
+                    member,
+                    alerting: !present || !firing_on.is_empty(),
+                    firing_on,
+                }
+            }
+            CompositeMember::Rule(name) => match self.rules.iter().position(|r| r.name == name) {
+                Some(rule_index) => {
+                    let firing_on = firing_on(&|index, _| index == rule_index);
+                    MemberStatus {
+                        member,
+                        alerting: !firing_on.is_empty(),
+                        firing_on,
+                    }
+                }
+                None => MemberStatus {
+                    member,
+                    alerting: composite_firing.get(name).copied().unwrap_or(false),
+                    firing_on: Vec::new(),
+                },
+            },
+        }
+    }
+
+    /// Appends transitions to the history and applies the retention limits
+    fn record(&mut self, transitions: &[AlertTransition], now: u64) {
+        self.history
+            .extend(transitions.iter().map(|t| AlertHistoryEntry {
+                rule: t.rule.name(),
+                key_expr: t.key_expr.clone(),
+                state: t.transition,
+                value: t.value,
+                timestamp: t.timestamp,
+            }));
+
+        while self.history.len() > self.retention.max_entries {
+            self.history.pop_front();
+        }
+        let cutoff = now.saturating_sub(self.retention.max_age_ms);
+        while self
+            .history
+            .front()
+            .is_some_and(|entry| entry.timestamp < cutoff)
+        {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the matching history entries, newest first
+    pub fn history(&self, query: &HistoryQuery) -> HistoryPage {
+        let topic_pattern = query.topic.as_deref().map(keyexpr::new);
+
+        let matching: Vec<&AlertHistoryEntry> = self
+            .history
+            .iter()
+            .rev()
+            .filter(|entry| match &topic_pattern {
+                Some(Ok(pattern)) => {
+                    keyexpr::new(&entry.key_expr).is_ok_and(|key| pattern.includes(key))
+                }
+                Some(Err(_)) => query.topic.as_deref() == Some(entry.key_expr.as_str()),
+                None => true,
+            })
+            .filter(|entry| query.rule.as_deref().is_none_or(|rule| rule == entry.rule))
+            .filter(|entry| query.state.is_none_or(|state| state == entry.state))
+            .filter(|entry| query.from.is_none_or(|from| entry.timestamp >= from))
+            .filter(|entry| query.to.is_none_or(|to| entry.timestamp <= to))
+            .collect();
+
+        let offset = query.offset.unwrap_or(0);
+        let limit = query
+            .limit
+            .unwrap_or(HISTORY_DEFAULT_LIMIT)
+            .min(HISTORY_MAX_LIMIT);
+
+        HistoryPage {
+            total: matching.len(),
+            offset,
+            limit,
+            entries: matching
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Every alert that is not idle, including those still in a dwell state
+    pub fn active(&self, now: u64) -> Vec<AlertView> {
+        let topic_alerts = self
+            .alerts
+            .iter()
+            .filter(|(_, alert)| alert.state != AlertState::Ok)
+            .map(|((index, key), alert)| {
+                let rule = AnyRule::Topic(self.rules[*index]);
+                AlertView {
+                    id: alert.id,
+                    rule,
+                    key_expr: key.clone(),
+                    state: alert.state,
+                    value: alert.value,
+                    dwell_remaining_ms: alert.state.dwell_remaining_ms(rule.dwell_ms(), now),
+                    members: Vec::new(),
+                    acknowledged: alert.ack.clone(),
+                    silenced_by: self.silences.matching(rule.name(), key, now),
+                }
+            });
+
+        let composite_alerts = self
+            .composites
+            .iter()
+            .zip(&self.composite_alerts)
+            .filter(|(_, alert)| alert.state != AlertState::Ok)
+            .map(|(rule, alert)| {
+                let rule = AnyRule::Composite(*rule);
+                AlertView {
+                    id: alert.id,
+                    rule,
+                    key_expr: rule.name().to_string(),
+                    state: alert.state,
+                    value: alert.value,
+                    dwell_remaining_ms: alert.state.dwell_remaining_ms(rule.dwell_ms(), now),
+                    members: alert.members.clone(),
+                    acknowledged: alert.ack.clone(),
+                    silenced_by: self.silences.matching(rule.name(), rule.name(), now),
+                }
+            });
+
+        topic_alerts.chain(composite_alerts).collect()
+    }
+
+    /// Alerts currently firing (or waiting to resolve), as transitions for re-publishing
+    fn firing(&self, topics: &Topics, now: u64) -> Vec<AlertTransition> {
+        let topic_alerts = self
+            .alerts
+            .iter()
+            .filter(|(_, alert)| alert.state.is_firing())
+            .filter_map(|((index, key), alert)| {
+                Some(AlertTransition {
+                    instance: self.instance,
+                    rule: AnyRule::Topic(self.rules[*index]),
+                    key_expr: key.clone(),
+                    transition: Transition::Fired,
+                    value: alert.value,
+                    stats: Some(AlertStats::from(&topics.get(key)?.data)),
+                    members: Vec::new(),
+                    timestamp: now,
+                    suppressed: false,
+                })
+            });
+
+        let composite_alerts = self
+            .composites
+            .iter()
+            .zip(&self.composite_alerts)
+            .filter(|(_, alert)| alert.state.is_firing())
+            .map(|(rule, alert)| AlertTransition {
+                instance: self.instance,
+                rule: AnyRule::Composite(*rule),
+                key_expr: rule.name.to_string(),
+                transition: Transition::Fired,
+                value: alert.value,
+                stats: None,
+                members: alert.members.clone(),
+                timestamp: now,
+                suppressed: false,
+            });
+
+        topic_alerts.chain(composite_alerts).collect()
+    }
+
+    /// Acknowledges a non-idle alert, suppressing its notifications until it returns to `Ok`.
+    /// Returns false if no such alert is active.
+    pub fn acknowledge(&mut self, id: u64, by: Option<String>, now: u64) -> bool {
+        let ack = &mut self
+            .alerts
+            .values_mut()
+            .map(|alert| (alert.id, alert.state, &mut alert.ack))
+            .chain(
+                self.composite_alerts
+                    .iter_mut()
+                    .map(|alert| (alert.id, alert.state, &mut alert.ack)),
+            )
+            .find(|(alert_id, state, _)| *alert_id == id && *state != AlertState::Ok);
+
+        match ack {
+            Some((_, _, ack)) => {
+                **ack = Some(Ack { by, at: now });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn composites(&self) -> &[CompositeRule] {
+        &self.composites
+    }
+
+    pub fn silences(&self) -> &Silences {
+        &self.silences
+    }
+
+    pub fn silences_mut(&mut self) -> &mut Silences {
+        &mut self.silences
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+}
+
+/// Validates the composite rules and sorts them so every referenced composite
+/// comes before the rules that use it. Rules with unknown references, or that
+/// are part of (or depend on) a reference cycle, are dropped with a warning.
+fn order_composites(rules: &[AlertRule], composites: &[CompositeRule]) -> Vec<CompositeRule> {
+    let rule_names: HashSet<&str> = rules.iter().map(|rule| rule.name).collect();
+    let mut composite_names = HashSet::new();
+
+    let mut remaining: Vec<CompositeRule> = composites
+        .iter()
+        .filter(|rule| {
+            let valid_aggregation = match rule.aggregation {
+                Aggregation::AtLeast(k) => k > 0 && k <= rule.members.len(),
+                _ => true,
+            };
+            let valid_members = rule.members.iter().all(|member| match member {
+                CompositeMember::Topics(pattern) => keyexpr::new(*pattern).is_ok(),
+                CompositeMember::Rule(name) => {
+                    rule_names.contains(name) || composites.iter().any(|c| c.name == *name)
+                }
+            });
+            let unique = !rule_names.contains(rule.name) && composite_names.insert(rule.name);
+
+            let valid = !rule.members.is_empty() && valid_aggregation && valid_members && unique;
+            if !valid {
+                warn!("Ignoring invalid composite alert rule '{}'", rule.name);
+            }
+            valid
+        })
+        .copied()
+        .collect();
+
+    let mut ordered: Vec<CompositeRule> = Vec::new();
+    loop {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|rule| {
+            rule.members.iter().all(|member| match member {
+                CompositeMember::Rule(name) => {
+                    rule_names.contains(name) || ordered.iter().any(|c| c.name == *name)
+                }
+                CompositeMember::Topics(_) => true,
+            })
+        });
+        if ready.is_empty() {
+            for rule in &blocked {
+                warn!(
+                    "Ignoring composite alert rule '{}': its rule references form a cycle",
+                    rule.name
+                );
+            }
+            return ordered;
+        }
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+}
+
+/// Rate as seen by the alert rules.
+/// `estimated_hz` only changes when a sample arrives, so once a topic has been
+/// silent for more than two of its usual intervals the silence itself bounds the rate.
+/// A topic without an estimate yet counts as 0 Hz.
+pub fn effective_hz(topic: &TopicData, now: u64) -> f64 {
+    let Some(hz) = topic.estimated_hz else {
+        return 0.0;
+    };
+    let silence_ms = now.saturating_sub(topic.received_timestamp) as f64;
+    if hz > 0.0 && silence_ms > 2.0 * 1000.0 / hz {
+        1000.0 / silence_ms
+    } else {
+        hz
+    }
+}
+
+/// How many times larger or smaller the latest payload is than the rolling average.
+/// Sizes are floored at one byte so an empty payload is a finite, large deviation.
+fn size_deviation(topic: &TopicData) -> f64 {
+    let size = (topic.last_data_size_bytes as f64).max(1.0);
+    let avg = topic.avg_data_size_bytes.max(1.0);
+    size.max(avg) / size.min(avg)
+}
+
+/// Payload published on `<prefix>/<topic>` for every alert transition
+#[derive(Serialize)]
+struct AlertMessage<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'static str>,
+    rule: &'a AnyRule,
+    key_expr: &'a str,
+    /// What `value` measures, e.g. `hz`
+    metric: &'static str,
+    /// `fired` or `resolved`
+    transition: Transition,
+    /// `ok` before firing, `firing` before resolving
+    previous_state: &'static str,
+    /// `firing` or `ok`
+    state: &'static str,
+    value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a AlertStats>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    members: &'a [MemberStatus],
+    timestamp: u64,
+}
+
+/// Replaces the characters that are not allowed in a concrete key expression
+fn sanitize_key(key: &str) -> String {
+    key.replace(['*', '$', '?', '#'], "_")
+}
+
+/// Publishes an alert onto Zenoh.
+/// Delivery is restricted to remote subscribers so the monitor never sees its own alerts.
+async fn publish_alert(session: &zenoh::Session, prefix: &str, t: &AlertTransition) {
+    let message = AlertMessage {
+        instance: t.instance,
+        rule: &t.rule,
+        key_expr: &t.key_expr,
+        metric: t.rule.metric(),
+        transition: t.transition,
+        previous_state: match t.transition {
+            Transition::Fired => "ok",
+            Transition::Resolved => "firing",
+        },
+        state: match t.transition {
+            Transition::Fired => "firing",
+            Transition::Resolved => "ok",
+        },
+        value: t.value,
+        stats: t.stats.as_ref(),
+        members: &t.members,
+        timestamp: t.timestamp,
+    };
+
+    let key = format!("{}/{}", prefix, sanitize_key(&t.key_expr));
+    let payload = match serde_json::to_string(&message) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialise alert for '{}': {}", t.key_expr, e);
+            return;
+        }
+    };
+
+    if let Err(e) = session
+        .put(&key, payload)
+        .allowed_destination(Locality::Remote)
+        .await
+    {
+        warn!("Failed to publish alert on '{}': {}", key, e);
+    }
+}
+
+/// Periodically evaluates the alert rules against the topic cache,
+/// handing every transition to the configured notifiers.
+///
+/// When `publish_prefix` is set, transitions are published onto Zenoh and
+/// firing alerts are re-published every `republish_ms` so late joiners see them.
+/// Transitions of an alert within `debounce_ms` of its last publish are held
+/// back and only the latest is published, or none if it flapped back.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_alert_evaluator(
+    cache: TopicCache,
+    alerts: AlertStore,
+    session: ZenohSession,
+    notifiers: NotifierHub,
+    period_ms: u64,
+    publish_prefix: Option<&'static str>,
+    republish_ms: u64,
+    debounce_ms: u64,
+) {
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    let mut last_republish = get_timestamp();
+    // Per (rule, key): when the last transition was published, and which one
+    let mut published: HashMap<(&'static str, String), (u64, Transition)> = HashMap::new();
+    let mut held: HashMap<(&'static str, String), AlertTransition> = HashMap::new();
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let (transitions, republish) = {
+            let topics = cache.read().await;
+            let mut engine = alerts.write().await;
+            let transitions = engine.evaluate(&topics, now);
+            let republish =
+                if publish_prefix.is_some() && now.saturating_sub(last_republish) >= republish_ms {
+                    last_republish = now;
+                    engine.firing(&topics, now)
+                } else {
+                    Vec::new()
+                };
+            (transitions, republish)
+        };
+
+        for t in &transitions {
+            if !t.suppressed {
+                notifiers.dispatch(&Event::AlertTransition(t.clone())).await;
+            }
+            match t.transition {
+                Transition::Fired => warn!(
+                    "Alert '{}' fired on '{}' (value {:.2})",
+                    t.rule.name(),
+                    t.key_expr,
+                    t.value
+                ),
+                Transition::Resolved => info!(
+                    "Alert '{}' resolved on '{}' (value {:.2})",
+                    t.rule.name(),
+                    t.key_expr,
+                    t.value
+                ),
+            }
+        }
+
+        let Some(prefix) = publish_prefix else {
+            continue;
+        };
+        let Some(session) = session.read().await.clone() else {
+            continue;
+        };
+        for t in transitions {
+            held.insert((t.rule.name(), t.key_expr.clone()), t);
+        }
+        let mut due = Vec::new();
+        held.retain(|alert, t| match published.get(alert) {
+            Some((at, _)) if now.saturating_sub(*at) < debounce_ms => true,
+            Some((_, last)) if *last == t.transition => false,
+            _ => {
+                due.push(t.clone());
+                false
+            }
+        });
+        published.retain(|_, (at, _)| now.saturating_sub(*at) < debounce_ms);
+
+        // A transition this tick is newer than a re-publish of the same alert
+        for t in republish.iter().chain(&due) {
+            publish_alert(&session, prefix, t).await;
+        }
+        for t in due {
+            published.insert((t.rule.name(), t.key_expr), (now, t.transition));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TopicState;
+
+    /// Feeds `(asserting, clearing, now)` inputs to `step` from `Ok`, returning
+    /// each resulting state and transition
+    fn steps(
+        dwell: (u64, u64),
+        inputs: &[(bool, bool, u64)],
+    ) -> Vec<(AlertState, Option<Transition>)> {
+        let mut state = AlertState::Ok;
+        inputs
+            .iter()
+            .map(|&(asserting, clearing, now)| {
+                let (next, transition) = state.step(asserting, clearing, dwell, now);
+                state = next;
+                (next, transition)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flapping_within_the_assert_dwell_never_fires() {
+        use AlertState::*;
+        assert_eq!(
+            steps(
+                (1000, 1000),
+                &[
+                    (true, false, 0),
+                    (false, false, 500),
+                    (true, false, 600),
+                    (false, true, 1200),
+                    (true, false, 1300),
+                    (true, false, 2299),
+                    (false, false, 2300),
+                ]
+            ),
+            [
+                (Pending { since: 0 }, None),
+                (Ok, None),
+                (Pending { since: 600 }, None),
+                (Ok, None),
+                (Pending { since: 1300 }, None),
+                (Pending { since: 1300 }, None),
+                (Ok, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_fires_once_the_assert_dwell_has_passed() {
+        use AlertState::*;
+        assert_eq!(
+            steps(
+                (1000, 1000),
+                &[
+                    (true, false, 0),
+                    (true, false, 999),
+                    (true, false, 1000),
+                    (true, false, 5000),
+                ]
+            ),
+            [
+                (Pending { since: 0 }, None),
+                (Pending { since: 0 }, None),
+                (Firing { since: 1000 }, Some(Transition::Fired)),
+                (Firing { since: 1000 }, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolving_goes_back_to_firing_when_the_condition_returns() {
+        use AlertState::*;
+        let fired = [(true, false, 0), (true, false, 1000)];
+        assert_eq!(
+            steps(
+                (1000, 1000),
+                &[
+                    fired[0],
+                    fired[1],
+                    (false, true, 2000),
+                    (true, false, 2500),
+                    // Inside the hysteresis band nothing changes
+                    (false, false, 2600),
+                    (false, true, 3000),
+                    (false, false, 3500),
+                    (false, true, 4000),
+                    (false, true, 4999),
+                    (false, true, 5000),
+                ]
+            )[2..],
+            [
+                (
+                    Resolving {
+                        since: 2000,
+                        fired_at: 1000
+                    },
+                    None
+                ),
+                (Firing { since: 1000 }, None),
+                (Firing { since: 1000 }, None),
+                (
+                    Resolving {
+                        since: 3000,
+                        fired_at: 1000
+                    },
+                    None
+                ),
+                (Firing { since: 1000 }, None),
+                (
+                    Resolving {
+                        since: 4000,
+                        fired_at: 1000
+                    },
+                    None
+                ),
+                (
+                    Resolving {
+                        since: 4000,
+                        fired_at: 1000
+                    },
+                    None
+                ),
+                (Ok, Some(Transition::Resolved)),
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_dwell_transitions_immediately() {
+        use AlertState::*;
+        assert_eq!(
+            steps(
+                (0, 0),
+                &[(true, false, 0), (false, false, 10), (false, true, 20)]
+            ),
+            [
+                (Firing { since: 0 }, Some(Transition::Fired)),
+                (Firing { since: 0 }, None),
+                (Ok, Some(Transition::Resolved)),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_rate_hovering_between_thresholds_flips_the_alert_once_each_way() {
+        let condition = AlertCondition::RateBelow {
+            assert_hz: 8.0,
+            clear_hz: 9.5,
+        };
+        let mut state = AlertState::Ok;
+        let mut transitions = Vec::new();
+        for (i, hz) in [10.0, 7.9, 8.1, 7.9, 9.0, 7.5, 9.4, 8.0, 9.6, 9.0, 8.1]
+            .into_iter()
+            .enumerate()
+        {
+            let now = i as u64 * 1000;
+            let topic = TopicData {
+                estimated_hz: Some(hz),
+                received_timestamp: now,
+                ..TopicData::default()
+            };
+            let (_, asserting, clearing) = condition.check(&topic, now);
+            let (next, transition) = state.step(asserting, clearing, (0, 0), now);
+            state = next;
+            transitions.extend(transition.map(|transition| (hz, transition)));
+        }
+        assert_eq!(
+            transitions,
+            [(7.9, Transition::Fired), (9.6, Transition::Resolved)]
+        );
+    }
+
+    fn sized(bytes: u64, avg_bytes: f64) -> TopicData {
+        TopicData {
+            last_data_size_bytes: bytes,
+            avg_data_size_bytes: avg_bytes,
+            ..TopicData::default()
+        }
+    }
+
+    /// `(asserting, clearing)` of `condition` for a payload of `bytes` after an average of `avg_bytes`
+    fn size_check(condition: AlertCondition, bytes: u64, avg_bytes: f64) -> (bool, bool) {
+        let (_, asserting, clearing) = condition.check(&sized(bytes, avg_bytes), 0);
+        (asserting, clearing)
+    }
+
+    #[test]
+    fn size_thresholds_assert_and_clear_with_a_band_between() {
+        let below = AlertCondition::SizeBelow {
+            assert_bytes: 10,
+            clear_bytes: 20,
+        };
+        assert_eq!(size_check(below, 9, 0.0), (true, false));
+        assert_eq!(size_check(below, 10, 0.0), (false, false));
+        assert_eq!(size_check(below, 20, 0.0), (false, true));
+
+        let above = AlertCondition::SizeAbove {
+            assert_bytes: 1000,
+            clear_bytes: 800,
+        };
+        assert_eq!(size_check(above, 1001, 0.0), (true, false));
+        assert_eq!(size_check(above, 900, 0.0), (false, false));
+        assert_eq!(size_check(above, 800, 0.0), (false, true));
+    }
+
+    #[test]
+    fn size_deviation_works_both_ways_and_floors_empty_payloads() {
+        let deviation = AlertCondition::SizeDeviation {
+            assert_factor: 10.0,
+            clear_factor: 2.0,
+        };
+        assert_eq!(size_check(deviation, 1000, 100.0), (true, false));
+        assert_eq!(size_check(deviation, 10, 100.0), (true, false));
+        assert_eq!(size_check(deviation, 500, 100.0), (false, false));
+        assert_eq!(size_check(deviation, 150, 100.0), (false, true));
+
+        // An empty payload counts as one byte: a large, finite deviation
+        assert_eq!(size_deviation(&sized(0, 100.0)), 100.0);
+        assert_eq!(size_check(deviation, 0, 100.0), (true, false));
+        // and nothing but empty payloads is no deviation at all
+        assert_eq!(size_deviation(&sized(0, 0.0)), 1.0);
+        assert_eq!(size_check(deviation, 0, 0.0), (false, true));
+        assert_eq!(size_deviation(&sized(50, 0.0)), 50.0);
+    }
+
+    #[test]
+    fn size_and_rate_rules_on_one_topic_fire_independently() {
+        let rule = |name, condition| AlertRule {
+            name,
+            key_expr: "robot/**",
+            severity: Severity::Warning,
+            condition,
+            assert_dwell_ms: 0,
+            clear_dwell_ms: 0,
+        };
+        let rules = [
+            rule(
+                "slow",
+                AlertCondition::RateBelow {
+                    assert_hz: 8.0,
+                    clear_hz: 9.5,
+                },
+            ),
+            rule(
+                "oversized",
+                AlertCondition::SizeAbove {
+                    assert_bytes: 1000,
+                    clear_bytes: 800,
+                },
+            ),
+        ];
+        let mut engine = AlertEngine::new(
+            &rules,
+            &[],
+            HistoryRetention {
+                max_entries: 100,
+                max_age_ms: u64::MAX,
+            },
+            Silences::in_memory(),
+            None,
+        );
+        let mut evaluate = |hz: f64, bytes: u64, now: u64| {
+            let topic = TopicData {
+                key_expr: "robot/lidar".to_string(),
+                estimated_hz: Some(hz),
+                received_timestamp: now,
+                last_data_size_bytes: bytes,
+                avg_data_size_bytes: 500.0,
+                ..TopicData::default()
+            };
+            let topics = Topics::from([("robot/lidar".to_string(), TopicState::from(topic))]);
+            engine
+                .evaluate(&topics, now)
+                .iter()
+                .map(|t| (t.rule.name(), t.transition))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(evaluate(10.0, 500, 1000), []);
+        assert_eq!(
+            evaluate(10.0, 5000, 2000),
+            [("oversized", Transition::Fired)]
+        );
+        // The rate dropping doesn't touch the size alert, nor the other way round
+        assert_eq!(evaluate(5.0, 5000, 3000), [("slow", Transition::Fired)]);
+        assert_eq!(
+            evaluate(5.0, 500, 4000),
+            [("oversized", Transition::Resolved)]
+        );
+        assert_eq!(evaluate(10.0, 900, 5000), [("slow", Transition::Resolved)]);
+        assert_eq!(
+            evaluate(5.0, 5000, 6000),
+            [
+                ("slow", Transition::Fired),
+                ("oversized", Transition::Fired)
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use log::{info, warn};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{self, Duration};
+
+use crate::*;
+
+/// Version of the audit snapshot schema, bumped on incompatible field changes
+pub const AUDIT_SCHEMA_VERSION: u32 = 1;
+
+/// Periodic record of which topics existed and at what rate, for audit trails.
+/// Each snapshot is appended to `path` as one JSON line.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AuditLog {
+    pub path: &'static str,
+    pub period_ms: u64,
+}
+
+impl AuditLog {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.period_ms < 1000 {
+            return Err("the audit snapshot period must be at least 1000 ms".to_string());
+        }
+        if self.path.is_empty() {
+            return Err("the audit log needs a file path".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct TopicEntry<'a> {
+    key_expr: &'a str,
+    /// Rate as the alert rules see it, so a silent topic decays toward 0
+    hz: f64,
+    avg_size_bytes: f64,
+    /// `null` for a pinned topic that has never been received
+    last_received: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'static str>,
+    at: u64,
+    /// `at` as UTC, for reading the file by eye
+    at_utc: String,
+    topic_count: usize,
+    /// Sorted by key, so consecutive snapshots diff cleanly
+    topics: Vec<TopicEntry<'a>>,
+}
+
+/// Appends a snapshot of every cached topic to the audit log every `period_ms`,
+/// and a last one at the shutdown signal. A failed write is logged and retried
+/// with the next snapshot.
+pub async fn run_audit_log(
+    cache: TopicCache,
+    audit: AuditLog,
+    instance: Option<&'static str>,
+    mut shutdown: ShutdownSignal,
+) {
+    info!(
+        "Writing an audit snapshot to {} every {} ms",
+        audit.path, audit.period_ms
+    );
+    let mut interval = time::interval(Duration::from_millis(audit.period_ms));
+    loop {
+        let last = tokio::select! {
+            _ = interval.tick() => false,
+            () = shutdown::stopping(&mut shutdown) => true,
+        };
+        write_snapshot(&cache, audit, instance).await;
+        if last {
+            return;
+        }
+    }
+}
+
+async fn write_snapshot(cache: &TopicCache, audit: AuditLog, instance: Option<&'static str>) {
+    let now = get_timestamp();
+    let mut line = {
+        let topics = cache.read().await;
+        let mut entries: Vec<TopicEntry> = topics
+            .values()
+            .map(|topic| TopicEntry {
+                key_expr: &topic.key_expr,
+                hz: alerts::effective_hz(topic, now),
+                avg_size_bytes: topic.avg_data_size_bytes,
+                last_received: (!topic.is_placeholder()).then_some(topic.received_timestamp),
+                instance: topic.instance.as_deref(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.key_expr.cmp(b.key_expr));
+        let snapshot = Snapshot {
+            schema_version: AUDIT_SCHEMA_VERSION,
+            instance,
+            at: now,
+            at_utc: format::timestamp(now),
+            topic_count: entries.len(),
+            topics: entries,
+        };
+        match serde_json::to_vec(&snapshot) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialise the audit snapshot: {}", e);
+                return;
+            }
+        }
+    };
+    line.push(b'\n');
+
+    let result = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(audit.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!(
+            "Failed to write the audit snapshot to {}: {}",
+            audit.path, e
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use base64::Engine;
+use log::warn;
+use serde::Serialize;
+use std::path::Path;
+
+/// How the page presents itself, so several monitor instances are easy to tell apart
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Branding {
+    /// Text of the browser tab
+    pub page_title: &'static str,
+    pub heading: &'static str,
+    pub subtitle: &'static str,
+    /// Image shown next to the heading, embedded into the page at startup
+    pub logo_path: Option<&'static str>,
+    /// Colour of the stats bar and the topic keys
+    pub accent_color: Option<&'static str>,
+}
+
+impl Branding {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(color) = self.accent_color
+            && !crate::is_plain_css_value(color)
+        {
+            return Err(format!(
+                "accent color '{}' is not a plain CSS colour",
+                color
+            ));
+        }
+        Ok(())
+    }
+
+    /// The logo as a `data:` URI, or `None` with a warning if it cannot be used.
+    /// A broken logo should never cost the dashboard, so this never fails.
+    pub fn logo_data_uri(&self) -> Option<String> {
+        let path = self.logo_path?;
+        let mime = match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("png") => "image/png",
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("svg") => "image/svg+xml",
+            _ => {
+                warn!("Ignoring logo {}: unsupported image type", path);
+                return None;
+            }
+        };
+        match std::fs::read(path) {
+            Ok(bytes) => Some(format!(
+                "data:{};base64,{}",
+                mime,
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            )),
+            Err(e) => {
+                warn!("Ignoring logo {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Overrides of the theme for the accent colour
+    pub fn css(&self) -> String {
+        self.accent_color.map_or_else(String::new, |color| {
+            format!(
+                ":root {{ --topic: {color}; }}\n    .stats {{ background: {color}; }}",
+                color = color
+            )
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use crate::format::{self, Units};
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+/// Plot area margins: left leaves room for the y labels, top for the title
+const LEFT: f64 = 70.0;
+const RIGHT: f64 = 15.0;
+const TOP: f64 = 30.0;
+const BOTTOM: f64 = 30.0;
+const Y_TICKS: usize = 4;
+const X_TICKS: u64 = 5;
+
+/// What the y axis measures, for its labels
+#[derive(Debug, Clone, Copy)]
+pub enum Unit {
+    Hz,
+    Bytes(Units),
+    BytesPerSecond(Units),
+}
+
+impl Unit {
+    fn label(&self, value: f64) -> String {
+        match *self {
+            Unit::Hz => format::hz(value),
+            Unit::Bytes(units) => format::size(value, units),
+            Unit::BytesPerSecond(units) => format::bandwidth(value, units),
+        }
+    }
+}
+
+/// Rounds the top of the y axis up to 1, 2 or 5 times a power of ten
+fn nice_max(max: f64) -> f64 {
+    if max <= 0.0 || !max.is_finite() {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|step| step * magnitude)
+        .find(|candidate| *candidate >= max)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// A duration as the x axis shows it: "-30s", "-5m", "now"
+fn relative_label(ms: u64) -> String {
+    match ms {
+        0 => "now".to_string(),
+        ms if ms % 60_000 == 0 => format!("-{}m", ms / 60_000),
+        ms => format!("-{}s", ms / 1000),
+    }
+}
+
+fn open_svg(title: &str) -> String {
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">
+<rect width="100%" height="100%" fill="white"/>
+<text x="{cx}" y="18" text-anchor="middle" font-size="13" fill="#2c3e50">{title}</text>
+"##,
+        w = WIDTH,
+        h = HEIGHT,
+        cx = WIDTH / 2.0,
+        title = html_escape::encode_text(title),
+    )
+}
+
+/// Shown instead of a chart when the topic has no history, so embeds don't break
+pub fn no_data(title: &str) -> String {
+    let mut svg = open_svg(title);
+    svg.push_str(&format!(
+        r##"<text x="{}" y="{}" text-anchor="middle" font-size="14" fill="#7f8c8d">no data</text>
+</svg>
+"##,
+        WIDTH / 2.0,
+        HEIGHT / 2.0
+    ));
+    svg
+}
+
+/// Line chart of `(timestamp, value)` points over the `window_ms` ending at `now`
+pub fn render(title: &str, unit: Unit, points: &[(u64, f64)], window_ms: u64, now: u64) -> String {
+    let start = now.saturating_sub(window_ms);
+    let points: Vec<&(u64, f64)> = points.iter().filter(|(at, _)| *at >= start).collect();
+    if points.is_empty() {
+        return no_data(title);
+    }
+
+    let max = nice_max(points.iter().map(|(_, value)| *value).fold(0.0, f64::max));
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let x =
+        |at: u64| LEFT + (at.saturating_sub(start)) as f64 / window_ms.max(1) as f64 * plot_width;
+    let y = |value: f64| TOP + plot_height - value / max * plot_height;
+
+    let mut svg = open_svg(title);
+    for tick in 0..=Y_TICKS {
+        let value = max * tick as f64 / Y_TICKS as f64;
+        svg.push_str(&format!(
+            r##"<line x1="{LEFT}" x2="{x2}" y1="{y}" y2="{y}" stroke="#e8ecf0"/>
+<text x="{lx}" y="{ly}" text-anchor="end" fill="#7f8c8d">{label}</text>
+"##,
+            x2 = WIDTH - RIGHT,
+            y = y(value),
+            lx = LEFT - 6.0,
+            ly = y(value) + 4.0,
+            label = unit.label(value),
+        ));
+    }
+    for tick in 0..=X_TICKS {
+        let ago = window_ms - window_ms * tick / X_TICKS;
+        svg.push_str(&format!(
+            r##"<text x="{x}" y="{y}" text-anchor="middle" fill="#7f8c8d">{label}</text>
+"##,
+            x = x(now - ago),
+            y = HEIGHT - BOTTOM + 16.0,
+            label = relative_label(ago),
+        ));
+    }
+    let line: Vec<String> = points
+        .iter()
+        .map(|(at, value)| format!("{:.1},{:.1}", x(*at), y(*value)))
+        .collect();
+    svg.push_str(&format!(
+        r##"<polyline points="{}" fill="none" stroke="#3498db" stroke-width="1.5"/>
+</svg>
+"##,
+        line.join(" ")
+    ));
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_points_in_the_window_render_the_placeholder() {
+        let placeholder = no_data("robot/lidar rate");
+        assert!(placeholder.contains(">no data</text>"));
+        assert!(placeholder.contains(">robot/lidar rate</text>"));
+        assert!(placeholder.trim_end().ends_with("</svg>"));
+
+        assert_eq!(
+            render("robot/lidar rate", Unit::Hz, &[], 60_000, 100_000),
+            placeholder
+        );
+        // Points older than the window are left out
+        let stale = [(10_000, 5.0), (39_999, 5.0)];
+        assert_eq!(
+            render("robot/lidar rate", Unit::Hz, &stale, 60_000, 100_000),
+            placeholder
+        );
+    }
+
+    #[test]
+    fn points_in_the_window_draw_a_line() {
+        let points = [(39_999, 7.0), (40_000, 10.0), (100_000, 5.0)];
+        let svg = render("robot/lidar rate", Unit::Hz, &points, 60_000, 100_000);
+        assert!(!svg.contains("no data"));
+        // Only the two points in the window, from its left edge to its right
+        assert!(svg.contains(r#"<polyline points="70.0,30.0 625.0,120.0""#));
+        assert!(svg.contains(">-1m</text>") && svg.contains(">now</text>"));
+    }
+
+    #[test]
+    fn titles_are_escaped() {
+        let svg = no_data("<script>alert(1)</script> rate");
+        assert!(!svg.contains("<script>"));
+        assert!(svg.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn axis_tops_round_up_to_1_2_or_5() {
+        assert_eq!(nice_max(0.0), 1.0);
+        assert_eq!(nice_max(f64::NAN), 1.0);
+        assert_eq!(nice_max(7.0), 10.0);
+        assert_eq!(nice_max(10.0), 10.0);
+        assert_eq!(nice_max(12.0), 20.0);
+        assert_eq!(nice_max(0.3), 0.5);
+        assert_eq!(relative_label(90_000), "-90s");
+        assert_eq!(relative_label(600_000), "-10m");
+    }
+}
@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration, Instant};
+use zenoh::key_expr::keyexpr;
+
+use crate::alerts::{self, AlertEngine, AlertView};
+use crate::silences::Silences;
+use crate::*;
+
+/// Exit codes of `--check` mode
+pub const EXIT_PASSED: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_CONNECT_TIMEOUT: i32 = 3;
+
+/// How long to wait for the Zenoh subscriber before giving up
+const CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// Expected topics, loaded from the TOML manifest:
+///
+/// ```toml
+/// [[topic]]
+/// key_expr = "robot/*/lidar"
+/// hz = 10.0
+/// tolerance = 0.2
+///
+/// [[topic]]
+/// key_expr = "robot/*/camera/**"
+/// min_count = 2
+/// min_hz = 25.0
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    #[serde(default, rename = "topic")]
+    pub topics: Vec<Expectation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expectation {
+    pub key_expr: String,
+    /// Number of distinct topics that must match `key_expr`
+    #[serde(default = "default_min_count")]
+    pub min_count: usize,
+    /// Expected rate; each matching topic must be within `tolerance` (a fraction) of it
+    pub hz: Option<f64>,
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    pub min_hz: Option<f64>,
+    pub max_hz: Option<f64>,
+}
+
+fn default_min_count() -> usize {
+    1
+}
+
+fn default_tolerance() -> f64 {
+    0.2
+}
+
+impl Expectation {
+    /// The accepted rate range, combining `hz`/`tolerance` with the explicit bounds
+    fn rate_bounds(&self) -> (f64, f64) {
+        let (mut low, mut high) = match self.hz {
+            Some(hz) => (hz * (1.0 - self.tolerance), hz * (1.0 + self.tolerance)),
+            None => (0.0, f64::INFINITY),
+        };
+        if let Some(min_hz) = self.min_hz {
+            low = low.max(min_hz);
+        }
+        if let Some(max_hz) = self.max_hz {
+            high = high.min(max_hz);
+        }
+        (low, high)
+    }
+
+    /// The rate shown next to the measured one: `hz`, else the middle of the bounds
+    fn expected_hz(&self) -> Option<f64> {
+        self.hz.or(match (self.min_hz, self.max_hz) {
+            (Some(min_hz), Some(max_hz)) => Some((min_hz + max_hz) / 2.0),
+            (min_hz, max_hz) => min_hz.or(max_hz),
+        })
+    }
+}
+
+/// Keeps each topic's `expected_hz` and `hz_deviation_pct` up to date in the
+/// dashboard. The first manifest entry with a rate that matches a topic
+/// applies, so a wildcard entry sets the same expectation on every match.
+pub async fn run_rate_expectations(topic_cache: TopicCache, manifest: Manifest, period_ms: u64) {
+    // Resolved once per key; topics keep their key, so matches never change
+    let mut resolved: HashMap<String, Option<f64>> = HashMap::new();
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let mut cache = topic_cache.write().await;
+        resolved.retain(|key, _| cache.contains_key(key));
+        for topic in cache.values_mut() {
+            let expected_hz = *resolved.entry(topic.key_expr.clone()).or_insert_with(|| {
+                manifest
+                    .topics
+                    .iter()
+                    .filter(|expectation| {
+                        alerts::key_matches(&expectation.key_expr, &topic.key_expr)
+                    })
+                    .find_map(Expectation::expected_hz)
+            });
+            // Rounded so a rate wobbling in the last digits doesn't resend the row.
+            // A topic without a rate estimate yet isn't off its expected rate.
+            let deviation = expected_hz
+                .filter(|hz| *hz > 0.0 && topic.estimated_hz.is_some())
+                .map(|hz| {
+                    let pct = (alerts::effective_hz(topic, now) - hz) / hz * 100.0;
+                    (pct * 10.0).round() / 10.0
+                });
+            if topic.expected_hz != expected_hz || topic.hz_deviation_pct != deviation {
+                topic.expected_hz = expected_hz;
+                topic.hz_deviation_pct = deviation;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct MatchedTopic {
+    key_expr: String,
+    hz: f64,
+    passed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpectationResult {
+    #[serde(flatten)]
+    expectation: Expectation,
+    passed: bool,
+    matched: Vec<MatchedTopic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    passed: bool,
+    duration_s: u64,
+    topics_seen: usize,
+    expectations: Vec<ExpectationResult>,
+    firing_alerts: Vec<AlertView>,
+}
+
+pub fn load_manifest(path: &str) -> Result<Manifest, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let manifest: Manifest = toml::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+    for expectation in &manifest.topics {
+        keyexpr::new(expectation.key_expr.as_str())
+            .map_err(|e| format!("invalid key_expr '{}': {}", expectation.key_expr, e))?;
+    }
+    Ok(manifest)
+}
+
+/// Runs the subscriber for `duration_s`, then checks the manifest and alert rules.
+/// Prints a human-readable and a JSON report to stdout and returns the exit code.
+pub async fn run(
+    manifest: Manifest,
+    duration_s: u64,
+    config: Config,
+    session_config: zenoh::Config,
+) -> i32 {
+    let topic_cache: TopicCache = Arc::new(RwLock::new(HashMap::new()));
+    let session: ZenohSession = Arc::new(RwLock::new(None));
+    let status: SubscriberState = Arc::new(RwLock::new(SubscriberStatus::Connecting));
+    let mut engine = AlertEngine::new(
+        config.alert_rules,
+        config.composite_alert_rules,
+        config.alert_history_retention,
+        Silences::in_memory(),
+        config.instance_name,
+    );
+
+    // The same subscriber and stats pipeline as the dashboard, so results match.
+    // It runs until the process exits, so the shutdown signal is never sent.
+    let (_keep_running, shutdown) = tokio::sync::watch::channel(false);
+    let reload_period_ms = config.reload_period_ms;
+    tokio::spawn(run_subscriber_watchdog(
+        topic_cache.clone(),
+        Tombstones::default(),
+        session,
+        status.clone(),
+        Arc::new(tokio::sync::watch::channel(config.subscribe_key_expr.to_string()).0),
+        config.decoder_chain(),
+        DecodeLimiter::new(&config),
+        None,
+        Notifiers::start(&[], config.instance_name),
+        Arc::new(Config {
+            // `CONNECT_TIMEOUT_MS` bounds the wait instead
+            zenoh_open_attempts: None,
+            ..config
+        }),
+        session_config,
+        Arc::new(SystemClock),
+        shutdown,
+    ));
+
+    let connect_deadline = Instant::now() + Duration::from_millis(CONNECT_TIMEOUT_MS);
+    while !matches!(*status.read().await, SubscriberStatus::Connected { .. }) {
+        if Instant::now() >= connect_deadline {
+            println!(
+                "FAIL: could not connect to Zenoh within {}ms",
+                CONNECT_TIMEOUT_MS
+            );
+            return EXIT_CONNECT_TIMEOUT;
+        }
+        time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let end = Instant::now() + Duration::from_secs(duration_s);
+    let mut interval = time::interval(Duration::from_millis(reload_period_ms));
+    while Instant::now() < end {
+        interval.tick().await;
+        engine.evaluate(&*topic_cache.read().await, get_timestamp());
+    }
+
+    let now = get_timestamp();
+    let topics = topic_cache.read().await;
+    let expectations: Vec<ExpectationResult> = manifest
+        .topics
+        .into_iter()
+        .map(|expectation| check_expectation(expectation, &topics, now))
+        .collect();
+    let firing_alerts: Vec<AlertView> = engine
+        .active(now)
+        .into_iter()
+        .filter(|alert| alert.state.is_firing())
+        .collect();
+
+    let report = CheckReport {
+        passed: expectations.iter().all(|e| e.passed) && firing_alerts.is_empty(),
+        duration_s,
+        topics_seen: topics.len(),
+        expectations,
+        firing_alerts,
+    };
+    print_report(&report);
+
+    if report.passed {
+        EXIT_PASSED
+    } else {
+        EXIT_FAILED
+    }
+}
+
+fn check_expectation(expectation: Expectation, topics: &Topics, now: u64) -> ExpectationResult {
+    let (low, high) = expectation.rate_bounds();
+    let pattern = keyexpr::new(expectation.key_expr.as_str()).ok();
+
+    let mut matched: Vec<MatchedTopic> = topics
+        .values()
+        .filter(|topic| {
+            pattern.is_some_and(|pattern| {
+                keyexpr::new(topic.key_expr.as_str()).is_ok_and(|key| pattern.includes(key))
+            })
+        })
+        .map(|topic| {
+            let hz = alerts::effective_hz(topic, now);
+            MatchedTopic {
+                key_expr: topic.key_expr.clone(),
+                hz,
+                passed: hz >= low && hz <= high,
+            }
+        })
+        .collect();
+    matched.sort_by(|a, b| a.key_expr.cmp(&b.key_expr));
+
+    let reason = if matched.len() < expectation.min_count {
+        Some(format!(
+            "expected at least {} topic(s), saw {}",
+            expectation.min_count,
+            matched.len()
+        ))
+    } else if matched.iter().any(|m| !m.passed) {
+        Some(format!("rate outside {:.2}..{:.2} Hz", low, high))
+    } else {
+        None
+    };
+
+    ExpectationResult {
+        expectation,
+        passed: reason.is_none(),
+        matched,
+        reason,
+    }
+}
+
+fn print_report(report: &CheckReport) {
+    for result in &report.expectations {
+        println!(
+            "{} {} ({} topic(s))",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.expectation.key_expr,
+            result.matched.len()
+        );
+        for topic in &result.matched {
+            println!(
+                "    {} {} {:.2} Hz",
+                if topic.passed { "ok " } else { "bad" },
+                topic.key_expr,
+                topic.hz
+            );
+        }
+        if let Some(reason) = &result.reason {
+            println!("    {}", reason);
+        }
+    }
+    for alert in &report.firing_alerts {
+        println!(
+            "FAIL alert '{}' firing on {} (value {:.2})",
+            alert.rule.name(),
+            alert.key_expr,
+            alert.value
+        );
+    }
+    println!(
+        "{}: {} topic(s) seen in {}s",
+        if report.passed { "PASSED" } else { "FAILED" },
+        report.topics_seen,
+        report.duration_s
+    );
+
+    match serde_json::to_string(report) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Failed to serialise report: {}", e),
+    }
+}
@@ -0,0 +1,108 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::*;
+
+/// Command-line flags. Each overrides the constant named in its help, the
+/// settings file and the environment; the values are checked by
+/// `Config::validate` like the constants are.
+#[derive(Debug, Parser)]
+#[command(about = "Web dashboard of the topics on a Zenoh network")]
+pub struct Cli {
+    /// TOML settings file; otherwise config.toml here or monitor.toml beside
+    /// the binary is read, if present
+    #[arg(long = "config", value_name = "PATH")]
+    pub settings: Option<String>,
+    /// Run once without the web server, checking the topics against this manifest
+    #[arg(long, value_name = "MANIFEST")]
+    pub check: Option<String>,
+    /// How long `--check` collects stats before checking
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub duration: u64,
+    /// Drop samples with smaller payloads on arrival (MIN_PAYLOAD_BYTES)
+    #[arg(long, value_name = "BYTES")]
+    pub min_size: Option<u64>,
+    /// Show expected rates from this manifest (EXPECTED_TOPICS_MANIFEST)
+    #[arg(long, value_name = "MANIFEST")]
+    pub manifest: Option<String>,
+    /// Port of the web server (PORT)
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Zenoh locator to connect to, e.g. tcp/192.168.1.5:7447; repeat for
+    /// several (ZENOH_CONNECT_ENDPOINTS)
+    #[arg(long = "connect", visible_alias = "endpoint", value_name = "LOCATOR")]
+    pub endpoints: Vec<String>,
+    /// Key expression subscribed to at startup (SUBSCRIBE_KEY_EXPR)
+    #[arg(long)]
+    pub key_expr: Option<String>,
+    /// Zenoh mode (ZENOH_MODE)
+    #[arg(long, value_parser = ["peer", "client", "router"])]
+    pub mode: Option<String>,
+    /// Zenoh JSON5 configuration file, used as is; --connect and --mode are
+    /// then ignored
+    #[arg(long, value_name = "PATH")]
+    pub zenoh_config: Option<String>,
+}
+
+impl Cli {
+    /// Parses the command line, exiting on `--help` or an invalid flag. As
+    /// before clap, an invalid flag exits with `check::EXIT_USAGE` in check
+    /// mode and `EXIT_CONFIG` otherwise.
+    pub fn parse_or_exit() -> Self {
+        match Cli::try_parse() {
+            Ok(cli) => cli,
+            Err(e) if !e.use_stderr() => e.exit(),
+            Err(e) => {
+                let _ = e.print();
+                let checking = std::env::args().any(|arg| arg == "--check");
+                std::process::exit(if checking {
+                    check::EXIT_USAGE
+                } else {
+                    EXIT_CONFIG
+                });
+            }
+        }
+    }
+
+    /// The settings file named with `--config`, else the default one, if present
+    pub fn settings_file(&self) -> Option<PathBuf> {
+        match &self.settings {
+            Some(path) => Some(PathBuf::from(path)),
+            None => settings::default_path(),
+        }
+    }
+
+    /// Overrides `config` with the flags given
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(min_size) = self.min_size {
+            config.min_payload_bytes = min_size;
+        }
+        if let Some(path) = &self.manifest {
+            config.expected_topics_manifest = Some(path.clone());
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if !self.endpoints.is_empty() {
+            config.zenoh_connect_endpoints = leaked(&self.endpoints);
+        }
+        if let Some(key_expr) = &self.key_expr {
+            config.subscribe_key_expr = key_expr.clone().leak();
+        }
+        if let Some(mode) = &self.mode {
+            config.zenoh_mode = mode.clone().leak();
+        }
+        if let Some(path) = &self.zenoh_config {
+            config.zenoh_config_file = Some(path.clone());
+        }
+    }
+}
+
+/// `values` leaked to live as long as the process, like the constants they replace
+pub fn leaked(values: &[String]) -> &'static [&'static str] {
+    values
+        .iter()
+        .map(|value| &*value.clone().leak())
+        .collect::<Vec<_>>()
+        .leak()
+}
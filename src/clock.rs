@@ -0,0 +1,73 @@
+use std::sync::Arc;
+#[cfg(test)]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the subscriber's timestamps
+pub trait Clock: Send + Sync {
+    /// Wall-clock milliseconds since the epoch, for display and for comparing with other hosts
+    fn now_ms(&self) -> u64;
+    /// Nanoseconds on a clock that never steps, for measuring intervals. At
+    /// 1 kHz and above, whole milliseconds would round most intervals to 0 or 1.
+    fn monotonic_ns(&self) -> u64;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The system clocks, used in production
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        crate::get_timestamp()
+    }
+
+    fn monotonic_ns(&self) -> u64 {
+        crate::get_monotonic_ns()
+    }
+}
+
+/// A clock that only moves when told to, so a test can feed the rate math an
+/// exact sequence of arrival times and expect exact rates
+#[cfg(test)]
+pub struct ManualClock {
+    /// Nanoseconds since the epoch, so sub-millisecond steps add up
+    wall_ns: AtomicU64,
+    monotonic_ns: AtomicU64,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            wall_ns: AtomicU64::new(start_ms * 1_000_000),
+            monotonic_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the wall clock alone, like an NTP step; intervals are unaffected
+    pub fn set(&self, now_ms: u64) {
+        self.wall_ns.store(now_ms * 1_000_000, Ordering::Relaxed);
+    }
+
+    /// Lets `ms` pass on both clocks
+    pub fn advance(&self, ms: u64) {
+        self.advance_ns(ms * 1_000_000);
+    }
+
+    /// Lets `ns` pass on both clocks, e.g. 200 000 for one interval at 5 kHz
+    pub fn advance_ns(&self, ns: u64) {
+        self.wall_ns.fetch_add(ns, Ordering::Relaxed);
+        self.monotonic_ns.fetch_add(ns, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.wall_ns.load(Ordering::Relaxed) / 1_000_000
+    }
+
+    fn monotonic_ns(&self) -> u64 {
+        self.monotonic_ns.load(Ordering::Relaxed)
+    }
+}
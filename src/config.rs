@@ -0,0 +1,548 @@
+use serde::Serialize;
+
+use crate::alerts::{AlertRule, CompositeRule, HistoryRetention};
+use crate::audit::AuditLog;
+use crate::federation::Upstream;
+use crate::health::HealthThresholds;
+use crate::highlight::HighlightRule;
+use crate::notify::NotifierConfig;
+use crate::settings::Settings;
+use crate::timing::TimingThreshold;
+use crate::*;
+
+/// The settings in effect, logged at startup and served at `/api/config`.
+///
+/// Secrets (notifier URLs, upstream tokens) are redacted on serialisation, so this can be
+/// shown to anyone who can reach the web UI.
+#[derive(Debug, Serialize)]
+pub struct Config {
+    /// The TOML file the settings below were read from, if any
+    pub settings_file: Option<String>,
+    pub instance_name: Option<&'static str>,
+    pub branding: Branding,
+    pub port: u16,
+    pub grpc_port: Option<u16>,
+    pub base_path: Option<&'static str>,
+    pub log_level: &'static str,
+    pub zenoh_mode: &'static str,
+    pub zenoh_connect_endpoints: &'static [&'static str],
+    pub zenoh_open_attempts: Option<u32>,
+    pub zenoh_namespace: Option<&'static str>,
+    /// A native Zenoh JSON5 configuration used verbatim instead of the mode,
+    /// endpoints and namespace above
+    pub zenoh_config_file: Option<String>,
+    pub seed_query: Option<SeedQuery>,
+    pub subscribe_key_expr: &'static str,
+    pub reload_period_ms: u64,
+    pub prefix_group_depth: usize,
+    pub ui_columns: &'static [&'static str],
+    pub load_bar_metric: LoadMetric,
+    pub rate_display: RateDisplay,
+    pub default_sort: Sort,
+    pub sort_fields: &'static [SortField],
+    pub theme: Theme,
+    pub extra_css_path: Option<&'static str>,
+    pub key_display_max_chars: usize,
+    pub views_file: &'static str,
+    pub pins_file: &'static str,
+    pub display_units: format::Units,
+    pub hz_history: bool,
+    pub memory_limit_bytes: Option<usize>,
+    pub window_size: usize,
+    pub rate_window: RateWindow,
+    pub gap_factor: f64,
+    pub gap_max_interval_ms: Option<u64>,
+    pub decoder_enabled: bool,
+    /// Names of the decoders tried in order
+    pub decoders: Vec<&'static str>,
+    pub decode_concurrency: usize,
+    pub decode_cache_entries: usize,
+    pub keep_last_good_decode: bool,
+    pub measure_processing_latency: bool,
+    pub min_payload_bytes: u64,
+    pub source_names: &'static [(&'static str, &'static str)],
+    pub source_window_ms: u64,
+    pub expected_topics_manifest: Option<String>,
+    pub empty_payloads_count_toward_hz: bool,
+    pub duplicate_samples: DuplicateSamples,
+    pub sample_kinds: SampleKinds,
+    pub skip_repeated_payloads: bool,
+    pub stability_weights: StabilityWeights,
+    pub sse_seq_numbers: bool,
+    pub sse_heartbeat_ms: u64,
+    pub sse_decoded_event: bool,
+    pub watchdog_backoff_ms: (u64, u64),
+    pub highlight_rules: &'static [HighlightRule],
+    pub timing_thresholds: &'static [TimingThreshold],
+    pub timing_unit: format::TimeUnit,
+    pub alert_rules: &'static [AlertRule],
+    pub composite_alert_rules: &'static [CompositeRule],
+    pub alert_publish_prefix: Option<&'static str>,
+    pub publisher_liveliness: Option<&'static str>,
+    pub multi_publisher_keys: &'static [&'static str],
+    pub alert_republish_ms: u64,
+    pub alert_publish_debounce_ms: u64,
+    pub federation_upstreams: &'static [Upstream],
+    pub federation_poll_ms: u64,
+    pub federation_only: bool,
+    pub alert_history_retention: HistoryRetention,
+    pub health_summary: Option<HealthThresholds>,
+    pub silences_file: &'static str,
+    pub audit_log: Option<AuditLog>,
+    pub cache_dump_dir: &'static str,
+    pub notifiers: &'static [NotifierConfig],
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            settings_file: None,
+            instance_name: INSTANCE_NAME,
+            branding: BRANDING,
+            port: PORT,
+            grpc_port: GRPC_PORT,
+            base_path: BASE_PATH,
+            log_level: LOG_LEVEL.as_str(),
+            zenoh_mode: ZENOH_MODE,
+            zenoh_connect_endpoints: ZENOH_CONNECT_ENDPOINTS,
+            zenoh_open_attempts: ZENOH_OPEN_ATTEMPTS,
+            zenoh_namespace: ZENOH_NAMESPACE,
+            zenoh_config_file: None,
+            seed_query: SEED_QUERY,
+            subscribe_key_expr: SUBSCRIBE_KEY_EXPR,
+            reload_period_ms: RELOAD_PERIOD_MS,
+            prefix_group_depth: PREFIX_GROUP_DEPTH,
+            ui_columns: UI_COLUMNS,
+            load_bar_metric: LOAD_BAR_METRIC,
+            rate_display: RATE_DISPLAY,
+            default_sort: DEFAULT_SORT,
+            sort_fields: SORT_FIELDS,
+            theme: THEME,
+            extra_css_path: EXTRA_CSS_PATH,
+            key_display_max_chars: KEY_DISPLAY_MAX_CHARS,
+            views_file: VIEWS_FILE,
+            pins_file: PINS_FILE,
+            display_units: DISPLAY_UNITS,
+            hz_history: HZ_HISTORY,
+            memory_limit_bytes: MEMORY_LIMIT_BYTES,
+            window_size: WINDOW_SIZE,
+            rate_window: RATE_WINDOW,
+            gap_factor: GAP_FACTOR,
+            gap_max_interval_ms: GAP_MAX_INTERVAL_MS,
+            decoder_enabled: !DECODERS.is_empty(),
+            decoders: DECODERS.iter().map(|decoder| decoder.name).collect(),
+            decode_concurrency: DECODE_CONCURRENCY,
+            decode_cache_entries: DECODE_CACHE_ENTRIES,
+            keep_last_good_decode: KEEP_LAST_GOOD_DECODE,
+            measure_processing_latency: MEASURE_PROCESSING_LATENCY,
+            min_payload_bytes: MIN_PAYLOAD_BYTES,
+            source_names: SOURCE_NAMES,
+            source_window_ms: SOURCE_WINDOW_MS,
+            expected_topics_manifest: EXPECTED_TOPICS_MANIFEST.map(str::to_string),
+            empty_payloads_count_toward_hz: EMPTY_PAYLOADS_COUNT_TOWARD_HZ,
+            duplicate_samples: DUPLICATE_SAMPLES,
+            sample_kinds: SAMPLE_KINDS,
+            skip_repeated_payloads: SKIP_REPEATED_PAYLOADS,
+            stability_weights: STABILITY_WEIGHTS,
+            sse_seq_numbers: SSE_SEQ_NUMBERS,
+            sse_heartbeat_ms: SSE_HEARTBEAT_MS,
+            sse_decoded_event: SSE_DECODED_EVENT,
+            watchdog_backoff_ms: (WATCHDOG_MIN_BACKOFF_MS, WATCHDOG_MAX_BACKOFF_MS),
+            highlight_rules: HIGHLIGHT_RULES,
+            timing_thresholds: TIMING_THRESHOLDS,
+            timing_unit: TIMING_UNIT,
+            alert_rules: ALERT_RULES,
+            composite_alert_rules: COMPOSITE_ALERT_RULES,
+            alert_publish_prefix: ALERT_PUBLISH_PREFIX,
+            publisher_liveliness: PUBLISHER_LIVELINESS,
+            multi_publisher_keys: MULTI_PUBLISHER_KEYS,
+            alert_republish_ms: ALERT_REPUBLISH_MS,
+            alert_publish_debounce_ms: ALERT_PUBLISH_DEBOUNCE_MS,
+            federation_upstreams: FEDERATION_UPSTREAMS,
+            federation_poll_ms: FEDERATION_POLL_MS,
+            federation_only: FEDERATION_ONLY,
+            alert_history_retention: ALERT_HISTORY_RETENTION,
+            health_summary: HEALTH_SUMMARY,
+            silences_file: SILENCES_FILE,
+            audit_log: AUDIT_LOG,
+            cache_dump_dir: CACHE_DUMP_DIR,
+            notifiers: NOTIFIERS,
+        }
+    }
+}
+
+/// The configuration in effect: the constants, overridden by the settings file,
+/// then by the `ZTM_*` environment variables read through `env_var`, then by
+/// the command-line flags
+pub fn resolve(cli: &Cli, env_var: impl Fn(&str) -> Option<String>) -> Result<Config, String> {
+    let mut config = Config::default();
+    if let Some(path) = cli.settings_file() {
+        Settings::load(&path)?.apply(&mut config)?;
+        config.settings_file = Some(path.display().to_string());
+    }
+    Settings::from_env(env_var)?.apply(&mut config)?;
+    cli.apply(&mut config);
+    Ok(config)
+}
+
+impl Config {
+    /// Checks the settings that cannot be checked at compile time
+    pub fn validate(&self) -> Result<(), String> {
+        let columns = self
+            .ui_columns
+            .iter()
+            .map(|name| Column::parse(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !columns.contains(&Column::Key) {
+            return Err("UI columns must include 'key'".to_string());
+        }
+        // Shorter than this, an elided key no longer says which topic it is
+        if (1..8).contains(&self.key_display_max_chars) {
+            return Err("the key display length must be 0 (off) or at least 8".to_string());
+        }
+        for field in self.sort_fields.iter().chain([&self.default_sort.field]) {
+            if !columns.contains(&field.column()) {
+                return Err(format!(
+                    "cannot sort by '{}', it is not one of the UI columns",
+                    field.column().name()
+                ));
+            }
+        }
+        if !self.sort_fields.contains(&self.default_sort.field) {
+            return Err("the default sort field must be one of the sort fields".to_string());
+        }
+        // A factor of 1 or less would call every slower-than-average interval a gap
+        if !self.gap_factor.is_finite() || self.gap_factor <= 1.0 {
+            return Err("the gap factor must be greater than 1".to_string());
+        }
+        if self.gap_max_interval_ms == Some(0) {
+            return Err("the maximum interval before a gap must be above 0".to_string());
+        }
+        let weights = self.stability_weights;
+        if [weights.jitter, weights.flaps, weights.out_of_order]
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err("stability weights must be finite and non-negative".to_string());
+        }
+        if let Some(base_path) = self.base_path {
+            let valid = base_path.starts_with('/')
+                && !base_path.ends_with('/')
+                && base_path[1..].split('/').all(|segment| {
+                    !segment.is_empty()
+                        && segment
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || "-_.~".contains(c))
+                });
+            if !valid {
+                return Err(format!(
+                    "base path '{}' must look like '/monitor' or '/tools/monitor'",
+                    base_path
+                ));
+            }
+        }
+        self.branding.validate()?;
+        for rule in self.highlight_rules {
+            rule.validate()?;
+        }
+        for threshold in self.timing_thresholds {
+            threshold.validate()?;
+        }
+        // Deletes carry no payload, so any size threshold drops them all
+        if self.sample_kinds == SampleKinds::DeleteOnly && self.min_payload_bytes > 0 {
+            return Err(format!(
+                "sample kinds 'delete_only' record nothing with a minimum payload size of {} bytes",
+                self.min_payload_bytes
+            ));
+        }
+        if self.decoder_enabled && DECODERS.is_empty() {
+            return Err("decoder_enabled needs at least one decoder in DECODERS".to_string());
+        }
+        if self.reload_period_ms == 0 {
+            return Err("reload_period_ms must be at least 1".to_string());
+        }
+        if self.window_size == 0 {
+            return Err("window_size must be at least 1".to_string());
+        }
+        if self.sse_heartbeat_ms < self.reload_period_ms {
+            return Err("the SSE heartbeat period must be at least the reload period".to_string());
+        }
+        if self.grpc_port.is_some() && !cfg!(feature = "grpc") {
+            return Err("a gRPC port is set, but this build has no `grpc` feature".to_string());
+        }
+        if self.grpc_port == Some(self.port) {
+            return Err("the gRPC port must differ from the web server's".to_string());
+        }
+        if let Some(audit) = &self.audit_log {
+            audit.validate()?;
+        }
+        if self.cache_dump_dir.is_empty() {
+            return Err("the cache dump needs a directory".to_string());
+        }
+        if let Some(thresholds) = &self.health_summary {
+            thresholds.validate()?;
+        }
+        let mut upstream_names = HashSet::new();
+        for upstream in self.federation_upstreams {
+            upstream.validate()?;
+            if !upstream_names.insert(upstream.name) {
+                return Err(format!("upstream name '{}' is used twice", upstream.name));
+            }
+        }
+        if self.federation_only && self.federation_upstreams.is_empty() {
+            return Err("federation-only mode needs at least one upstream".to_string());
+        }
+        self.zenoh()?;
+        if let Some(seed) = &self.seed_query {
+            seed.validate()?;
+        }
+        if self.zenoh_open_attempts == Some(0) {
+            return Err("zenoh_open_attempts must be at least 1".to_string());
+        }
+        zenoh::key_expr::keyexpr::new(self.subscribe_key_expr).map_err(|e| {
+            format!(
+                "invalid subscribe key expression '{}': {}",
+                self.subscribe_key_expr, e
+            )
+        })?;
+        if let Some(prefix) = self.publisher_liveliness {
+            let valid = zenoh::key_expr::keyexpr::new(prefix).is_ok_and(|prefix| !prefix.is_wild());
+            if !valid {
+                return Err(format!(
+                    "invalid publisher liveliness prefix '{}': it must be a key expression without wildcards",
+                    prefix
+                ));
+            }
+        }
+        for key_expr in self.multi_publisher_keys {
+            zenoh::key_expr::keyexpr::new(*key_expr).map_err(|e| {
+                format!(
+                    "invalid multi-publisher key expression '{}': {}",
+                    key_expr, e
+                )
+            })?;
+        }
+        if let Some(path) = self.extra_css_path {
+            std::fs::metadata(path).map_err(|e| format!("extra CSS file {}: {}", path, e))?;
+        }
+        Ok(())
+    }
+
+    /// The decoders tried in order; none when decoding is disabled
+    pub fn decoder_chain(&self) -> &'static [NamedDecoder] {
+        if self.decoder_enabled { DECODERS } else { &[] }
+    }
+
+    /// `log_level` as a filter
+    pub fn log_filter(&self) -> LevelFilter {
+        LevelFilter::from_str(self.log_level).unwrap_or(LOG_LEVEL)
+    }
+
+    /// Smoothing factor for the rolling averages (payload size, decode error
+    /// and out-of-order fractions), matched to `window_size`
+    pub fn window_alpha(&self) -> f64 {
+        2.0 / (self.window_size as f64 + 1.0)
+    }
+
+    /// The Zenoh session configuration: the `zenoh_config_file` as written, or
+    /// else one built from the mode, endpoints and namespace
+    pub fn zenoh(&self) -> Result<zenoh::Config, String> {
+        match &self.zenoh_config_file {
+            Some(path) => zenoh::Config::from_file(path)
+                .map_err(|e| format!("invalid zenoh config file {}: {}", path, e)),
+            None => zenoh_config(
+                self.zenoh_mode,
+                self.zenoh_connect_endpoints,
+                self.zenoh_namespace,
+            ),
+        }
+    }
+
+    /// Where the Zenoh session connects, for logs and errors
+    pub fn zenoh_target(&self) -> String {
+        match &self.zenoh_config_file {
+            Some(path) => format!("the endpoints in {}", path),
+            None => format!(
+                "{} in {} mode",
+                self.zenoh_connect_endpoints.join(", "),
+                self.zenoh_mode
+            ),
+        }
+    }
+
+    /// The configured table columns; unknown names are skipped, see `validate`
+    pub fn columns(&self) -> Vec<Column> {
+        self.ui_columns
+            .iter()
+            .filter_map(|name| Column::parse(name).ok())
+            .collect()
+    }
+}
+
+/// Serialises a secret as a fixed placeholder so its presence is visible but not its value
+pub fn redacted<S: serde::Serializer>(_: &&str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("<redacted>")
+}
+
+pub fn redacted_option<S: serde::Serializer>(
+    _: &Option<&str>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("<redacted>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    /// A settings file unique to this test process
+    fn settings_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ztm-{}-{}.toml", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn resolved(args: &[&str], env: &[(&str, &str)]) -> Result<Config, String> {
+        let cli = Cli::parse_from(std::iter::once("monitor").chain(args.iter().copied()));
+        resolve(&cli, |name| {
+            env.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn cli_beats_env_beats_file_beats_default() {
+        let empty = settings_file("empty", "");
+        let file = settings_file(
+            "layers",
+            "port = 9090\nreload_period_ms = 500\nsubscribe_key_expr = \"file/**\"\n",
+        );
+        let (empty, file) = (empty.to_str().unwrap(), file.to_str().unwrap());
+
+        let config = resolved(&["--config", empty], &[]).unwrap();
+        assert_eq!(config.port, PORT);
+        assert_eq!(config.reload_period_ms, RELOAD_PERIOD_MS);
+
+        let config = resolved(&["--config", file], &[]).unwrap();
+        assert_eq!(config.port, 9090);
+        assert_eq!(config.subscribe_key_expr, "file/**");
+        assert_eq!(config.settings_file.as_deref(), Some(file));
+
+        let env = [("ZTM_PORT", "9191"), ("ZTM_KEY_EXPR", "env/**")];
+        let config = resolved(&["--config", file], &env).unwrap();
+        assert_eq!(config.port, 9191);
+        assert_eq!(config.subscribe_key_expr, "env/**");
+        // Layers only override what they set
+        assert_eq!(config.reload_period_ms, 500);
+
+        let config = resolved(&["--config", file, "--port", "9292"], &env).unwrap();
+        assert_eq!(config.port, 9292);
+        assert_eq!(config.subscribe_key_expr, "env/**");
+        assert_eq!(config.reload_period_ms, 500);
+    }
+
+    #[test]
+    fn env_lists_are_comma_separated() {
+        let empty = settings_file("lists", "");
+        let config = resolved(
+            &["--config", empty.to_str().unwrap()],
+            &[("ZTM_ENDPOINTS", " tcp/10.0.0.1:7447, ,tcp/10.0.0.2:7447 ")],
+        )
+        .unwrap();
+        assert_eq!(
+            config.zenoh_connect_endpoints,
+            ["tcp/10.0.0.1:7447", "tcp/10.0.0.2:7447"]
+        );
+    }
+
+    #[test]
+    fn every_startup_flag_has_a_variable_and_a_key() {
+        let file = settings_file(
+            "flags",
+            "min_payload_bytes = 16\nexpected_topics_manifest = \"file.toml\"\n\
+             zenoh_config_file = \"file.json5\"\n",
+        );
+        let file = file.to_str().unwrap();
+
+        let config = resolved(&["--config", file], &[]).unwrap();
+        assert_eq!(config.min_payload_bytes, 16);
+        assert_eq!(
+            config.expected_topics_manifest.as_deref(),
+            Some("file.toml")
+        );
+        assert_eq!(config.zenoh_config_file.as_deref(), Some("file.json5"));
+
+        let env = [
+            ("ZTM_MIN_SIZE", "32"),
+            ("ZTM_MANIFEST", "env.toml"),
+            ("ZTM_ZENOH_CONFIG", "env.json5"),
+        ];
+        let config = resolved(&["--config", file], &env).unwrap();
+        assert_eq!(config.min_payload_bytes, 32);
+        assert_eq!(config.expected_topics_manifest.as_deref(), Some("env.toml"));
+        assert_eq!(config.zenoh_config_file.as_deref(), Some("env.json5"));
+
+        let flags = [
+            "--config",
+            file,
+            "--min-size",
+            "64",
+            "--manifest",
+            "flag.toml",
+            "--zenoh-config",
+            "flag.json5",
+        ];
+        let config = resolved(&flags, &env).unwrap();
+        assert_eq!(config.min_payload_bytes, 64);
+        assert_eq!(
+            config.expected_topics_manifest.as_deref(),
+            Some("flag.toml")
+        );
+        assert_eq!(config.zenoh_config_file.as_deref(), Some("flag.json5"));
+    }
+
+    #[test]
+    fn bad_env_values_name_the_variable() {
+        let error = |name: &str, value: &str| {
+            Settings::from_env(|var| (var == name).then(|| value.to_string())).unwrap_err()
+        };
+        assert_eq!(
+            error("ZTM_PORT", "eighty"),
+            "invalid ZTM_PORT 'eighty': invalid digit found in string"
+        );
+        assert_eq!(
+            error("ZTM_PORT", "70000"),
+            "invalid ZTM_PORT '70000': number too large to fit in target type"
+        );
+        assert_eq!(
+            error("ZTM_DECODER_ENABLED", "yes"),
+            "invalid ZTM_DECODER_ENABLED 'yes': provided string was not `true` or `false`"
+        );
+        assert_eq!(
+            error("ZTM_MIN_SIZE", "-1"),
+            "invalid ZTM_MIN_SIZE '-1': invalid digit found in string"
+        );
+
+        let empty = settings_file("errors", "");
+        assert_eq!(
+            resolved(
+                &["--config", empty.to_str().unwrap()],
+                &[("ZTM_PORT", "eighty")]
+            )
+            .unwrap_err(),
+            "invalid ZTM_PORT 'eighty': invalid digit found in string"
+        );
+        assert_eq!(
+            resolved(
+                &["--config", empty.to_str().unwrap()],
+                &[("ZTM_LOG_LEVEL", "loud")]
+            )
+            .unwrap_err(),
+            "invalid log_level 'loud', expected off, error, warn, info, debug or trace"
+        );
+    }
+}
@@ -2,30 +2,89 @@ use log::{error, warn};
 use msg_utils::get_decode_handler;
 use zenoh::sample::Sample;
 
+use crate::DecoderFn;
+
+/// Payload bytes shown by `hex_decoder` before the output is truncated
+const HEX_PREVIEW_BYTES: usize = 64;
+
+/// A decoder in the fallback chain; the name is reported for the one that matched
+#[derive(Debug, Clone, Copy)]
+pub struct NamedDecoder {
+    pub name: &'static str,
+    pub decode: DecoderFn,
+}
+
+impl NamedDecoder {
+    pub const fn new(name: &'static str, decode: DecoderFn) -> Self {
+        Self { name, decode }
+    }
+}
+
+/// Tries each decoder in order and returns the first success with the name of
+/// the decoder that produced it. If every decoder fails, the error lists each
+/// decoder's reason.
+pub fn decode_chain(
+    chain: &[NamedDecoder],
+    sample: Sample,
+) -> Result<(&'static str, String), String> {
+    let mut errors = Vec::new();
+    for decoder in chain {
+        match (decoder.decode)(sample.clone()) {
+            Ok(text) => return Ok((decoder.name, text)),
+            Err(e) => errors.push(format!("{}: {}", decoder.name, e)),
+        }
+    }
+    Err(errors.join("; "))
+}
+
 /// A decoder function to convert the sample into a human-readable string
 ///
 /// # Arguments
 /// * `sample` - The sample to be decoded
 ///
 /// # Returns
-/// A human-readable string representation of the sample
+/// A human-readable string representation of the sample, or a description of
+/// why it could not be decoded
 #[allow(dead_code)]
-pub fn flatbuffer_decoder(sample: Sample) -> String {
+pub fn flatbuffer_decoder(sample: Sample) -> Result<String, String> {
     let payload_bytes = sample.payload().to_bytes().into_owned();
     let key_str = format!("{}", sample.key_expr());
-    let s: String;
 
     if let Some(decode_fn) = get_decode_handler(&key_str) {
         match decode_fn(payload_bytes) {
-            Ok(decoded_msg) => s = format!("{:?}", decoded_msg),
+            Ok(decoded_msg) => Ok(format!("{:?}", decoded_msg)),
             Err(err) => {
                 error!("Error decoding message on {}: {}", key_str, err);
-                s = format!("Error decoding message on {}: {}", key_str, err);
+                Err(format!("Error decoding message on {}: {}", key_str, err))
             }
         }
     } else {
         warn!("No handler found for message on {}", key_str);
-        s = format!("No handler found for message on {}", key_str);
+        Err(format!("No handler found for message on {}", key_str))
+    }
+}
+
+/// Decodes JSON payloads into their compact form
+#[allow(dead_code)]
+pub fn json_decoder(sample: Sample) -> Result<String, String> {
+    let payload = sample.payload().to_bytes();
+    serde_json::from_slice::<serde_json::Value>(&payload)
+        .map(|value| value.to_string())
+        .map_err(|e| format!("Not JSON: {}", e))
+}
+
+/// Shows the start of the payload as hex; never fails, so it suits the end of a chain
+#[allow(dead_code)]
+pub fn hex_decoder(sample: Sample) -> Result<String, String> {
+    let payload = sample.payload().to_bytes();
+    let hex: Vec<String> = payload
+        .iter()
+        .take(HEX_PREVIEW_BYTES)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if payload.len() > HEX_PREVIEW_BYTES {
+        Ok(format!("{} … ({} bytes)", hex.join(" "), payload.len()))
+    } else {
+        Ok(hex.join(" "))
     }
-    s
 }
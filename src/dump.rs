@@ -0,0 +1,82 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::*;
+
+#[derive(Serialize)]
+struct Dump<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'static str>,
+    at: u64,
+    /// `at` as UTC, for reading the file by eye
+    at_utc: String,
+    topic_count: usize,
+    /// Every cached topic as `/api/topics` lists it, sorted by key
+    topics: Vec<&'a TopicData>,
+}
+
+/// Writes every cached topic to a new `cache-<UTC time>.json` in `dir`,
+/// creating it if needed, and returns the file's path. The cache is only
+/// locked while it is serialised.
+pub async fn write(
+    cache: &TopicCache,
+    dir: &str,
+    instance: Option<&'static str>,
+) -> Result<PathBuf, String> {
+    let now = get_timestamp();
+    let json = {
+        let topics = cache.read().await;
+        let mut entries: Vec<&TopicData> = topics.values().map(|topic| &topic.data).collect();
+        entries.sort_by(|a, b| a.key_expr.cmp(&b.key_expr));
+        serde_json::to_vec_pretty(&Dump {
+            instance,
+            at: now,
+            at_utc: format::timestamp(now),
+            topic_count: entries.len(),
+            topics: entries,
+        })
+        .map_err(|e| format!("failed to serialise the cache: {}", e))?
+    };
+
+    let file_time = chrono::DateTime::from_timestamp_millis(now as i64)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%S%.3fZ");
+    let path = Path::new(dir).join(format!("cache-{}.json", file_time));
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("failed to create {}: {}", dir, e))?;
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Dumps the cache to `dir` on every SIGUSR1, for capturing state during an
+/// incident with `kill -USR1 <pid>`
+#[cfg(unix)]
+pub async fn run_dump_on_signal(
+    cache: TopicCache,
+    dir: &'static str,
+    instance: Option<&'static str>,
+) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut signals = match signal(SignalKind::user_defined1()) {
+        Ok(signals) => signals,
+        Err(e) => {
+            warn!(
+                "Failed to listen for SIGUSR1, cache dumps are HTTP only: {}",
+                e
+            );
+            return;
+        }
+    };
+    info!("Dumping the cache to {} on SIGUSR1", dir);
+    while signals.recv().await.is_some() {
+        match write(&cache, dir, instance).await {
+            Ok(path) => info!("Wrote cache dump to {}", path.display()),
+            Err(e) => warn!("Cache dump failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration};
+
+use crate::config::redacted_option;
+use crate::*;
+
+/// Upstream requests that take longer than this count as failed
+const HTTP_TIMEOUT_MS: u64 = 5000;
+/// Longest wait between attempts to reach an unreachable upstream
+const MAX_BACKOFF_MS: u64 = 60_000;
+/// Clock offset changes smaller than this are ignored, so network jitter
+/// doesn't shift every federated timestamp (and resend every row) each poll
+const SKEW_TOLERANCE_MS: i64 = 500;
+/// Header carrying the server clock on `/api/topics` responses
+pub const SERVER_TIME_HEADER: &str = "x-server-time";
+
+/// Another monitor whose topics are merged into this one
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Upstream {
+    /// Instance label; its topics appear under `<name>/<key>`
+    pub name: &'static str,
+    /// Base URL of the monitor, including any base path, e.g. `http://vehicle1:8080`
+    pub url: &'static str,
+    /// Sent as `Authorization: Bearer <token>`, e.g. to a reverse proxy in front of it
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "redacted_option"
+    )]
+    pub token: Option<&'static str>,
+}
+
+impl Upstream {
+    pub fn validate(&self) -> Result<(), String> {
+        // The name becomes the first segment of every key from this upstream
+        if self.name.is_empty() || self.name.contains(['/', '*', '$', '?', '#']) {
+            return Err(format!(
+                "upstream name '{}' must be a single key segment",
+                self.name
+            ));
+        }
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(format!(
+                "upstream '{}' URL '{}' must start with http:// or https://",
+                self.name, self.url
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Connection state of one upstream, as reported by `/api/federation`
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamStatus {
+    pub name: &'static str,
+    pub url: &'static str,
+    pub connected: bool,
+    pub last_success: Option<u64>,
+    pub last_error: Option<String>,
+    /// Added to the upstream's timestamps to bring them onto this clock
+    pub clock_offset_ms: i64,
+    pub topics: usize,
+}
+
+pub type FederationState = Arc<RwLock<Vec<UpstreamStatus>>>;
+
+pub fn new_state(upstreams: &[Upstream]) -> FederationState {
+    Arc::new(RwLock::new(
+        upstreams
+            .iter()
+            .map(|upstream| UpstreamStatus {
+                name: upstream.name,
+                url: upstream.url,
+                connected: false,
+                last_success: None,
+                last_error: None,
+                clock_offset_ms: 0,
+                topics: 0,
+            })
+            .collect(),
+    ))
+}
+
+/// Fetches the upstream's topics and the offset between its clock and ours.
+/// The offset is measured against the middle of the request, so half the
+/// round trip is not mistaken for skew.
+async fn fetch(
+    client: &reqwest::Client,
+    upstream: &Upstream,
+) -> Result<(Vec<TopicData>, Option<i64>), String> {
+    let mut request = client.get(format!("{}/api/topics", upstream.url.trim_end_matches('/')));
+    if let Some(token) = upstream.token {
+        request = request.bearer_auth(token);
+    }
+    let sent = get_timestamp();
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let received = get_timestamp();
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    // Older monitors don't send their clock; their timestamps are taken as is
+    let offset = response
+        .headers()
+        .get(SERVER_TIME_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|remote| (sent / 2 + received / 2) as i64 - remote as i64);
+    let topics = response
+        .json::<Vec<TopicData>>()
+        .await
+        .map_err(|e| format!("invalid topics: {}", e))?;
+    Ok((topics, offset))
+}
+
+/// Replaces the upstream's topics in the cache with `topics`.
+///
+/// Keys are prefixed with the upstream's name, so the same key on two vehicles
+/// stays two rows. A local topic already holding a prefixed key wins, with a
+/// warning the first time. Unchanged topics are not rewritten, so they are not
+/// resent over SSE.
+async fn merge(
+    cache: &TopicCache,
+    tombstones: &Tombstones,
+    upstream: &Upstream,
+    topics: Vec<TopicData>,
+    offset_ms: i64,
+    collisions: &mut HashSet<String>,
+) -> usize {
+    let mut cache = cache.write().await;
+    let mut seen = HashSet::new();
+    for mut topic in topics {
+        let key = format!("{}/{}", upstream.name, topic.key_expr);
+        // A pinned placeholder is not a local topic and gives way
+        if cache
+            .get(&key)
+            .is_some_and(|existing| existing.instance.is_none() && !existing.is_placeholder())
+        {
+            if collisions.insert(key.clone()) {
+                warn!(
+                    "Federated topic '{}' collides with a local topic, showing the local one",
+                    key
+                );
+            }
+            continue;
+        }
+        topic.key_expr = key.clone();
+        topic.instance = Some(upstream.name.to_string());
+        // Decoded content is served as markup. Re-escaping what the upstream
+        // escaped keeps it as it was, and whatever it didn't escape becomes inert.
+        topic.decoded_content = topic
+            .decoded_content
+            .map(|content| html_escape_string(&html_escape::decode_html_entities(&content)));
+        if !topic.is_placeholder() {
+            topic.received_timestamp = topic.received_timestamp.saturating_add_signed(offset_ms);
+        }
+        // Pins belong to this monitor, not to the upstream
+        topic.pinned = cache.get(&key).is_some_and(|existing| existing.pinned);
+        seen.insert(key.clone());
+        if cache.get(&key).map(|existing| &existing.data) != Some(&topic) {
+            cache.insert(key, topic.into());
+        }
+    }
+    retain_topics(&mut cache, &mut *tombstones.write().await, |key, topic| {
+        topic.instance.as_deref() != Some(upstream.name) || topic.pinned || seen.contains(key)
+    });
+    seen.len()
+}
+
+/// Polls one upstream every `poll_ms`, backing off while it is unreachable.
+/// Its topics are kept while it is down, so they go stale on the page instead
+/// of disappearing.
+pub async fn run_upstream(
+    cache: TopicCache,
+    tombstones: Tombstones,
+    state: FederationState,
+    index: usize,
+    upstream: Upstream,
+    poll_ms: u64,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(HTTP_TIMEOUT_MS))
+        .build()
+        .unwrap_or_else(|e| {
+            warn!("Failed to build HTTP client, using defaults: {}", e);
+            reqwest::Client::new()
+        });
+    let mut offset_ms = 0;
+    let mut collisions = HashSet::new();
+    let mut delay_ms = poll_ms;
+    loop {
+        match fetch(&client, &upstream).await {
+            Ok((topics, measured)) => {
+                if let Some(measured) = measured
+                    && (measured - offset_ms).abs() > SKEW_TOLERANCE_MS
+                {
+                    info!(
+                        "Clock offset to upstream '{}' is now {} ms",
+                        upstream.name, measured
+                    );
+                    offset_ms = measured;
+                }
+                let count = merge(
+                    &cache,
+                    &tombstones,
+                    &upstream,
+                    topics,
+                    offset_ms,
+                    &mut collisions,
+                )
+                .await;
+                let mut state = state.write().await;
+                let status = &mut state[index];
+                if !status.connected {
+                    info!(
+                        "Connected to upstream '{}' ({})",
+                        upstream.name, upstream.url
+                    );
+                }
+                status.connected = true;
+                status.last_success = Some(get_timestamp());
+                status.clock_offset_ms = offset_ms;
+                status.topics = count;
+                delay_ms = poll_ms;
+            }
+            Err(e) => {
+                let mut state = state.write().await;
+                let status = &mut state[index];
+                if status.connected || status.last_error.is_none() {
+                    warn!(
+                        "Lost upstream '{}' ({}): {}",
+                        upstream.name, upstream.url, e
+                    );
+                }
+                status.connected = false;
+                status.last_error = Some(e);
+                delay_ms = (delay_ms * 2).min(MAX_BACKOFF_MS.max(poll_ms));
+            }
+        }
+        time::sleep(Duration::from_millis(delay_ms)).await;
+    }
+}
@@ -0,0 +1,236 @@
+use serde::Serialize;
+
+/// Unit family for byte sizes and bandwidths
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Units {
+    /// Powers of 1000: kB, MB, GB
+    Si,
+    /// Powers of 1024: KiB, MiB, GiB
+    Binary,
+}
+
+impl Units {
+    fn base(&self) -> f64 {
+        match self {
+            Units::Si => 1000.0,
+            Units::Binary => 1024.0,
+        }
+    }
+
+    fn prefixes(&self) -> &'static [&'static str] {
+        match self {
+            Units::Si => &["B", "kB", "MB", "GB", "TB"],
+            Units::Binary => &["B", "KiB", "MiB", "GiB", "TiB"],
+        }
+    }
+}
+
+/// Rounds to `decimals` places the same way `format!` will print it
+fn round_to(value: f64, decimals: usize) -> f64 {
+    format!("{:.*}", decimals, value).parse().unwrap_or(value)
+}
+
+/// A rate rounded for the cache and the wire: 2 decimals, or 3 significant
+/// figures below 1 Hz, so float noise never makes a steady rate look changed
+pub fn round_hz(hz: f64) -> f64 {
+    if !hz.is_finite() || hz <= 0.0 {
+        return hz;
+    }
+    if hz >= 1.0 {
+        return round_to(hz, 2);
+    }
+    let decimals = 2 - hz.log10().floor() as i32;
+    round_to(hz, decimals as usize)
+}
+
+/// Serialises a float rounded to 2 decimals, for sizes and statistics whose
+/// full precision would only make every update longer
+pub fn serialize_rounded<S: serde::Serializer>(
+    value: &f64,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, 2))
+}
+
+/// Scales `value` to the largest unit that keeps it below the base once rounded,
+/// so 999_960 bytes prints as "1.0 MB" rather than "1000.0 kB"
+fn scaled(value: f64, units: Units, suffix: &str) -> String {
+    if !value.is_finite() || value < 0.0 {
+        return "-".to_string();
+    }
+    let prefixes = units.prefixes();
+    let mut scaled = value;
+    for (index, prefix) in prefixes.iter().enumerate() {
+        // Whole bytes need no decimals
+        let decimals = if index == 0 { 0 } else { 1 };
+        if round_to(scaled, decimals) < units.base() || index + 1 == prefixes.len() {
+            return format!("{:.*} {}{}", decimals, scaled, prefix, suffix);
+        }
+        scaled /= units.base();
+    }
+    unreachable!("the last prefix always returns")
+}
+
+/// A payload size, e.g. "512 B" or "1.4 MB"
+pub fn size(bytes: f64, units: Units) -> String {
+    scaled(bytes, units, "")
+}
+
+/// A bandwidth, e.g. "12.5 kB/s"
+pub fn bandwidth(bytes_per_second: f64, units: Units) -> String {
+    scaled(bytes_per_second, units, "/s")
+}
+
+/// The rate of a topic with too few samples to estimate one: "– (1 msg)"
+pub fn hz_pending(message_count: u64) -> String {
+    match message_count {
+        0 => "–".to_string(),
+        1 => "– (1 msg)".to_string(),
+        count => format!("– ({} msgs)", count),
+    }
+}
+
+/// A rate with precision that shrinks as it grows: "0.98 Hz", "118.2 Hz", "2400 Hz"
+pub fn hz(hz: f64) -> String {
+    if !hz.is_finite() || hz < 0.0 {
+        return "-".to_string();
+    }
+    if hz == 0.0 {
+        return "0 Hz".to_string();
+    }
+    let decimals = if round_to(hz, 2) < 10.0 {
+        2
+    } else if round_to(hz, 1) < 1000.0 {
+        1
+    } else {
+        0
+    };
+    format!("{:.*} Hz", decimals, hz)
+}
+
+/// Unit of the jitter and latency display strings
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    Ms,
+    Us,
+}
+
+/// A duration given in milliseconds, e.g. "3.2 ms" or "3200 µs"
+pub fn duration(ms: f64, unit: TimeUnit) -> String {
+    if !ms.is_finite() || ms < 0.0 {
+        return "-".to_string();
+    }
+    match unit {
+        TimeUnit::Ms => format!("{:.1} ms", ms),
+        TimeUnit::Us => format!("{:.0} µs", ms * 1000.0),
+    }
+}
+
+/// A time given in milliseconds since the epoch, as the page shows it, e.g.
+/// "2024-05-01 12:34:56.789 UTC"
+pub fn timestamp(ms: u64) -> String {
+    let days = (ms / 86_400_000) as i64;
+    let of_day = ms % 86_400_000;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03} UTC",
+        year,
+        month,
+        day,
+        of_day / 3_600_000,
+        of_day / 60_000 % 60,
+        of_day / 1000 % 60,
+        of_day % 1000
+    )
+}
+
+/// Time since a sample, as the page's age column shows it, e.g. "4.2 s ago" or "3 m ago"
+pub fn age(ms: u64) -> String {
+    if ms < 10_000 {
+        format!("{:.1} s ago", ms as f64 / 1000.0)
+    } else if ms < 60_000 {
+        format!("{} s ago", ms / 1000)
+    } else if ms < 3_600_000 {
+        format!("{} m ago", ms / 60_000)
+    } else {
+        format!("{} h ago", ms / 3_600_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_roll_over_to_the_next_unit_once_rounded() {
+        assert_eq!(size(0.0, Units::Si), "0 B");
+        assert_eq!(size(999.0, Units::Si), "999 B");
+        assert_eq!(size(1000.0, Units::Si), "1.0 kB");
+        assert_eq!(size(999_949.0, Units::Si), "999.9 kB");
+        assert_eq!(size(999_960.0, Units::Si), "1.0 MB");
+        assert_eq!(size(1023.0, Units::Binary), "1023 B");
+        assert_eq!(size(1024.0, Units::Binary), "1.0 KiB");
+        // Beyond the last prefix the value just grows
+        assert_eq!(size(2e15, Units::Si), "2000.0 TB");
+        assert_eq!(bandwidth(1000.0, Units::Si), "1.0 kB/s");
+    }
+
+    #[test]
+    fn rates_lose_decimals_as_they_grow() {
+        assert_eq!(hz(0.0), "0 Hz");
+        assert_eq!(hz(9.994), "9.99 Hz");
+        assert_eq!(hz(9.996), "10.0 Hz");
+        assert_eq!(hz(999.94), "999.9 Hz");
+        assert_eq!(hz(999.96), "1000 Hz");
+        assert_eq!(round_hz(0.0), 0.0);
+        assert_eq!(round_hz(0.012345), 0.0123);
+        assert_eq!(round_hz(123.456), 123.46);
+    }
+
+    #[test]
+    fn non_finite_and_negative_values_print_as_a_dash() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1.0] {
+            assert_eq!(size(value, Units::Si), "-");
+            assert_eq!(bandwidth(value, Units::Binary), "-");
+            assert_eq!(hz(value), "-");
+            assert_eq!(duration(value, TimeUnit::Ms), "-");
+        }
+        assert!(round_hz(f64::NAN).is_nan());
+        assert_eq!(round_hz(f64::INFINITY), f64::INFINITY);
+    }
+
+    #[test]
+    fn durations_ages_and_timestamps() {
+        assert_eq!(duration(0.0, TimeUnit::Ms), "0.0 ms");
+        assert_eq!(duration(3.2, TimeUnit::Us), "3200 µs");
+        assert_eq!(age(0), "0.0 s ago");
+        assert_eq!(age(9_949), "9.9 s ago");
+        assert_eq!(age(10_000), "10 s ago");
+        assert_eq!(age(59_999), "59 s ago");
+        assert_eq!(age(60_000), "1 m ago");
+        assert_eq!(age(3_600_000), "1 h ago");
+        assert_eq!(timestamp(0), "1970-01-01 00:00:00.000 UTC");
+        assert_eq!(timestamp(1_709_210_096_789), "2024-02-29 12:34:56.789 UTC");
+        assert_eq!(hz_pending(0), "–");
+        assert_eq!(hz_pending(1), "– (1 msg)");
+        assert_eq!(hz_pending(2), "– (2 msgs)");
+    }
+}
@@ -0,0 +1,135 @@
+use futures::Stream;
+use log::{error, info};
+use std::pin::Pin;
+use tonic::{Request, Response, Status};
+
+use crate::*;
+
+pub mod proto {
+    tonic::include_proto!("zenoh_topic_monitor");
+}
+
+use proto::topic_monitor_server::{TopicMonitor, TopicMonitorServer};
+
+impl From<TopicData> for proto::TopicData {
+    fn from(topic: TopicData) -> Self {
+        Self {
+            key_expr: topic.key_expr,
+            last_data_size_bytes: topic.last_data_size_bytes,
+            received_timestamp: topic.received_timestamp,
+            decoded_content: topic.decoded_content,
+            decoded_by: topic.decoded_by,
+            estimated_hz: topic.estimated_hz,
+            message_count: topic.message_count,
+            avg_data_size_bytes: topic.avg_data_size_bytes,
+            size_delta_bytes: topic.size_delta_bytes,
+            size_delta_pct: topic.size_delta_pct,
+            empty_payload: topic.empty_payload,
+            decode_errors: topic.decode_errors,
+            duplicate_samples: topic.duplicate_samples,
+            out_of_order_samples: topic.out_of_order_samples,
+            stability: u32::from(topic.stability),
+            jitter_ms: topic.jitter_ms,
+            latency_ms: topic.latency_ms,
+            last_source: topic.last_source,
+            source_count: u32::from(topic.source_count),
+            publisher_count: topic.publisher_count,
+            publisher_conflict: topic.publisher_conflict,
+            instance: topic.instance,
+            identical_repeat_count: topic.identical_repeat_count,
+            distinct_payloads: topic.distinct_payloads,
+            gap_count: topic.gap_count,
+            expected_hz: topic.expected_hz,
+            hz_deviation_pct: topic.hz_deviation_pct,
+            highlight: topic.highlight,
+            pinned: topic.pinned,
+            size_display: topic.size_display,
+            hz_display: topic.hz_display,
+            bandwidth_display: topic.bandwidth_display,
+        }
+    }
+}
+
+impl From<DeltaUpdate> for proto::DeltaUpdate {
+    fn from(delta: DeltaUpdate) -> Self {
+        Self {
+            seq: delta.seq,
+            updated: delta.updated.into_iter().map(Into::into).collect(),
+            added: delta.added.into_iter().map(Into::into).collect(),
+            reappeared: delta.reappeared,
+            removed: delta.removed,
+            server_time: delta.server_time,
+            ages: delta.ages.into_iter().collect(),
+            hz_history: delta
+                .hz_history
+                .into_iter()
+                .map(|(key, points)| (key, proto::Sparkline { points }))
+                .collect(),
+            throughput: delta.throughput.map(|latest| proto::Throughput {
+                at: latest.point.at,
+                messages_per_sec: latest.point.messages_per_sec,
+                bytes_per_sec: latest.point.bytes_per_sec,
+                topics: latest.point.topics,
+                rate_display: latest.rate_display,
+                bandwidth_display: latest.bandwidth_display,
+            }),
+        }
+    }
+}
+
+struct Service {
+    sources: DeltaSources,
+    shutdown: ShutdownSignal,
+}
+
+#[tonic::async_trait]
+impl TopicMonitor for Service {
+    type StreamDeltasStream =
+        Pin<Box<dyn Stream<Item = Result<proto::DeltaUpdate, Status>> + Send + 'static>>;
+
+    /// The same deltas as `/sse`, at most one every `Config::reload_period_ms`
+    async fn stream_deltas(
+        &self,
+        _request: Request<proto::StreamDeltasRequest>,
+    ) -> Result<Response<Self::StreamDeltasStream>, Status> {
+        let ticks = self.sources.ticks();
+        let stream = futures::stream::unfold(
+            (
+                DeltaTracker::new(self.sources.clone()),
+                ticks,
+                self.shutdown.clone(),
+            ),
+            |(mut tracker, mut ticks, mut shutdown)| async move {
+                // Unchanged ticks send nothing; HTTP/2 keeps the stream alive.
+                // At the shutdown signal the stream ends, so the server can stop.
+                let delta = loop {
+                    tokio::select! {
+                        changed = ticks.changed() => changed.ok()?,
+                        () = crate::shutdown::stopping(&mut shutdown) => return None,
+                    }
+                    let current = ticks.borrow_and_update().clone();
+                    if let Some(delta) = tracker.next(current).await {
+                        break delta;
+                    }
+                };
+                Some((Ok(delta.into()), (tracker, ticks, shutdown)))
+            },
+        );
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Serves the `TopicMonitor` gRPC service on `port` until the shutdown signal
+pub async fn serve(sources: DeltaSources, port: u16, shutdown: ShutdownSignal) {
+    info!("Starting gRPC server on 127.0.0.1:{}", port);
+    let mut stopping = shutdown.clone();
+    let result = tonic::transport::Server::builder()
+        .add_service(TopicMonitorServer::new(Service { sources, shutdown }))
+        .serve_with_shutdown(([127, 0, 0, 1], port).into(), async move {
+            crate::shutdown::stopping(&mut stopping).await;
+        })
+        .await;
+    if let Err(e) = result {
+        error!("gRPC server stopped: {}", e);
+    }
+}
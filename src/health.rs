@@ -0,0 +1,135 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use crate::alerts::{AlertView, Severity};
+use crate::*;
+
+/// Counts of troubled topics that turn the summary yellow or red. A topic is
+/// counted once per list, however many of its alerts fire.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HealthThresholds {
+    /// Topics with a firing critical alert
+    pub red_critical: usize,
+    /// Topics not heard from for `STALE_AFTER_MS`, including pinned topics never received
+    pub red_stale: usize,
+    /// Topics with a firing warning or critical alert
+    pub yellow_alerts: usize,
+    pub yellow_stale: usize,
+}
+
+impl HealthThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        if [
+            self.red_critical,
+            self.red_stale,
+            self.yellow_alerts,
+            self.yellow_stale,
+        ]
+        .contains(&0)
+        {
+            return Err("health thresholds must be at least 1".to_string());
+        }
+        if self.yellow_stale > self.red_stale {
+            return Err("the yellow stale threshold must not exceed the red one".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthLevel {
+    Green,
+    Yellow,
+    Red,
+}
+
+/// One-glance state of everything monitored, served at `/api/health-summary`
+#[derive(Debug, Serialize)]
+pub struct HealthSummary {
+    pub level: HealthLevel,
+    /// Why the level is not green, e.g. "2 critical topics"
+    pub reasons: Vec<String>,
+    pub topics: usize,
+    pub subscriber_healthy: bool,
+    /// Keys of the topics counted, for drilling down
+    pub critical: BTreeSet<String>,
+    pub warning: BTreeSet<String>,
+    pub stale: BTreeSet<String>,
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+/// Summarises the cached topics and the active alerts at `now`. Silenced alerts
+/// and alerts still in their dwell are not counted. A subscriber that isn't
+/// receiving makes the summary red, since nothing below can be trusted.
+pub fn summarize(
+    thresholds: &HealthThresholds,
+    topics: &Topics,
+    alerts: &[AlertView],
+    subscriber_healthy: bool,
+    now: u64,
+) -> HealthSummary {
+    let mut critical = BTreeSet::new();
+    let mut warning = BTreeSet::new();
+    for alert in alerts {
+        if !alert.state.is_firing() || alert.silenced_by.is_some() {
+            continue;
+        }
+        match alert.rule.severity() {
+            Severity::Critical => critical.insert(alert.key_expr.clone()),
+            Severity::Warning => warning.insert(alert.key_expr.clone()),
+            Severity::Info => false,
+        };
+    }
+    // A topic with both is only shown as critical
+    warning.retain(|key| !critical.contains(key));
+    let stale: BTreeSet<String> = topics
+        .values()
+        .filter(|topic| {
+            topic.is_placeholder() || now.saturating_sub(topic.received_timestamp) > STALE_AFTER_MS
+        })
+        .map(|topic| topic.key_expr.clone())
+        .collect();
+
+    let alerting = critical.len() + warning.len();
+    let mut level = HealthLevel::Green;
+    let mut reasons = Vec::new();
+    if !subscriber_healthy {
+        level = HealthLevel::Red;
+        reasons.push("the Zenoh subscriber is not receiving".to_string());
+    }
+    if critical.len() >= thresholds.red_critical {
+        level = HealthLevel::Red;
+    } else if alerting >= thresholds.yellow_alerts {
+        level = level.max(HealthLevel::Yellow);
+    }
+    if critical.len() >= thresholds.red_critical || alerting >= thresholds.yellow_alerts {
+        if !critical.is_empty() {
+            reasons.push(plural(critical.len(), "critical topic"));
+        }
+        if !warning.is_empty() {
+            reasons.push(plural(warning.len(), "topic") + " with warnings");
+        }
+    }
+    if stale.len() >= thresholds.yellow_stale {
+        level = level.max(if stale.len() >= thresholds.red_stale {
+            HealthLevel::Red
+        } else {
+            HealthLevel::Yellow
+        });
+        reasons.push(plural(stale.len(), "stale topic"));
+    }
+
+    HealthSummary {
+        level,
+        reasons,
+        topics: topics.len(),
+        subscriber_healthy,
+        critical,
+        warning,
+        stale,
+    }
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use tokio::time::{self, Duration};
+use zenoh::key_expr::keyexpr;
+
+use crate::*;
+
+/// Topic metric a highlight rule compares against its threshold
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HighlightMetric {
+    /// Rate in Hz, decaying while the topic is silent
+    Hz,
+    /// Latest payload size in bytes
+    Size,
+    /// Bytes per second, from the rate and the rolling average size
+    Bandwidth,
+    /// Milliseconds since the last sample
+    AgeMs,
+    /// Latest payload size minus the previous one, in bytes
+    SizeDeltaBytes,
+    /// Latest payload size change relative to the previous one, in percent
+    SizeDeltaPct,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Below,
+    Above,
+}
+
+/// Colours rows of topics matching `key_expr` whose metric is beyond the
+/// threshold. Purely visual: highlights never raise alerts or notify anyone.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HighlightRule {
+    pub key_expr: &'static str,
+    pub metric: HighlightMetric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Row class suffix: the row gets `highlight-<class>`
+    pub class: &'static str,
+    /// CSS background colour of the highlighted row
+    pub color: &'static str,
+}
+
+impl HighlightRule {
+    /// Class and colour end up in the page's CSS, so only plain values are accepted
+    pub fn validate(&self) -> Result<(), String> {
+        keyexpr::new(self.key_expr)
+            .map_err(|e| format!("invalid highlight key_expr '{}': {}", self.key_expr, e))?;
+        let class_ok = !self.class.is_empty()
+            && self
+                .class
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !class_ok {
+            return Err(format!(
+                "highlight class '{}' must be letters, digits, '-' or '_'",
+                self.class
+            ));
+        }
+        if !is_plain_css_value(self.color) {
+            return Err(format!(
+                "highlight color '{}' is not a plain CSS colour",
+                self.color
+            ));
+        }
+        Ok(())
+    }
+
+    fn applies_to(&self, topic: &TopicData, now: u64) -> bool {
+        if !alerts::key_matches(self.key_expr, &topic.key_expr) {
+            return false;
+        }
+        let value = match self.metric {
+            HighlightMetric::Hz => alerts::effective_hz(topic, now),
+            HighlightMetric::Size => topic.last_data_size_bytes as f64,
+            HighlightMetric::Bandwidth => {
+                alerts::effective_hz(topic, now) * topic.avg_data_size_bytes
+            }
+            HighlightMetric::AgeMs => now.saturating_sub(topic.received_timestamp) as f64,
+            HighlightMetric::SizeDeltaBytes => topic.size_delta_bytes as f64,
+            HighlightMetric::SizeDeltaPct => topic.size_delta_pct,
+        };
+        match self.comparison {
+            Comparison::Below => value < self.threshold,
+            Comparison::Above => value > self.threshold,
+        }
+    }
+}
+
+/// One rule per configured class, for the page's stylesheet
+pub fn css(rules: &[HighlightRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| {
+            format!(
+                "tr.highlight-{} td {{ background-color: {}; }}",
+                rule.class, rule.color
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sets each topic's `highlight` to the class of the first matching rule,
+/// every `period_ms`. Only changed topics are written, so unchanged rows are
+/// not resent over SSE.
+pub async fn run_highlighter(
+    topic_cache: TopicCache,
+    rules: &'static [HighlightRule],
+    period_ms: u64,
+) {
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let mut cache = topic_cache.write().await;
+        for topic in cache.values_mut() {
+            let class = rules
+                .iter()
+                .find(|rule| rule.applies_to(topic, now))
+                .map(|rule| rule.class);
+            if topic.highlight.as_deref() != class {
+                topic.highlight = class.map(str::to_string);
+            }
+        }
+    }
+}
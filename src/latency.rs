@@ -0,0 +1,149 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Processing times kept for the average and percentiles
+const CAPACITY: usize = 1000;
+
+/// Wall time the subscribe loop spent on each of the latest samples it
+/// cached: from receiving the sample, through locking the cache and decoding
+/// from the decode cache, to the insert. Decodes on the blocking pool run off
+/// the loop and are not counted. Recording never allocates.
+#[derive(Debug)]
+pub struct ProcessingLatency {
+    times_us: [u32; CAPACITY],
+    /// Slot the next time is written to
+    next: usize,
+    len: usize,
+    /// Samples timed since startup
+    samples: u64,
+}
+
+impl Default for ProcessingLatency {
+    fn default() -> Self {
+        Self {
+            times_us: [0; CAPACITY],
+            next: 0,
+            len: 0,
+            samples: 0,
+        }
+    }
+}
+
+/// `/api/diagnostics` summary of the latest processing times, in milliseconds
+#[derive(Debug, Serialize)]
+pub struct LatencyReport {
+    pub samples: u64,
+    /// Times the figures below are taken over
+    pub window: usize,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl ProcessingLatency {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.times_us[self.next] = u32::try_from(elapsed.as_micros()).unwrap_or(u32::MAX);
+        self.next = (self.next + 1) % CAPACITY;
+        self.len = (self.len + 1).min(CAPACITY);
+        self.samples += 1;
+    }
+
+    /// `None` until a sample has been timed
+    pub fn report(&self) -> Option<LatencyReport> {
+        if self.len == 0 {
+            return None;
+        }
+        let mut times = self.times_us[..self.len].to_vec();
+        times.sort_unstable();
+        // Nearest rank, so p99 of a few samples is their slowest
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * self.len as f64).ceil() as usize;
+            f64::from(times[rank.clamp(1, self.len) - 1]) / 1000.0
+        };
+        let total_us: u64 = times.iter().map(|&us| u64::from(us)).sum();
+        Some(LatencyReport {
+            samples: self.samples,
+            window: self.len,
+            avg_ms: total_us as f64 / self.len as f64 / 1000.0,
+            p50_ms: percentile(50.0),
+            p99_ms: percentile(99.0),
+        })
+    }
+}
+
+pub type ProcessingLatencies = Arc<RwLock<ProcessingLatency>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_all(latency: &mut ProcessingLatency, times_us: impl IntoIterator<Item = u64>) {
+        for us in times_us {
+            latency.record(Duration::from_micros(us));
+        }
+    }
+
+    #[test]
+    fn nothing_timed_reports_nothing() {
+        assert!(ProcessingLatency::default().report().is_none());
+    }
+
+    #[test]
+    fn few_samples_take_nearest_rank_percentiles() {
+        let mut latency = ProcessingLatency::default();
+        record_all(&mut latency, (1..=10).rev().map(|ms| ms * 1000));
+        let report = latency.report().unwrap();
+        assert_eq!((report.samples, report.window), (10, 10));
+        assert_eq!(report.avg_ms, 5.5);
+        assert_eq!(report.p50_ms, 5.0);
+        // p99 of ten samples is the slowest
+        assert_eq!(report.p99_ms, 10.0);
+
+        let mut latency = ProcessingLatency::default();
+        record_all(&mut latency, [250]);
+        let report = latency.report().unwrap();
+        assert_eq!((report.p50_ms, report.p99_ms), (0.25, 0.25));
+    }
+
+    #[test]
+    fn a_full_window_ranks_every_slot() {
+        let mut latency = ProcessingLatency::default();
+        record_all(&mut latency, 1..=CAPACITY as u64);
+        let report = latency.report().unwrap();
+        assert_eq!((report.samples, report.window), (1000, CAPACITY));
+        assert_eq!(report.p50_ms, 0.5);
+        assert_eq!(report.p99_ms, 0.99);
+        assert_eq!(report.avg_ms, 0.5005);
+    }
+
+    #[test]
+    fn the_oldest_times_are_overwritten_once_full() {
+        let mut latency = ProcessingLatency::default();
+        record_all(&mut latency, std::iter::repeat_n(9000, CAPACITY));
+        record_all(&mut latency, std::iter::repeat_n(1000, 600));
+        record_all(&mut latency, std::iter::repeat_n(3000, 400));
+        let report = latency.report().unwrap();
+        assert_eq!((report.samples, report.window), (2000, CAPACITY));
+        // None of the first thousand are left
+        assert_eq!(report.avg_ms, 1.8);
+        assert_eq!(report.p50_ms, 1.0);
+        assert_eq!(report.p99_ms, 3.0);
+
+        // Half way round again, half the window is newer still
+        record_all(&mut latency, std::iter::repeat_n(5000, CAPACITY / 2));
+        let report = latency.report().unwrap();
+        assert_eq!(report.window, CAPACITY);
+        assert_eq!(report.p50_ms, 3.0);
+        assert_eq!(report.p99_ms, 5.0);
+    }
+
+    #[test]
+    fn overlong_times_saturate() {
+        let mut latency = ProcessingLatency::default();
+        latency.record(Duration::from_secs(u64::MAX / 4));
+        let report = latency.report().unwrap();
+        assert_eq!(report.p99_ms, f64::from(u32::MAX) / 1000.0);
+    }
+}
@@ -0,0 +1,107 @@
+use log::{info, warn};
+use std::collections::{BTreeSet, HashMap};
+use tokio::time::{self, Duration};
+use zenoh::sample::SampleKind;
+
+use crate::*;
+
+/// Splits a token key `<prefix>/<topic key>/<publisher id>` into the topic key
+/// and the publisher id
+fn parse_token<'a>(prefix: &str, token: &'a str) -> Option<(&'a str, &'a str)> {
+    token
+        .strip_prefix(prefix)?
+        .strip_prefix('/')?
+        .rsplit_once('/')
+}
+
+/// Counts the live publisher tokens under `prefix` per topic and keeps each
+/// topic's `publisher_count` up to date. More than one publisher flags
+/// `publisher_conflict`, unless the key matches `expected_multiple`.
+///
+/// The subscriber is declared on the subscriber's session once it is open, and
+/// again whenever the watchdog replaces the session. Tokens are learned from
+/// the liveliness history, so publishers that came up first are counted too.
+pub async fn run_publisher_liveliness(
+    topic_cache: TopicCache,
+    session: ZenohSession,
+    prefix: &'static str,
+    expected_multiple: &'static [&'static str],
+    period_ms: u64,
+) {
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    let mut tokens: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut subscriber = None;
+    loop {
+        let sample = match &subscriber {
+            None => {
+                interval.tick().await;
+                let current = session.read().await.clone();
+                if let Some(current) = current {
+                    match current
+                        .liveliness()
+                        .declare_subscriber(format!("{}/**", prefix))
+                        .history(true)
+                        .await
+                    {
+                        Ok(declared) => {
+                            info!("Counting publisher liveliness tokens under '{}'", prefix);
+                            subscriber = Some(declared);
+                        }
+                        Err(e) => warn!("Failed to declare the liveliness subscriber: {}", e),
+                    }
+                }
+                None
+            }
+            Some(declared) => tokio::select! {
+                sample = declared.recv_async() => match sample {
+                    Ok(sample) => Some(sample),
+                    // The session closed; the tokens are learned again on the next one
+                    Err(_) => {
+                        subscriber = None;
+                        tokens.clear();
+                        None
+                    }
+                },
+                _ = interval.tick() => None,
+            },
+        };
+
+        if let Some(sample) = sample {
+            let Some((topic, publisher)) = parse_token(prefix, sample.key_expr().as_str()) else {
+                continue;
+            };
+            match sample.kind() {
+                SampleKind::Put => {
+                    tokens
+                        .entry(topic.to_string())
+                        .or_default()
+                        .insert(publisher.to_string());
+                }
+                SampleKind::Delete => {
+                    if let Some(publishers) = tokens.get_mut(topic) {
+                        publishers.remove(publisher);
+                        if publishers.is_empty() {
+                            tokens.remove(topic);
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+
+        let mut cache = topic_cache.write().await;
+        for topic in cache.values_mut() {
+            let count = tokens
+                .get(&topic.key_expr)
+                .map(|publishers| u32::try_from(publishers.len()).unwrap_or(u32::MAX));
+            let conflict = count.is_some_and(|count| count > 1)
+                && !expected_multiple
+                    .iter()
+                    .any(|key_expr| alerts::key_matches(key_expr, &topic.key_expr));
+            if topic.publisher_count != count || topic.publisher_conflict != conflict {
+                topic.publisher_count = count;
+                topic.publisher_conflict = conflict;
+            }
+        }
+    }
+}
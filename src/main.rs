@@ -1,52 +1,849 @@
 use ftail::Ftail;
 use log::{LevelFilter, debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{RwLock, Semaphore, watch};
 use tokio::time::{self, Duration};
-use warp::{Filter, sse};
-use zenoh::sample::Sample;
+use warp::http::StatusCode;
+use warp::{Filter, Reply, sse};
+use zenoh::config::{EndPoint, WhatAmI};
+use zenoh::sample::{Sample, SampleKind};
 
+mod alerts;
+mod audit;
+mod branding;
+mod chart;
+mod check;
+mod cli;
+mod clock;
+mod config;
 mod decoder;
+mod dump;
+mod federation;
+mod format;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod health;
+mod highlight;
+mod latency;
+mod liveliness;
+mod memory;
+mod notify;
+mod persist;
+mod pins;
+mod query;
+mod report;
+mod settings;
+mod shutdown;
+mod silences;
+mod throughput;
+mod timing;
+mod views;
 
-type DecoderFn = Option<fn(Sample) -> String>;
-const DECODER: DecoderFn = Some(decoder::flatbuffer_decoder);
+use alerts::{
+    AckRequest, AlertEngine, AlertRule, AlertStore, CompositeRule, HistoryQuery, HistoryRetention,
+};
+use audit::AuditLog;
+use branding::Branding;
+use cli::Cli;
+use clock::{SharedClock, SystemClock};
+use config::Config;
+use decoder::NamedDecoder;
+use federation::{FederationState, Upstream};
+use health::HealthThresholds;
+use highlight::HighlightRule;
+use latency::{LatencyReport, ProcessingLatencies};
+use notify::{NotifierConfig, NotifierHub, Notifiers};
+use pins::{PinRequest, Pins};
+use query::SeedQuery;
+use report::ReportQuery;
+use shutdown::Tasks;
+use silences::{SilenceRequest, Silences};
+use throughput::{LatestThroughput, Throughput, ThroughputQuery};
+use timing::TimingThreshold;
+use views::{Sort, SortDirection, SortField, View, Views};
+
+type DecoderFn = fn(Sample) -> Result<String, String>;
+/// Decoders tried in order on each sample until one succeeds, e.g.
+/// flatbuffer, then `decoder::json_decoder`, then `decoder::hex_decoder`.
+/// An empty chain disables decoding.
+const DECODERS: &[NamedDecoder] = &[NamedDecoder::new("flatbuffer", decoder::flatbuffer_decoder)];
+
+/// Maximum number of samples decoded concurrently; samples arriving while all
+/// permits are taken skip decoding and keep the previous decoded content
+const DECODE_CONCURRENCY: usize = 4;
+/// Distinct payloads per topic whose decoded content is kept, so a payload the
+/// topic sent before is not decoded again; 0 turns the cache off
+const DECODE_CACHE_ENTRIES: usize = 0;
+/// A failed decode keeps the last good decoded content and flags the error
+/// beside it; when off, the error replaces the content
+const KEEP_LAST_GOOD_DECODE: bool = true;
+/// Times how long the subscribe loop spends on each sample, reported at
+/// `/api/diagnostics`; a climbing time means the monitor can't keep up
+const MEASURE_PROCESSING_LATENCY: bool = false;
 
 const LOG_LEVEL: log::LevelFilter = LevelFilter::Warn;
 const PORT: u16 = 8080;
+/// Port of the gRPC stream of topic deltas, e.g. `Some(50051)`. Needs a build
+/// with the `grpc` feature.
+const GRPC_PORT: Option<u16> = None;
+/// Path prefix of every route, for serving behind a reverse proxy at e.g.
+/// `Some("/monitor")`; `None` serves at the root
+const BASE_PATH: Option<&str> = None;
+/// Other monitors whose topics are merged into this dashboard as `<name>/<key>`, e.g.
+/// `Upstream { name: "vehicle1", url: "http://vehicle1:8080", token: None }`
+const FEDERATION_UPSTREAMS: &[Upstream] = &[];
+/// How often each upstream's topics are fetched
+const FEDERATION_POLL_MS: u64 = 1000;
+/// Only show the upstreams' topics, without subscribing to Zenoh locally
+const FEDERATION_ONLY: bool = false;
+const ZENOH_MODE: &str = "peer";
+const ZENOH_CONNECT_ENDPOINTS: &[&str] = &["tcp/127.0.0.1:7447"];
+/// Zenoh namespace of the session, e.g. `Some("team-a")`, so monitors of several
+/// teams can share one router. It is prepended to every key the monitor
+/// subscribes to, queries or publishes, and stripped from the keys it receives.
+const ZENOH_NAMESPACE: Option<&str> = None;
+/// Query the subscribed key expression once the subscriber is up, to fill the
+/// table with stored values, e.g. `Some(SeedQuery { timeout_ms: 3000, max_replies: 1000 })`
+const SEED_QUERY: Option<SeedQuery> = None;
+/// Failed attempts to open the first Zenoh session before exiting with
+/// `EXIT_ZENOH_UNREACHABLE`, e.g. `Some(5)`; `None` keeps retrying. Once a
+/// session has opened, the watchdog always retries.
+const ZENOH_OPEN_ATTEMPTS: Option<u32> = None;
+/// Key expression subscribed to at startup; `POST /api/subscribe` changes it at runtime
+const SUBSCRIBE_KEY_EXPR: &str = "**";
 const RELOAD_PERIOD_MS: u64 = 1000;
+/// Columns of the topic table, in order. Known columns: key, size, hz,
+/// bandwidth, timestamp, age, load, stability, jitter, latency, source, instance, decoded,
+/// status. The key column is required.
+const UI_COLUMNS: &[&str] = &["key", "size", "hz", "timestamp", "decoded"];
+/// Keys longer than this are shown with the middle elided, e.g. `robot/front…/points`;
+/// the full key is in the tooltip and the detail view, and filtering always uses
+/// it. 0 shows every key in full.
+const KEY_DISPLAY_MAX_CHARS: usize = 80;
+/// Initial order of the table and of `/api/topics` without a `sort` parameter
+const DEFAULT_SORT: Sort = Sort {
+    field: SortField::Key,
+    direction: SortDirection::Asc,
+};
+/// Fields the sort button cycles through; each needs its column in `UI_COLUMNS`
+const SORT_FIELDS: &[SortField] = &[SortField::Key, SortField::Timestamp];
+/// What the bars of the `load` column show each topic's share of
+const LOAD_BAR_METRIC: LoadMetric = LoadMetric::Bandwidth;
+/// How the frequency column starts out: `Absolute` Hz, or `Relative` to each
+/// topic's expected rate; the Rates button switches it
+const RATE_DISPLAY: RateDisplay = RateDisplay::Absolute;
+/// Name of this monitor, reported by `/api/about` and in alert payloads, e.g. `Some("car1")`
+const INSTANCE_NAME: Option<&str> = None;
+/// Page title, heading, subtitle, logo and accent colour
+const BRANDING: Branding = Branding {
+    page_title: "Zenoh DDS Topic Monitor",
+    heading: "Zenoh DDS Monitor",
+    subtitle: "Real-time topic monitoring",
+    logo_path: None,
+    accent_color: None,
+};
+/// Where saved dashboard views are persisted
+const VIEWS_FILE: &str = "views.json";
+/// Where pinned topics are persisted
+const PINS_FILE: &str = "pins.json";
+/// Default colour scheme of the dashboard; `Auto` follows the browser's
+/// preference. Each browser can pick another, which it remembers.
+const THEME: Theme = Theme::Light;
+/// CSS file appended to the page's styles, to adjust colours without forking
+const EXTRA_CSS_PATH: Option<&str> = None;
+/// Estimated cache size at which the oldest topics are evicted; load shedding
+/// (no decoding, faster eviction of silent topics) starts at 80% of it
+const MEMORY_LIMIT_BYTES: Option<usize> = None;
+/// Units for the display strings attached to each topic: `Si` (kB, MB) or `Binary` (KiB, MiB)
+const DISPLAY_UNITS: format::Units = format::Units::Si;
+/// Number of leading key segments topics are grouped by in the header and `/api/stats`
+const PREFIX_GROUP_DEPTH: usize = 1;
+/// How samples received more than once over different routes are handled
+const DUPLICATE_SAMPLES: DuplicateSamples = DuplicateSamples::DropByTimestamp;
+/// Which sample kinds are recorded, e.g. `SampleKinds::DeleteOnly` to watch
+/// which keys are being cleared
+const SAMPLE_KINDS: SampleKinds = SampleKinds::Both;
+/// Friendly names for publishing sessions by zid, shown in the source column,
+/// e.g. `("8a3c1f0e2b4d4e6f9a0b1c2d3e4f5a6b", "car1-lidar")`
+const SOURCE_NAMES: &[(&str, &str)] = &[];
+/// Publishers seen on a topic within this long count toward its `source_count`
+const SOURCE_WINDOW_MS: u64 = 10_000;
+/// Liveliness prefix under which publishers declare a token per topic as
+/// `<prefix>/<topic key>/<publisher id>`, e.g. `Some("publishers")`. The live
+/// tokens are counted into each topic's `publisher_count`; `None` disables it.
+const PUBLISHER_LIVELINESS: Option<&str> = None;
+/// Key expressions on which several live publishers are expected, so the
+/// publishers column doesn't warn about them, e.g. `"robot/*/heartbeat"`
+const MULTI_PUBLISHER_KEYS: &[&str] = &[];
+/// Expected-topics manifest (as for `--check`) whose rates are shown next to the
+/// measured ones; overridden by `--manifest <path>`
+const EXPECTED_TOPICS_MANIFEST: Option<&str> = None;
+/// Rate deviations from the manifest beyond these percentages are shown amber and red
+const RATE_DEVIATION_WARN_PCT: f64 = 10.0;
+const RATE_DEVIATION_BAD_PCT: f64 = 25.0;
+/// Samples with smaller payloads are dropped on arrival, as if never received;
+/// overridden by `--min-size <bytes>`. 0 keeps everything.
+const MIN_PAYLOAD_BYTES: u64 = 0;
+/// Whether zero-length (signal-only) payloads contribute to a topic's estimated rate
+const EMPTY_PAYLOADS_COUNT_TOWARD_HZ: bool = true;
+/// Annotate each SSE delta with a sequence number so clients can detect gaps
+const SSE_SEQ_NUMBERS: bool = true;
+/// Ticks where nothing changed send no delta; after this long without one a
+/// `heartbeat` event tells the page the stream is still alive
+const SSE_HEARTBEAT_MS: u64 = 15_000;
+/// Send decoded content in a `decoded` event after each update instead of in
+/// the update itself, so clients that don't listen for it never receive it
+const SSE_DECODED_EVENT: bool = false;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Rate and payload-size alerts evaluated every `RELOAD_PERIOD_MS`, e.g.
+/// `AlertRule { name: "lidar-rate", key_expr: "robot/*/lidar", severity: alerts::Severity::Warning, condition: alerts::AlertCondition::RateBelow { assert_hz: 8.0, clear_hz: 9.5 }, assert_dwell_ms: 3000, clear_dwell_ms: 5000 }`
+const ALERT_RULES: &[AlertRule] = &[];
+/// Rules over several topics or other rules, evaluated after `ALERT_RULES`, e.g.
+/// `CompositeRule { name: "perception-down", severity: alerts::Severity::Critical, members: &[alerts::CompositeMember::Rule("lidar-rate"), alerts::CompositeMember::Topics("robot/*/camera")], aggregation: alerts::Aggregation::All, assert_dwell_ms: 0, clear_dwell_ms: 5000 }`
+const COMPOSITE_ALERT_RULES: &[CompositeRule] = &[];
+/// Row colours by metric thresholds, evaluated every `RELOAD_PERIOD_MS`; the
+/// first matching rule wins. Independent of alerting, so nobody is paged, e.g.
+/// `HighlightRule { key_expr: "robot/*/lidar", metric: highlight::HighlightMetric::Hz, comparison: highlight::Comparison::Below, threshold: 8.0, class: "degraded", color: "#fff3cd" }`
+const HIGHLIGHT_RULES: &[HighlightRule] = &[];
+/// Jitter and latency bounds per key expression; a value beyond its bound colours
+/// the cell and the first matching entry wins. Empty by default, e.g.
+/// `TimingThreshold { key_expr: "robot/*/lidar", jitter_ms: Some(5.0), latency_ms: Some(50.0) }`
+const TIMING_THRESHOLDS: &[TimingThreshold] = &[];
+/// Unit of the jitter and latency columns: `Ms` or `Us`
+const TIMING_UNIT: format::TimeUnit = format::TimeUnit::Ms;
+/// Publish alert transitions as JSON on `<prefix>/<topic>`, e.g. `Some("monitor/alerts")`
+const ALERT_PUBLISH_PREFIX: Option<&str> = None;
+/// How often firing alerts are re-published so late joiners see them
+const ALERT_REPUBLISH_MS: u64 = 10_000;
+/// Minimum time between two published transitions of the same alert; a flap
+/// inside it is published as its final state, or not at all
+const ALERT_PUBLISH_DEBOUNCE_MS: u64 = 5_000;
+/// Destinations that alert transitions are delivered to, e.g.
+/// `NotifierConfig { name: "ops-slack", kind: notify::NotifierKind::Slack { webhook_url: "https://hooks.slack.com/services/...", template: "{severity}: {rule} on {topic} is {state} ({value})" }, events: &[notify::EventType::AlertTransition, notify::EventType::TopicAppeared], min_severity: alerts::Severity::Warning, key_expr: "**" }`
+const NOTIFIERS: &[NotifierConfig] = &[];
+/// When the header's health summary turns yellow or red; `None` turns it and
+/// `/api/health-summary` off
+const HEALTH_SUMMARY: Option<HealthThresholds> = Some(HealthThresholds {
+    red_critical: 1,
+    red_stale: 5,
+    yellow_alerts: 1,
+    yellow_stale: 1,
+});
+/// Appends a JSON line listing every topic and its rate to a file at a fixed
+/// period, for audit trails, e.g.
+/// `Some(AuditLog { path: "logs/audit.jsonl", period_ms: 60_000 })`
+const AUDIT_LOG: Option<AuditLog> = None;
+/// Where `kill -USR1 <pid>` or `POST /api/dump` writes a timestamped JSON
+/// file of every cached topic, for post-mortem debugging
+const CACHE_DUMP_DIR: &str = "dumps";
+/// Where silences are saved so they survive restarts
+const SILENCES_FILE: &str = "silences.json";
+/// Alert transitions kept for `/api/alerts/history`
+const ALERT_HISTORY_RETENTION: HistoryRetention = HistoryRetention {
+    max_entries: 10_000,
+    max_age_ms: 7 * 24 * 60 * 60 * 1000,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 struct TopicData {
     key_expr: String,
     last_data_size_bytes: u64,
     received_timestamp: u64,
+    /// HTML-escaped on the server, so clients insert it as markup. Every other
+    /// string here is plain text that the client escapes or sets as text.
     #[serde(skip_serializing_if = "Option::is_none")]
     decoded_content: Option<String>,
-    estimated_hz: f64,
+    /// Name of the decoder in the chain that produced `decoded_content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoded_by: Option<String>,
+    /// Rounded by `format::round_hz` when stored. `None` until a second sample
+    /// gives an interval, so a topic heard from once doesn't read as dead.
+    estimated_hz: Option<f64>,
+    /// Samples counted toward the rate since the topic entered the cache
+    #[serde(default)]
+    message_count: u64,
+    /// Rolling average payload size of the samples preceding the latest one
+    #[serde(serialize_with = "format::serialize_rounded")]
+    avg_data_size_bytes: f64,
+    /// Latest payload size minus the previous one; 0 for a topic's first sample
+    size_delta_bytes: i64,
+    /// `size_delta_bytes` as a percentage of the previous size, floored at one byte
+    #[serde(serialize_with = "format::serialize_rounded")]
+    size_delta_pct: f64,
+    /// Number of consecutive zero-length payloads, including the latest
+    empty_streak: u64,
+    /// The latest sample carried no payload (a pure event signal)
+    empty_payload: bool,
+    /// Receive timestamp of the latest sample a decode finished for, failed or not
+    #[serde(skip)]
+    decoded_timestamp: u64,
+    /// Total number of failed decodes
+    decode_errors: u64,
+    /// Rolling fraction of recent decode attempts that failed
+    decode_error_fraction: f64,
+    /// Consecutive successful decodes, including the latest
+    decode_success_streak: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_decode_error: Option<String>,
+    /// Receive timestamp of the sample `last_decode_error` came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_decode_error_at: Option<u64>,
+    /// Samples dropped as duplicates of one already received via another route
+    duplicate_samples: u64,
+    /// Samples whose Zenoh timestamp is older than the previous one from the same publisher
+    out_of_order_samples: u64,
+    /// Rolling fraction of recent samples that arrived out of order
+    #[serde(skip)]
+    out_of_order_fraction: f64,
+    /// Latest Zenoh timestamp seen on the topic, for out-of-order detection
+    #[serde(skip)]
+    source_timestamp: Option<zenoh::time::Timestamp>,
+    /// 0 (erratic) to 100 (steady), from jitter, gaps and out-of-order samples
+    stability: u8,
+    /// Standard deviation of the inter-arrival intervals over the rate window
+    #[serde(serialize_with = "format::serialize_rounded")]
+    jitter_ms: f64,
+    /// Receive time minus the latest sample's Zenoh timestamp; `None` for samples without one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<f64>,
+    /// Beyond the bounds of the matching `TIMING_THRESHOLDS` entry
+    jitter_exceeded: bool,
+    latency_exceeded: bool,
+    /// Publisher of the latest sample that carried source info, as `<name or zid>:<entity id>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_source: Option<String>,
+    /// Distinct publishers seen within `SOURCE_WINDOW_MS`; more than one usually means
+    /// two nodes publish on the same key
+    source_count: u16,
+    /// Publishers and when each was last seen, pruned to `SOURCE_WINDOW_MS`
+    #[serde(skip)]
+    recent_sources: Vec<(String, u64)>,
+    /// Live liveliness tokens of publishers on the key, with `PUBLISHER_LIVELINESS`;
+    /// `None` when no publisher has announced itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    publisher_count: Option<u32>,
+    /// More than one live publisher on a key outside `MULTI_PUBLISHER_KEYS`
+    publisher_conflict: bool,
+    /// Upstream monitor the topic was federated from; `None` for local topics
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    /// The payload is byte-for-byte the previous one (with `SKIP_REPEATED_PAYLOADS`)
+    repeated_payload: bool,
+    /// Consecutive samples whose payload matched the one before, e.g. a latched publisher
+    identical_repeat_count: u64,
+    /// Number of times the payload changed, counting the first one
+    distinct_payloads: u64,
+    /// xxHash of the payload's first `PAYLOAD_HASH_PREFIX_BYTES` and its length
+    #[serde(skip)]
+    payload_prefix_hash: u64,
+    /// Only served by the detail endpoint, to keep SSE updates small
+    #[serde(skip)]
+    interval_stats: IntervalStats,
+    #[serde(skip)]
+    recent_gaps: VecDeque<Gap>,
+    /// Gaps since the topic appeared; each one reset the rate window
+    gap_count: u64,
+    /// Rate expected by the `--manifest` entry matching the topic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_hz: Option<f64>,
+    /// How far the current rate is from `expected_hz`, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hz_deviation_pct: Option<f64>,
+    /// Class of the first `HIGHLIGHT_RULES` entry matching the topic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlight: Option<String>,
+    /// Kept above the sorted topics and never evicted; see `/api/pins`
+    pinned: bool,
+    /// Display strings in `DISPLAY_UNITS`; the raw numbers above are for sorting
+    size_display: String,
+    hz_display: String,
+    bandwidth_display: String,
+    /// In `TIMING_UNIT`
+    jitter_display: String,
+    latency_display: String,
+}
+
+impl TopicData {
+    /// Row of a pinned key no sample has arrived on yet
+    fn placeholder(key_expr: &str) -> Self {
+        Self {
+            key_expr: key_expr.to_string(),
+            pinned: true,
+            size_display: "-".to_string(),
+            hz_display: "-".to_string(),
+            bandwidth_display: "-".to_string(),
+            jitter_display: "-".to_string(),
+            latency_display: "-".to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn is_placeholder(&self) -> bool {
+        self.received_timestamp == 0
+    }
+
+    /// Whether a client would see `self` differently from `old`. The receive
+    /// time and the sample and payload counts move with every sample even of
+    /// a steady topic, so they are left out, along with the fields never
+    /// serialized; a new sample alone travels as an age in `DeltaUpdate::ages`.
+    fn differs_from(&self, old: &TopicData) -> bool {
+        if self.received_timestamp == old.received_timestamp {
+            return self != old;
+        }
+        let fresh = TopicData {
+            received_timestamp: old.received_timestamp,
+            message_count: old.message_count,
+            distinct_payloads: old.distinct_payloads,
+            decoded_timestamp: old.decoded_timestamp,
+            out_of_order_fraction: old.out_of_order_fraction,
+            source_timestamp: old.source_timestamp,
+            recent_sources: old.recent_sources.clone(),
+            payload_prefix_hash: old.payload_prefix_hash,
+            interval_stats: old.interval_stats,
+            recent_gaps: old.recent_gaps.clone(),
+            ..self.clone()
+        };
+        fresh != *old
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct DeltaUpdate {
+    /// Per-connection sequence number, starting at 1 with a full snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seq: Option<u64>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     updated: Vec<TopicData>,
+    /// Topics that entered the cache since the previous update; the first
+    /// update of a stream sends everything as `updated` instead
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    added: Vec<TopicData>,
+    /// Keys in `added` that had been removed earlier this session
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reappeared: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     removed: Vec<String>,
+    /// Server clock at this update, so ages never depend on the browser's clock
+    server_time: u64,
+    /// Age of every topic every `AGE_REFRESH_TICKS` updates, and otherwise
+    /// of the topics that received a sample without changing what is shown
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    ages: BTreeMap<String, u64>,
+    /// Rate sparkline points of every topic, sent when a new sample was taken
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    hz_history: BTreeMap<String, Vec<f32>>,
+    /// Latest global totals, for the header chart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    throughput: Option<LatestThroughput>,
+}
+
+/// Everything kept per topic, in one map entry, so a topic's interval window,
+/// decode cache and duplicate fingerprints enter and leave the cache with it.
+/// Derefs to the served `TopicData`, so code reading the cache treats an entry
+/// as the topic itself.
+#[derive(Debug, Clone, Default)]
+struct TopicState {
+    data: TopicData,
+    intervals: IntervalWindow,
+    decodes: DecodeCache,
+    seen: SeenSamples,
+}
+
+impl From<TopicData> for TopicState {
+    fn from(data: TopicData) -> Self {
+        Self {
+            data,
+            ..Self::default()
+        }
+    }
+}
+
+impl Deref for TopicState {
+    type Target = TopicData;
+
+    fn deref(&self) -> &TopicData {
+        &self.data
+    }
+}
+
+impl DerefMut for TopicState {
+    fn deref_mut(&mut self) -> &mut TopicData {
+        &mut self.data
+    }
+}
+
+type Topics = HashMap<String, TopicState>;
+type TopicCache = Arc<RwLock<Topics>>;
+
+/// Keys removed from the cache this session and when, so a topic coming back
+/// (e.g. after eviction) can be told apart from one never seen before.
+/// Written by `remove_topic`, the one place topics leave the cache.
+#[derive(Debug, Default)]
+struct TombstoneStore {
+    /// When each key was removed, and the number of that removal
+    removed_at: HashMap<String, (u64, u64)>,
+    /// Removal numbers oldest first, for forgetting beyond `TOMBSTONE_CAPACITY`.
+    /// An entry a later removal of its key superseded is skipped.
+    order: VecDeque<(String, u64)>,
+    /// Removals so far. Readers that saw the cache at `removals == n` know a
+    /// key with a later removal number went away and came back since.
+    removals: u64,
+}
+
+impl TombstoneStore {
+    fn insert(&mut self, key_expr: &str, now: u64) {
+        self.removals += 1;
+        self.removed_at
+            .insert(key_expr.to_string(), (now, self.removals));
+        self.order.push_back((key_expr.to_string(), self.removals));
+        while self.removed_at.len() > TOMBSTONE_CAPACITY
+            && let Some((key, removal)) = self.order.pop_front()
+        {
+            if self
+                .removed_at
+                .get(&key)
+                .is_some_and(|&(_, r)| r == removal)
+            {
+                self.removed_at.remove(&key);
+            }
+        }
+        // Keys removed again and again leave superseded entries behind
+        if self.order.len() > 2 * TOMBSTONE_CAPACITY {
+            let removed_at = &self.removed_at;
+            self.order
+                .retain(|(key, removal)| removed_at.get(key).is_some_and(|&(_, r)| r == *removal));
+        }
+    }
+
+    fn get(&self, key_expr: &str) -> Option<u64> {
+        self.removed_at.get(key_expr).map(|&(at, _)| at)
+    }
+
+    /// The key was removed after the first `removals` removals
+    fn removed_since(&self, key_expr: &str, removals: u64) -> bool {
+        self.removed_at
+            .get(key_expr)
+            .is_some_and(|&(_, removal)| removal > removals)
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.removed_at.len()
+    }
 }
 
-type TopicCache = Arc<RwLock<HashMap<String, TopicData>>>;
+type Tombstones = Arc<RwLock<TombstoneStore>>;
+/// Tombstones kept; beyond this the oldest are forgotten
+const TOMBSTONE_CAPACITY: usize = 10_000;
 
+/// Rate sparklines: points sent per topic and how often one is sampled. The
+/// histories go out over SSE once per sample, separately from row updates.
+const HZ_HISTORY: bool = true;
+const HZ_HISTORY_POINTS: usize = 30;
+const HZ_HISTORY_PERIOD_MS: u64 = 3000;
+/// How long sampled metrics are kept for `/api/chart`
+const METRIC_HISTORY_MS: u64 = 10 * 60_000;
+/// Rendered charts are reused for this long, so dashboards polling them stay cheap
+const CHART_CACHE_MS: u64 = 5000;
+
+/// Every this many SSE updates, the age of every topic is sent, changed or not
+const AGE_REFRESH_TICKS: u64 = 5;
+/// Topics not heard from for this long are shown as stale
+const STALE_AFTER_MS: u64 = 5000;
+
+/// Samples in the rate window and the rolling averages (payload size, decode
+/// error fraction)
 const WINDOW_SIZE: usize = 20;
-type IntervalHistory = Arc<RwLock<HashMap<String, (u64, Vec<u64>)>>>;
+/// Window the rate is estimated over, e.g. `RateWindow::Duration { ms: 5000 }`
+/// for a consistent period regardless of topic speed
+const RATE_WINDOW: RateWindow = RateWindow::Samples(WINDOW_SIZE);
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RateWindow {
+    /// The last N inter-arrival intervals
+    Samples(usize),
+    /// Every interval that ended within the last `ms` milliseconds
+    Duration { ms: u64 },
+}
+
+impl RateWindow {
+    /// Drops the intervals that have fallen out of the window; `now` is in nanoseconds
+    fn prune(&self, intervals: &mut VecDeque<(u64, u64)>, now: u64) {
+        match *self {
+            RateWindow::Samples(count) => {
+                while intervals.len() > count {
+                    intervals.pop_front();
+                }
+            }
+            RateWindow::Duration { ms } => {
+                let cutoff = now.saturating_sub(ms * NANOS_PER_MS);
+                // The latest interval is always kept so a topic slower than the window still has a rate
+                while intervals.len() > 1 && intervals.front().is_some_and(|(ts, _)| *ts < cutoff) {
+                    intervals.pop_front();
+                }
+            }
+        }
+    }
+}
+
+/// An interval this many times longer than the window's average is a gap
+const GAP_FACTOR: f64 = 3.0;
+/// An interval longer than this is a gap however few intervals the window has,
+/// e.g. `Some(10_000)`
+const GAP_MAX_INTERVAL_MS: Option<u64> = None;
+/// Gaps are only detected once the window has this many intervals to compare with
+const GAP_MIN_INTERVALS: usize = 3;
+/// Number of recent gaps kept per topic
+const RECENT_GAPS: usize = 10;
+
+/// Relative weights of the stability score's components; they need not sum to 1
+#[derive(Debug, Clone, Copy, Serialize)]
+struct StabilityWeights {
+    /// Interval standard deviation relative to the average interval
+    jitter: f64,
+    /// Gaps ("flaps") within `STABILITY_WINDOW_MS`
+    flaps: f64,
+    out_of_order: f64,
+}
+
+const STABILITY_WEIGHTS: StabilityWeights = StabilityWeights {
+    jitter: 0.5,
+    flaps: 0.3,
+    out_of_order: 0.2,
+};
+/// Gaps this recent count as flaps; `STABILITY_MAX_FLAPS` of them score the flap component 0
+const STABILITY_WINDOW_MS: u64 = 5 * 60_000;
+const STABILITY_MAX_FLAPS: usize = 5;
+
+/// Combines the topic's diagnostics into a 0-100 score, 100 being perfectly steady.
+/// Each component is a penalty from 0 to 1, weighted by `weights`.
+fn stability_score(
+    stats: &IntervalStats,
+    gaps: &VecDeque<Gap>,
+    out_of_order_fraction: f64,
+    now: u64,
+    weights: StabilityWeights,
+) -> u8 {
+    let jitter = if stats.avg_ms > 0.0 {
+        (stats.jitter_ms / stats.avg_ms).min(1.0)
+    } else {
+        0.0
+    };
+    let recent_flaps = gaps
+        .iter()
+        .filter(|gap| now.saturating_sub(gap.started_at + gap.duration_ms) <= STABILITY_WINDOW_MS)
+        .count();
+    let flaps = (recent_flaps as f64 / STABILITY_MAX_FLAPS as f64).min(1.0);
+    let total = weights.jitter + weights.flaps + weights.out_of_order;
+    if total <= 0.0 {
+        return 100;
+    }
+    let penalty = (weights.jitter * jitter
+        + weights.flaps * flaps
+        + weights.out_of_order * out_of_order_fraction.clamp(0.0, 1.0))
+        / total;
+    (100.0 * (1.0 - penalty)).round() as u8
+}
+
+/// Inter-arrival statistics over the rate window, in fractional milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct IntervalStats {
+    min_ms: f64,
+    avg_ms: f64,
+    max_ms: f64,
+    /// Standard deviation of the intervals
+    jitter_ms: f64,
+}
+
+impl IntervalStats {
+    /// `intervals` are in nanoseconds; milliseconds are only for display and thresholds
+    fn of(intervals: &VecDeque<(u64, u64)>) -> Self {
+        if intervals.is_empty() {
+            return Self::default();
+        }
+        let to_ms = |delta: u64| delta as f64 / NANOS_PER_MS as f64;
+        let count = intervals.len() as f64;
+        let avg_ms = intervals
+            .iter()
+            .map(|(_, delta)| to_ms(*delta))
+            .sum::<f64>()
+            / count;
+        let variance = intervals
+            .iter()
+            .map(|(_, delta)| (to_ms(*delta) - avg_ms).powi(2))
+            .sum::<f64>()
+            / count;
+        Self {
+            min_ms: to_ms(intervals.iter().map(|(_, delta)| *delta).min().unwrap_or(0)),
+            avg_ms,
+            max_ms: to_ms(intervals.iter().map(|(_, delta)| *delta).max().unwrap_or(0)),
+            jitter_ms: variance.sqrt(),
+        }
+    }
+}
+
+/// A silence much longer than the topic's usual interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Gap {
+    /// Timestamp of the sample before the gap
+    started_at: u64,
+    duration_ms: u64,
+}
+
+/// A topic's last arrival and its recent `(timestamp, interval)` pairs, in
+/// nanoseconds on the monotonic clock
+#[derive(Debug, Clone, Default)]
+struct IntervalWindow {
+    last_arrival: Option<u64>,
+    intervals: VecDeque<(u64, u64)>,
+}
+
+/// One sample of a topic's metrics, taken by the history sampler
+#[derive(Debug, Clone, Copy)]
+struct MetricPoint {
+    at: u64,
+    hz: f32,
+    size: f32,
+    bandwidth: f32,
+}
+
+/// Recent metrics per topic for the sparklines and charts, sampled every
+/// `HZ_HISTORY_PERIOD_MS` and kept for `METRIC_HISTORY_MS`
+#[derive(Debug, Default)]
+struct MetricHistoryStore {
+    /// Incremented on every sample, so SSE connections know when to resend
+    revision: u64,
+    topics: HashMap<String, VecDeque<MetricPoint>>,
+}
+
+impl MetricHistoryStore {
+    /// The last `HZ_HISTORY_POINTS` rates of a topic, oldest first
+    fn sparkline(&self, key: &str) -> Vec<f32> {
+        self.topics.get(key).map_or_else(Vec::new, |points| {
+            let skip = points.len().saturating_sub(HZ_HISTORY_POINTS);
+            points.iter().skip(skip).map(|point| point.hz).collect()
+        })
+    }
+}
+
+type MetricHistory = Arc<RwLock<MetricHistoryStore>>;
 
+/// Samples every topic's metrics into its history. Topics that left the cache
+/// are dropped.
+async fn run_metric_history_sampler(cache: TopicCache, history: MetricHistory) {
+    let mut interval = time::interval(Duration::from_millis(HZ_HISTORY_PERIOD_MS));
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let topics = cache.read().await;
+        let mut history = history.write().await;
+        history.topics.retain(|key, _| topics.contains_key(key));
+        for topic in topics.values() {
+            // Two decimals is plenty for a 16px-high line and keeps updates small
+            let hz = (alerts::effective_hz(topic, now) * 100.0).round() / 100.0;
+            let points = history.topics.entry(topic.key_expr.clone()).or_default();
+            points.push_back(MetricPoint {
+                at: now,
+                hz: hz as f32,
+                size: topic.last_data_size_bytes as f32,
+                bandwidth: (hz * topic.avg_data_size_bytes) as f32,
+            });
+            let cutoff = now.saturating_sub(METRIC_HISTORY_MS);
+            while points.front().is_some_and(|point| point.at < cutoff) {
+                points.pop_front();
+            }
+        }
+        history.revision += 1;
+    }
+}
+
+/// The Zenoh session, shared once the subscriber has opened it
+type ZenohSession = Arc<RwLock<Option<zenoh::Session>>>;
+
+/// The key expression the subscriber should be declared on. Sending a new one
+/// makes the running subscriber switch over; a restarted one starts on it.
+type Subscription = Arc<watch::Sender<String>>;
+
+/// Minimum and maximum delay before the watchdog respawns an exited subscriber
+const WATCHDOG_MIN_BACKOFF_MS: u64 = 1000;
+const WATCHDOG_MAX_BACKOFF_MS: u64 = 60_000;
+/// A subscriber that ran at least this long resets the backoff
+const WATCHDOG_STABLE_MS: u64 = 60_000;
+/// Attempts to re-declare a subscriber whose channel closed on an open session,
+/// before the watchdog restarts the whole session
+const RESUBSCRIBE_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum SubscriberStatus {
+    Connecting,
+    Connected {
+        since: u64,
+    },
+    /// Receiving failed and the subscriber is being re-declared on the same session
+    Resubscribing {
+        attempt: u32,
+        retry_at: u64,
+    },
+    Restarting {
+        restarts: u32,
+        retry_at: u64,
+    },
+    /// No session has opened yet, e.g. the router was down at startup. The
+    /// dashboard keeps serving while the watchdog retries.
+    Unreachable {
+        attempts: u32,
+        retry_at: u64,
+    },
+    /// Federation only: there is no local subscriber
+    Disabled,
+}
+
+impl SubscriberStatus {
+    /// Whether samples are being received, as reported by `/readyz` and the page's banner
+    fn is_healthy(&self) -> bool {
+        matches!(
+            self,
+            SubscriberStatus::Connected { .. } | SubscriberStatus::Disabled
+        )
+    }
+}
+
+type SubscriberState = Arc<RwLock<SubscriberStatus>>;
+
+/// Flipped to `true` on Ctrl-C so tasks can finish their work and SSE streams
+/// can say goodbye before the process exits
+type ShutdownSignal = watch::Receiver<bool>;
+/// Time given to every task to stop after Ctrl-C; a second Ctrl-C doesn't wait
+const SHUTDOWN_TIMEOUT_MS: u64 = 5000;
+
+/// Wall-clock milliseconds since the epoch. A clock set before the epoch reads
+/// as 0 rather than panicking.
 fn get_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+const NANOS_PER_MS: u64 = 1_000_000;
+
+/// Nanoseconds since the first call, on a clock that NTP steps and manual
+/// changes of the wall clock don't affect
+fn get_monotonic_ns() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
 }
 
 /// Converts a string to HTML-compliant format by escaping special characters
@@ -54,136 +851,1480 @@ fn html_escape_string(input: &str) -> String {
     html_escape::encode_text(input).to_string()
 }
 
-async fn start_zenoh_subscriber(
-    topic_cache: TopicCache,
-    interval_history: IntervalHistory,
-    decoder: DecoderFn,
-) -> Result<(), Box<dyn std::error::Error>> {
-    info!("Opening Zenoh session...");
-    let mut config = zenoh::Config::default();
-    config
-        .insert_json5("connect/endpoints", "['tcp/127.0.0.1:7447']")
-        .unwrap();
-    config.insert_json5("mode", "'peer'").unwrap();
-    let zenoh_session = zenoh::open(config).await.unwrap();
+/// How samples that reach the monitor more than once, over different routes, are treated
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum DuplicateSamples {
+    /// Every sample counts, including duplicates
+    Keep,
+    /// Drop samples whose Zenoh timestamp was already seen on the key.
+    /// Samples without a timestamp are always kept.
+    DropByTimestamp,
+    /// As `DropByTimestamp`, and also drop untimestamped samples whose payload
+    /// matches one received on the key within `window_ms`
+    DropByPayload { window_ms: u64 },
+}
 
-    let subscriber = zenoh_session
-        .declare_subscriber("**")
-        .await
-        .map_err(|e| format!("Failed to declare subscriber: {}", e))?;
+/// Which kinds of samples the subscriber records
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SampleKinds {
+    /// Puts, with a delete removing its key's topic
+    Both,
+    /// Deletes are dropped on arrival, so deleted topics stay listed
+    PutOnly,
+    /// Puts are dropped on arrival, so the table lists the keys being cleared
+    DeleteOnly,
+}
 
-    info!("Zenoh subscriber started");
-    while let Ok(sample) = subscriber.recv_async().await {
-        let key_expr = sample.key_expr().as_str().to_string();
-        let data_bytes = sample.payload().to_bytes().len() as u64;
-        let timestamp = get_timestamp();
+impl SampleKinds {
+    fn accepts(self, kind: SampleKind) -> bool {
+        match self {
+            SampleKinds::Both => true,
+            SampleKinds::PutOnly => matches!(kind, SampleKind::Put),
+            SampleKinds::DeleteOnly => matches!(kind, SampleKind::Delete),
+        }
+    }
+}
 
-        let mut history = interval_history.write().await;
-        let entry = history
-            .entry(key_expr.clone())
-            .or_insert((timestamp, Vec::new()));
+/// Recognise a payload identical to the previous one on its key (e.g. a
+/// keep-alive): it still counts toward the rate, but isn't decoded again and
+/// doesn't flash the row
+const SKIP_REPEATED_PAYLOADS: bool = false;
+/// Payloads are compared by a hash of at most this many leading bytes plus
+/// their length, so huge payloads cost no more to hash than this
+const PAYLOAD_HASH_PREFIX_BYTES: usize = 64 * 1024;
 
-        // compute delta against last timestamp
-        let last_ts = entry.0;
-        if timestamp > last_ts {
-            let delta = timestamp - last_ts;
-            entry.1.push(delta);
-            if entry.1.len() > WINDOW_SIZE {
-                entry.1.remove(0);
+/// Hash of the whole payload, for the decode cache: unlike the prefix hash, two
+/// payloads that differ anywhere must not share decoded content
+fn payload_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+fn payload_prefix_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(&bytes[..bytes.len().min(PAYLOAD_HASH_PREFIX_BYTES)]);
+    hasher.write_u64(bytes.len() as u64);
+    hasher.finish()
+}
+
+/// Number of recent samples remembered per key for duplicate detection
+const DUPLICATE_MEMORY: usize = 32;
+
+/// What identifies a sample for duplicate detection, and for how long
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fingerprint {
+    hash: u64,
+    window_ms: u64,
+}
+
+impl Fingerprint {
+    /// `None` when `mode` doesn't compare samples like this one
+    fn of(mode: DuplicateSamples, sample: &Sample) -> Option<Self> {
+        let mut hasher = std::hash::DefaultHasher::new();
+        // Zenoh timestamps are unique per publisher, so they identify a sample for good;
+        // a payload match only counts within the window
+        let window_ms = match (mode, sample.timestamp()) {
+            (DuplicateSamples::Keep, _) => return None,
+            (_, Some(zenoh_timestamp)) => {
+                zenoh_timestamp.hash(&mut hasher);
+                u64::MAX
+            }
+            (DuplicateSamples::DropByPayload { window_ms }, None) => {
+                sample.payload().to_bytes().hash(&mut hasher);
+                window_ms
             }
+            (DuplicateSamples::DropByTimestamp, None) => return None,
+        };
+        Some(Self {
+            hash: hasher.finish(),
+            window_ms,
+        })
+    }
+}
+
+/// A topic's recent sample fingerprints, so a sample arriving over a second
+/// route is recognised and does not inflate the rate
+#[derive(Debug, Clone, Default)]
+struct SeenSamples {
+    /// `(fingerprint, received_at)`, oldest first
+    entries: VecDeque<(Fingerprint, u64)>,
+}
+
+impl SeenSamples {
+    /// Whether a sample with this fingerprint was seen within its window before `now`
+    fn contains(&self, fingerprint: Fingerprint, now: u64) -> bool {
+        self.entries.iter().any(|(seen, at)| {
+            seen.hash == fingerprint.hash && now.saturating_sub(*at) <= seen.window_ms
+        })
+    }
+
+    /// Remembers the `DUPLICATE_MEMORY` latest fingerprints
+    fn record(&mut self, fingerprint: Fingerprint, now: u64) {
+        self.entries.push_back((fingerprint, now));
+        if self.entries.len() > DUPLICATE_MEMORY {
+            self.entries.pop_front();
         }
-        entry.0 = timestamp; // update last seen timestamp
+    }
+}
 
-        let estimated_hz = if !entry.1.is_empty() {
-            let avg_delta = entry.1.iter().sum::<u64>() as f64 / entry.1.len() as f64;
-            if avg_delta > 0.0 {
-                1000.0 / avg_delta
-            } else {
-                0.0
+/// Estimates the rate from a window of `(timestamp, interval)` pairs, intervals in nanoseconds
+fn window_hz(intervals: &VecDeque<(u64, u64)>) -> f64 {
+    if intervals.is_empty() {
+        return 0.0;
+    }
+    let total_s = intervals.iter().map(|(_, delta)| delta).sum::<u64>() as f64 / 1e9;
+    let avg_delta_s = total_s / intervals.len() as f64;
+    if avg_delta_s > 0.0 {
+        1.0 / avg_delta_s
+    } else {
+        0.0
+    }
+}
+
+/// The rate stored for a topic that has sent `message_count` samples: none
+/// until the second, since one sample has no interval and 0 Hz would read as a dead topic
+fn measured_hz(message_count: u64, window_hz: f64) -> Option<f64> {
+    (message_count >= 2).then(|| format::round_hz(window_hz))
+}
+
+/// Change from the previous payload size, in bytes and as a percentage of
+/// it; the percentage is taken over at least one byte so an empty previous
+/// payload doesn't divide by zero. A topic's first sample has no change.
+fn size_delta(previous_bytes: Option<u64>, bytes: u64) -> (i64, f64) {
+    match previous_bytes {
+        Some(previous_bytes) => {
+            let delta = bytes as i64 - previous_bytes as i64;
+            (delta, delta as f64 * 100.0 / previous_bytes.max(1) as f64)
+        }
+        None => (0, 0.0),
+    }
+}
+
+/// Counts topics by their first `depth` key segments
+fn prefix_counts<'a>(
+    keys: impl Iterator<Item = &'a String>,
+    depth: usize,
+) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for key in keys {
+        let prefix = key
+            .splitn(depth.max(1) + 1, '/')
+            .take(depth.max(1))
+            .collect::<Vec<_>>()
+            .join("/");
+        *counts.entry(prefix).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Decoded content of a topic's last few distinct payloads, by payload hash
+#[derive(Debug, Clone, Default)]
+struct DecodeCache {
+    /// Least recently used first; the content is HTML-escaped
+    entries: VecDeque<(u64, &'static str, String)>,
+}
+
+impl DecodeCache {
+    /// A hit becomes the most recently used entry
+    fn get(&mut self, hash: u64) -> Option<(&'static str, String)> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(entry_hash, ..)| *entry_hash == hash)?;
+        let entry = self.entries.remove(index)?;
+        let hit = (entry.1, entry.2.clone());
+        self.entries.push_back(entry);
+        Some(hit)
+    }
+
+    /// Keeps the `capacity` most recently used payloads
+    fn insert(&mut self, hash: u64, name: &'static str, content: String, capacity: usize) {
+        self.entries.retain(|(entry_hash, ..)| *entry_hash != hash);
+        self.entries.push_back((hash, name, content));
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    fn content_bytes(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(_, _, content)| content.len())
+            .sum()
+    }
+}
+
+/// Records a finished decode of the sample received at `timestamp`; `Ok`
+/// content is already HTML-escaped. Every attempt counts toward the error
+/// stats, even if a newer sample's decode has already replaced the content.
+/// A failure replaces the content unless `keep_last_good`.
+fn record_decode(
+    topic: &mut TopicData,
+    result: Result<(&'static str, String), String>,
+    timestamp: u64,
+    alpha: f64,
+    keep_last_good: bool,
+) {
+    let failed = result.is_err();
+    topic.decode_error_fraction +=
+        alpha * (f64::from(u8::from(failed)) - topic.decode_error_fraction);
+    if failed {
+        topic.decode_errors += 1;
+        topic.decode_success_streak = 0;
+    } else {
+        topic.decode_success_streak += 1;
+    }
+
+    if topic.decoded_timestamp < timestamp {
+        match result {
+            Ok((name, content)) => {
+                topic.decoded_by = Some(name.to_string());
+                topic.decoded_content = Some(content);
             }
-        } else {
-            0.0
+            Err(error) => {
+                if !keep_last_good {
+                    topic.decoded_by = None;
+                    topic.decoded_content = Some(html_escape_string(&error));
+                }
+                topic.last_decode_error = Some(error);
+                topic.last_decode_error_at = Some(timestamp);
+            }
+        }
+        topic.decoded_timestamp = timestamp;
+    }
+}
+
+/// Bounds how many decodes run at once on the blocking pool
+#[derive(Clone)]
+struct DecodeLimiter {
+    permits: Arc<Semaphore>,
+    /// Samples that were not decoded because every permit was taken
+    skipped: Arc<AtomicU64>,
+    /// Samples whose decoded content came from their topic's decode cache
+    cache_hits: Arc<AtomicU64>,
+    /// Set by the memory guard while shedding load; no decodes are started
+    suspended: Arc<AtomicBool>,
+    /// Decodes run at once, from `Config::decode_concurrency`
+    concurrency: usize,
+    /// Distinct payloads kept per topic, from `Config::decode_cache_entries`
+    cache_entries: usize,
+    /// From `Config::keep_last_good_decode`
+    keep_last_good: bool,
+}
+
+impl DecodeLimiter {
+    fn new(config: &Config) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.decode_concurrency.max(1))),
+            skipped: Arc::new(AtomicU64::new(0)),
+            cache_hits: Arc::new(AtomicU64::new(0)),
+            suspended: Arc::new(AtomicBool::new(false)),
+            concurrency: config.decode_concurrency,
+            cache_entries: config.decode_cache_entries,
+            keep_last_good: config.keep_last_good_decode,
+        }
+    }
+
+    /// Decodes the sample on the blocking pool and stores the result in the cache,
+    /// unless a newer sample's decode has already landed. A successful decode
+    /// of a payload with a `payload_hash` also goes into the topic's decode cache.
+    fn spawn_decode(
+        &self,
+        decoders: &'static [NamedDecoder],
+        sample: Sample,
+        timestamp: u64,
+        topic_cache: TopicCache,
+        payload_hash: Option<u64>,
+        alpha: f64,
+    ) {
+        if self.suspended.load(Ordering::Relaxed) {
+            return;
+        }
+        let key_expr = sample.key_expr().as_str().to_string();
+        let Ok(permit) = self.permits.clone().try_acquire_owned() else {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            debug!("Decoder busy, skipping decode for '{}'", key_expr);
+            return;
         };
 
-        // Apply decoder if provided
-        let decoded_content = decoder.map(|decode_fn| {
-            let raw_decoded = decode_fn(sample.clone());
-            html_escape_string(&raw_decoded)
+        let (cache_entries, keep_last_good) = (self.cache_entries, self.keep_last_good);
+        tokio::spawn(async move {
+            let decoded = tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                decoder::decode_chain(decoders, sample)
+            })
+            .await;
+
+            let result = match decoded {
+                Ok(result) => result.map(|(name, text)| (name, html_escape_string(&text))),
+                Err(e) => {
+                    error!("Decoder failed on '{}': {}", key_expr, e);
+                    Err(format!("Decoder failed: {}", e))
+                }
+            };
+
+            let mut cache = topic_cache.write().await;
+            let Some(topic) = cache.get_mut(&key_expr) else {
+                return;
+            };
+            if let (Some(hash), Ok((name, content))) = (payload_hash, &result) {
+                topic
+                    .decodes
+                    .insert(hash, name, content.clone(), cache_entries);
+            }
+            record_decode(topic, result, timestamp, alpha, keep_last_good);
         });
+    }
+}
 
-        let topic_data = TopicData {
-            key_expr: key_expr.clone(),
-            last_data_size_bytes: data_bytes,
-            received_timestamp: timestamp,
-            decoded_content,
-            estimated_hz,
-        };
+/// A publisher as `<name>:<entity id>`, using `names` (`SOURCE_NAMES`) for known sessions
+fn source_label(id: &zenoh::session::EntityGlobalId, names: &[(&str, &'static str)]) -> String {
+    let zid = id.zid().to_string();
+    let name = names
+        .iter()
+        .find(|(known, _)| *known == zid)
+        .map_or(zid.as_str(), |(_, name)| name);
+    format!("{}:{}", name, id.eid())
+}
+
+/// True for values like `#fff3cd` or `rgb(255, 243, 205)` that are safe to put into a stylesheet
+fn is_plain_css_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c))
+}
 
-        debug!("Received data for topic '{}'", key_expr);
-        topic_cache.write().await.insert(key_expr, topic_data);
+/// Builds the session config from the mode and connect endpoints. Each value is
+/// parsed into its Zenoh type first, so a typo is reported against the setting
+/// it came from instead of as an opaque JSON5 error.
+fn zenoh_config(
+    mode: &str,
+    endpoints: &[&str],
+    namespace: Option<&str>,
+) -> Result<zenoh::Config, String> {
+    let mode = WhatAmI::from_str(mode).map_err(|_| {
+        format!(
+            "invalid zenoh_mode '{}', expected peer, client or router",
+            mode
+        )
+    })?;
+    let endpoints = endpoints
+        .iter()
+        .map(|endpoint| {
+            EndPoint::from_str(endpoint)
+                .map(|endpoint| endpoint.to_string())
+                .map_err(|e| {
+                    format!(
+                        "invalid zenoh_connect_endpoints entry '{}': {}",
+                        endpoint, e
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut config = zenoh::Config::default();
+    let endpoints_json = serde_json::to_string(&endpoints).map_err(|e| e.to_string())?;
+    config
+        .insert_json5("connect/endpoints", &endpoints_json)
+        .map_err(|e| {
+            format!(
+                "cannot set zenoh_connect_endpoints to {}: {}",
+                endpoints_json, e
+            )
+        })?;
+    let mode_json = serde_json::json!(mode.to_str()).to_string();
+    config
+        .insert_json5("mode", &mode_json)
+        .map_err(|e| format!("cannot set zenoh_mode to {}: {}", mode_json, e))?;
+    if let Some(namespace) = namespace {
+        // Zenoh rejects wildcards and empty chunks here
+        let namespace_json = serde_json::json!(namespace).to_string();
+        config
+            .insert_json5("namespace", &namespace_json)
+            .map_err(|e| format!("cannot set zenoh_namespace to {}: {}", namespace_json, e))?;
     }
+    Ok(config)
+}
 
-    Ok(())
+/// Records a sample arriving on `key_expr` and returns the topic's rate,
+/// interval statistics and the gap the sample ended, if any. A sample that
+/// doesn't count toward the rate leaves the history untouched.
+///
+/// A gap clears the window instead of joining it: one long pause would
+/// otherwise drag the rate down for the next `WINDOW_SIZE` samples. The sample
+/// ending the gap reports the rate from before it, and the rate after it is
+/// built from the intervals that follow.
+///
+/// Intervals are measured in nanoseconds on the monotonic `timestamp`, so a
+/// 5 kHz topic's 0.2 ms intervals keep their precision and a wall-clock step
+/// can't produce a negative or huge interval; `wall` only dates the gap.
+/// Depends on nothing but its arguments, so a sequence of timestamps always
+/// gives the same rates. `gap_factor` and `gap_max_interval_ms` are
+/// `Config::gap_factor` and `Config::gap_max_interval_ms`.
+fn record_arrival(
+    window: &mut IntervalWindow,
+    rate_window: RateWindow,
+    gap_factor: f64,
+    gap_max_interval_ms: Option<u64>,
+    timestamp: u64,
+    wall: u64,
+    counts_toward_hz: bool,
+) -> (f64, IntervalStats, Option<Gap>) {
+    let intervals = &mut window.intervals;
+    if !counts_toward_hz {
+        return (window_hz(intervals), IntervalStats::of(intervals), None);
+    }
+
+    // compute delta against last timestamp; the first sample has none. Samples
+    // delivered in one batch have a delta of 0, which is kept so bursts count.
+    if let Some(last_ts) = window.last_arrival.replace(timestamp) {
+        let delta = timestamp.saturating_sub(last_ts);
+        let delta_ms = delta as f64 / NANOS_PER_MS as f64;
+        let is_gap = (intervals.len() >= GAP_MIN_INTERVALS
+            && delta_ms > gap_factor * IntervalStats::of(intervals).avg_ms)
+            || gap_max_interval_ms.is_some_and(|max| delta > max * NANOS_PER_MS);
+        if is_gap {
+            let (hz, stats) = (window_hz(intervals), IntervalStats::of(intervals));
+            intervals.clear();
+            let duration_ms = delta / NANOS_PER_MS;
+            let gap = Gap {
+                started_at: wall.saturating_sub(duration_ms),
+                duration_ms,
+            };
+            return (hz, stats, Some(gap));
+        }
+        intervals.push_back((timestamp, delta));
+        rate_window.prune(intervals, timestamp);
+    }
+
+    (window_hz(intervals), IntervalStats::of(intervals), None)
 }
 
-/// Generate HTML for the web UI.
-/// `has_decoder`: whether to include the decoded-content column.
-/// Returns the full HTML page as a `String`.
-fn generate_html(has_decoder: bool) -> String {
-    let decoder_column_header = if has_decoder {
-        "<th>Decoded Content</th>"
-    } else {
-        ""
+/// Removes a topic from the cache, tombstoning it so a return is reported as a
+/// reappearance by both SSE streams and notifiers. Every removal goes through
+/// here, so a topic removed and re-added between two polls is still flagged.
+fn remove_topic(
+    cache: &mut Topics,
+    tombstones: &mut TombstoneStore,
+    key_expr: &str,
+    now: u64,
+) -> Option<TopicState> {
+    let topic = cache.remove(key_expr)?;
+    // A placeholder never appeared, so it can't reappear
+    if !topic.is_placeholder() {
+        tombstones.insert(key_expr, now);
+    }
+    Some(topic)
+}
+
+/// `HashMap::retain` through `remove_topic`. Returns the number removed.
+fn retain_topics(
+    cache: &mut Topics,
+    tombstones: &mut TombstoneStore,
+    mut keep: impl FnMut(&str, &TopicState) -> bool,
+) -> usize {
+    let now = get_timestamp();
+    let dropped: Vec<String> = cache
+        .iter()
+        .filter(|(key, topic)| !keep(key, topic))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in &dropped {
+        remove_topic(cache, tombstones, key, now);
+    }
+    dropped.len()
+}
+
+/// Drops local topics that `key_expr` no longer covers. Federated topics
+/// don't come from the subscriber and stay, and so do pinned ones.
+async fn retain_subscribed(topic_cache: &TopicCache, tombstones: &Tombstones, key_expr: &str) {
+    let dropped = {
+        let mut cache = topic_cache.write().await;
+        retain_topics(&mut cache, &mut *tombstones.write().await, |key, topic| {
+            topic.instance.is_some() || topic.pinned || alerts::key_matches(key_expr, key)
+        })
     };
+    info!(
+        "Dropped {} topic(s) outside the subscription '{}'",
+        dropped, key_expr
+    );
+}
 
-    // let topic_column_width = if has_decoder { "25%" } else { "75%" };
-    // let size_column_width = if has_decoder { "5%" } else { "5%" };
-    // let freq_column_width = if has_decoder { "5%" } else { "5%" };
-    // let timestamp_column_width = if has_decoder { "15%" } else { "15%" };
-    // let decoder_column_width = if has_decoder { "50%" } else { "0%" };
+/// Drops the topic of a deleted key, and with it the key's interval window and
+/// decode cache. A pinned topic stays, as a placeholder; topics from federated
+/// instances are left to their own monitor.
+async fn remove_deleted(topic_cache: &TopicCache, tombstones: &Tombstones, key_expr: &str) {
+    let mut cache = topic_cache.write().await;
+    let Some(topic) = cache.get(key_expr) else {
+        return;
+    };
+    if topic.instance.is_some() {
+        return;
+    }
+    if topic.pinned {
+        cache.insert(
+            key_expr.to_string(),
+            TopicData::placeholder(key_expr).into(),
+        );
+    } else {
+        remove_topic(
+            &mut cache,
+            &mut *tombstones.write().await,
+            key_expr,
+            get_timestamp(),
+        );
+    }
+    debug!("'{}' was deleted, dropped it from the cache", key_expr);
+}
 
-    let topic_column_width = "25%";
-    let size_column_width = "5%";
-    let freq_column_width = "7%";
-    let timestamp_column_width = "18%";
-    let decoder_column_width = if has_decoder { "45%" } else { "0%" };
+/// What the subscribe loop receives samples from, so the recovery from a
+/// failed receive can run against a fake
+trait SampleReceiver {
+    async fn recv(&self) -> Result<Sample, String>;
+}
 
-    format!(
-        r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-<meta charset="UTF-8">
-<meta name="viewport" content="width=device-width, initial-scale=1.0">
-<title>Zenoh DDS Topic Monitor</title>
-<style>
-    body {{
-        display: flex;
-        flex-direction: column;
-        height: 100vh;
-        margin: 0;
-        font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
-        background-color: #f5f7fa;
-        color: #333;
-    }}
-    .header {{
-        text-align: center;
-        margin-bottom: 30px;
-        flex-shrink: 0;
-    }}
-    .header h1 {{
-        color: #2c3e50;
-        margin: 0;
-        font-size: 2.5rem;
-        font-weight: 300;
-    }}
-    .header p {{
-        color: #7f8c8d;
-        margin: 10px 0 0 0;
-        font-size: 1.1rem;
+impl SampleReceiver for zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<Sample>> {
+    async fn recv(&self) -> Result<Sample, String> {
+        self.recv_async().await.map_err(|e| e.to_string())
+    }
+}
+
+/// Where `SampleReceiver`s are re-declared from
+trait SubscriberSession {
+    type Receiver: SampleReceiver;
+
+    fn is_closed(&self) -> bool;
+
+    async fn declare(&self, key: &str) -> Result<Self::Receiver, String>;
+}
+
+impl SubscriberSession for zenoh::Session {
+    type Receiver = zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<Sample>>;
+
+    fn is_closed(&self) -> bool {
+        zenoh::Session::is_closed(self)
+    }
+
+    async fn declare(&self, key: &str) -> Result<Self::Receiver, String> {
+        self.declare_subscriber(key.to_string())
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Handles a failed receive on `key`. A closed session can't be recovered
+/// here and is returned as an error for the watchdog to open a new one;
+/// otherwise the subscriber is re-declared on the same session.
+async fn recover<S: SubscriberSession>(
+    session: &S,
+    key: &str,
+    status: &SubscriberState,
+    config: &Config,
+    error: String,
+) -> Result<S::Receiver, String> {
+    error!("Zenoh subscriber on '{}' stopped receiving: {}", key, error);
+    if session.is_closed() {
+        return Err(format!("Zenoh session closed: {}", error));
+    }
+    resubscribe(session, key, status, config).await
+}
+
+/// Re-declares the subscriber on `key` after its channel closed while the
+/// session stayed open, backing off between attempts as the watchdog does.
+/// Gives up after `RESUBSCRIBE_ATTEMPTS`, leaving the watchdog to restart the session.
+async fn resubscribe<S: SubscriberSession>(
+    session: &S,
+    key: &str,
+    status: &SubscriberState,
+    config: &Config,
+) -> Result<S::Receiver, String> {
+    let (min_backoff_ms, max_backoff_ms) = config.watchdog_backoff_ms;
+    let mut backoff_ms = min_backoff_ms;
+    for attempt in 1..=RESUBSCRIBE_ATTEMPTS {
+        let retry_at = get_timestamp() + backoff_ms;
+        *status.write().await = SubscriberStatus::Resubscribing { attempt, retry_at };
+        warn!(
+            "Re-declaring the subscriber on '{}' in {}ms (attempt {}/{})",
+            key, backoff_ms, attempt, RESUBSCRIBE_ATTEMPTS
+        );
+        time::sleep(Duration::from_millis(backoff_ms)).await;
+        match session.declare(key).await {
+            Ok(subscriber) => {
+                info!("Zenoh subscriber on '{}' recovered", key);
+                *status.write().await = SubscriberStatus::Connected {
+                    since: get_timestamp(),
+                };
+                return Ok(subscriber);
+            }
+            Err(e) => error!("Failed to re-declare subscriber on '{}': {}", key, e),
+        }
+        backoff_ms = (backoff_ms * 2).min(max_backoff_ms);
+    }
+    Err(format!(
+        "gave up re-declaring the subscriber on '{}' after {} attempts",
+        key, RESUBSCRIBE_ATTEMPTS
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn start_zenoh_subscriber(
+    topic_cache: TopicCache,
+    tombstones: Tombstones,
+    session: ZenohSession,
+    status: SubscriberState,
+    subscription: Subscription,
+    decoders: &'static [NamedDecoder],
+    decode_limiter: DecodeLimiter,
+    processing_latency: Option<ProcessingLatencies>,
+    config: Arc<Config>,
+    session_config: zenoh::Config,
+    clock: SharedClock,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!(
+        "Opening Zenoh session, connecting to {}...",
+        config.zenoh_target()
+    );
+    let zenoh_session = zenoh::open(session_config)
+        .await
+        .map_err(|e| format!("Failed to open Zenoh session: {}", e))?;
+    *session.write().await = Some(zenoh_session.clone());
+
+    let mut key_changes = subscription.subscribe();
+    let mut key = key_changes.borrow_and_update().clone();
+    let mut subscriber = zenoh_session
+        .declare_subscriber(key.clone())
+        .await
+        .map_err(|e| format!("Failed to declare subscriber on '{}': {}", key, e))?;
+
+    info!("Zenoh subscriber started on '{}'", key);
+    *status.write().await = SubscriberStatus::Connected {
+        since: get_timestamp(),
+    };
+    // Declared after the subscriber, so nothing published meanwhile is missed
+    if let Some(seed) = config.seed_query {
+        let zenoh_session = zenoh_session.clone();
+        let key = key.clone();
+        let topic_cache = topic_cache.clone();
+        let units = config.display_units;
+        tokio::spawn(async move {
+            query::seed(&zenoh_session, &key, seed, &topic_cache, decoders, units).await;
+        });
+    }
+    loop {
+        let sample = tokio::select! {
+            sample = subscriber.recv() => match sample {
+                Ok(sample) => sample,
+                Err(e) => {
+                    subscriber = recover(&zenoh_session, &key, &status, &config, e).await?;
+                    continue;
+                }
+            },
+            Ok(()) = key_changes.changed() => {
+                key = key_changes.borrow_and_update().clone();
+                // The new subscriber is declared before the old one is dropped
+                // (and undeclared), so topics covered by both see no gap
+                subscriber = zenoh_session
+                    .declare_subscriber(key.clone())
+                    .await
+                    .map_err(|e| format!("Failed to declare subscriber on '{}': {}", key, e))?;
+                info!("Zenoh subscriber moved to '{}'", key);
+                retain_subscribed(&topic_cache, &tombstones, &key).await;
+                continue;
+            }
+        };
+
+        cache_sample(
+            sample,
+            &topic_cache,
+            &tombstones,
+            decoders,
+            &decode_limiter,
+            processing_latency.as_ref(),
+            &config,
+            &clock,
+        )
+        .await;
+    }
+}
+
+/// Caches one received sample: updates its topic's rate, size and timing
+/// stats and starts its decode, or removes the topic for a delete
+#[allow(clippy::too_many_arguments)]
+async fn cache_sample(
+    sample: Sample,
+    topic_cache: &TopicCache,
+    tombstones: &Tombstones,
+    decoders: &'static [NamedDecoder],
+    decode_limiter: &DecodeLimiter,
+    processing_latency: Option<&ProcessingLatencies>,
+    config: &Config,
+    clock: &SharedClock,
+) {
+    let started = Instant::now();
+    let alpha = config.window_alpha();
+    // Key expressions are canonical, so the same topic seen over any route
+    // maps to the same cache entry
+    let key_expr = sample.key_expr().as_str().to_string();
+    let data_bytes = sample.payload().len() as u64;
+    let timestamp = clock.now_ms();
+    let arrival = clock.monotonic_ns();
+
+    if !config.sample_kinds.accepts(sample.kind()) {
+        return;
+    }
+    // The key is gone, so its row goes too; streams then list it as removed
+    if matches!(sample.kind(), SampleKind::Delete) && config.sample_kinds == SampleKinds::Both {
+        remove_deleted(topic_cache, tombstones, &key_expr).await;
+        return;
+    }
+    // Below the threshold, a sample affects nothing: not the cache, rates or sizes
+    if data_bytes < config.min_payload_bytes {
+        return;
+    }
+
+    let fingerprint = Fingerprint::of(config.duplicate_samples, &sample);
+    let received_ms = arrival / NANOS_PER_MS;
+    let mut cache = topic_cache.write().await;
+    if let Some(fingerprint) = fingerprint
+        && let Some(topic) = cache.get_mut(&key_expr)
+        && topic.seen.contains(fingerprint, received_ms)
+    {
+        debug!("Dropping duplicate sample on '{}'", key_expr);
+        topic.duplicate_samples += 1;
+        return;
+    }
+
+    let empty_payload = data_bytes == 0;
+
+    // The window, decode cache and fingerprints move into the topic's new entry below
+    let (mut intervals, mut decodes, mut seen) = cache
+        .get_mut(&key_expr)
+        .map(|topic| {
+            (
+                std::mem::take(&mut topic.intervals),
+                std::mem::take(&mut topic.decodes),
+                std::mem::take(&mut topic.seen),
+            )
+        })
+        .unwrap_or_default();
+    if let Some(fingerprint) = fingerprint {
+        seen.record(fingerprint, received_ms);
+    }
+    let counts_toward_hz = !empty_payload || config.empty_payloads_count_toward_hz;
+    let (estimated_hz, interval_stats, gap) = record_arrival(
+        &mut intervals,
+        config.rate_window,
+        config.gap_factor,
+        config.gap_max_interval_ms,
+        arrival,
+        timestamp,
+        counts_toward_hz,
+    );
+
+    let pinned = cache.get(&key_expr).is_some_and(|topic| topic.pinned);
+    // A pinned placeholder has seen no sample, so there is nothing to carry over
+    let previous = cache
+        .get(&key_expr)
+        .filter(|previous| !previous.is_placeholder());
+
+    let message_count =
+        previous.map_or(0, |previous| previous.message_count) + u64::from(counts_toward_hz);
+    let estimated_hz = measured_hz(message_count, estimated_hz);
+
+    let payload_prefix_hash = payload_prefix_hash(&sample.payload().to_bytes());
+    let identical = previous.is_some_and(|p| p.payload_prefix_hash == payload_prefix_hash);
+    let repeated_payload = config.skip_repeated_payloads && identical;
+    let (identical_repeat_count, distinct_payloads) = match previous {
+        Some(previous) if identical => (
+            previous.identical_repeat_count + 1,
+            previous.distinct_payloads,
+        ),
+        Some(previous) => (0, previous.distinct_payloads + 1),
+        None => (0, 1),
+    };
+
+    // Decoding happens off the subscribe loop, so the previous decoded
+    // content is carried over until the new one lands. An empty payload
+    // has nothing to decode.
+    let (decoded_content, decoded_by, decoded_timestamp) = match previous {
+        Some(previous) if !empty_payload => (
+            previous.decoded_content.clone(),
+            previous.decoded_by.clone(),
+            previous.decoded_timestamp,
+        ),
+        _ => (None, None, 0),
+    };
+    let (decode_errors, decode_error_fraction, decode_success_streak, last_decode_error) =
+        match previous {
+            Some(previous) => (
+                previous.decode_errors,
+                previous.decode_error_fraction,
+                previous.decode_success_streak,
+                previous.last_decode_error.clone(),
+            ),
+            None => (0, 0.0, 0, None),
+        };
+    let last_decode_error_at = previous.and_then(|previous| previous.last_decode_error_at);
+    let (size_delta_bytes, size_delta_pct) = size_delta(
+        previous.map(|previous| previous.last_data_size_bytes),
+        data_bytes,
+    );
+    let duplicate_samples = previous.map_or(0, |previous| previous.duplicate_samples);
+
+    // Only timestamps from the same publisher are comparable; the newest is kept
+    let last_source_timestamp = previous.and_then(|previous| previous.source_timestamp);
+    let out_of_order = match (&last_source_timestamp, sample.timestamp()) {
+        (Some(last), Some(current)) => {
+            current.get_id() == last.get_id() && current.get_time() < last.get_time()
+        }
+        _ => false,
+    };
+    let source_timestamp = if out_of_order {
+        last_source_timestamp
+    } else {
+        sample.timestamp().copied().or(last_source_timestamp)
+    };
+    // Samples without source info keep what was learned from earlier ones
+    let mut recent_sources =
+        previous.map_or_else(Vec::new, |previous| previous.recent_sources.clone());
+    let last_source = match sample.source_info() {
+        Some(info) => {
+            let label = source_label(info.source_id(), config.source_names);
+            recent_sources.retain(|(source, seen)| {
+                *source != label && timestamp.saturating_sub(*seen) <= config.source_window_ms
+            });
+            recent_sources.push((label.clone(), timestamp));
+            Some(label)
+        }
+        None => previous.and_then(|previous| previous.last_source.clone()),
+    };
+    let source_count = u16::try_from(recent_sources.len()).unwrap_or(u16::MAX);
+
+    let out_of_order_samples =
+        previous.map_or(0, |previous| previous.out_of_order_samples) + u64::from(out_of_order);
+    let out_of_order_fraction = previous.map_or(0.0, |previous| {
+        previous.out_of_order_fraction
+            + alpha * (f64::from(u8::from(out_of_order)) - previous.out_of_order_fraction)
+    });
+    // Kept until the highlighter and rate expectations next run, so rows don't
+    // flicker on every sample
+    let highlight = previous.and_then(|previous| previous.highlight.clone());
+    let expected_hz = previous.and_then(|previous| previous.expected_hz);
+    let hz_deviation_pct = previous.and_then(|previous| previous.hz_deviation_pct);
+    let gap_count = previous.map_or(0, |previous| previous.gap_count) + u64::from(gap.is_some());
+    let mut recent_gaps =
+        previous.map_or_else(VecDeque::new, |previous| previous.recent_gaps.clone());
+    if let Some(gap) = gap {
+        recent_gaps.push_back(gap);
+        while recent_gaps.len() > RECENT_GAPS {
+            recent_gaps.pop_front();
+        }
+    }
+
+    // The size baseline trails by one sample so a sudden jump is compared
+    // against what the topic looked like before it.
+    let (avg_data_size_bytes, empty_streak) = match previous {
+        Some(previous) => (
+            previous.avg_data_size_bytes
+                + alpha * (previous.last_data_size_bytes as f64 - previous.avg_data_size_bytes),
+            if data_bytes == 0 {
+                previous.empty_streak + 1
+            } else {
+                0
+            },
+        ),
+        None => (data_bytes as f64, u64::from(data_bytes == 0)),
+    };
+
+    let stability = stability_score(
+        &interval_stats,
+        &recent_gaps,
+        out_of_order_fraction,
+        timestamp,
+        config.stability_weights,
+    );
+
+    let jitter_ms = interval_stats.jitter_ms;
+    let latency_ms = timing::latency_ms(&sample, timestamp);
+    let (jitter_exceeded, latency_exceeded) =
+        timing::exceeded(config.timing_thresholds, &key_expr, jitter_ms, latency_ms);
+
+    let mut topic_data = TopicData {
+        key_expr: key_expr.clone(),
+        last_data_size_bytes: data_bytes,
+        received_timestamp: timestamp,
+        decoded_content,
+        decoded_by,
+        estimated_hz,
+        message_count,
+        avg_data_size_bytes,
+        size_delta_bytes,
+        size_delta_pct,
+        empty_streak,
+        empty_payload,
+        decoded_timestamp,
+        decode_errors,
+        decode_error_fraction,
+        decode_success_streak,
+        last_decode_error,
+        last_decode_error_at,
+        duplicate_samples,
+        out_of_order_samples,
+        out_of_order_fraction,
+        source_timestamp,
+        stability,
+        jitter_ms,
+        latency_ms,
+        jitter_exceeded,
+        latency_exceeded,
+        last_source,
+        source_count,
+        recent_sources,
+        // Kept until the liveliness counter next runs
+        publisher_count: previous.and_then(|previous| previous.publisher_count),
+        publisher_conflict: previous.is_some_and(|previous| previous.publisher_conflict),
+        instance: None,
+        repeated_payload,
+        identical_repeat_count,
+        distinct_payloads,
+        payload_prefix_hash,
+        interval_stats,
+        recent_gaps,
+        gap_count,
+        expected_hz,
+        hz_deviation_pct,
+        highlight,
+        pinned,
+        size_display: format::size(data_bytes as f64, config.display_units),
+        hz_display: estimated_hz.map_or_else(|| format::hz_pending(message_count), format::hz),
+        bandwidth_display: format::bandwidth(
+            estimated_hz.unwrap_or(0.0) * avg_data_size_bytes,
+            config.display_units,
+        ),
+        jitter_display: format::duration(jitter_ms, config.timing_unit),
+        latency_display: latency_ms.map_or_else(
+            || "-".to_string(),
+            |latency| format::duration(latency, config.timing_unit),
+        ),
+    };
+
+    // Apply the decoder chain, if any. A repeated payload keeps its decoded
+    // content, and a payload the topic sent before reuses its cached decode.
+    let decode = !decoders.is_empty() && !empty_payload && !repeated_payload;
+    let decode_hash = (decode && decode_limiter.cache_entries != 0)
+        .then(|| payload_hash(&sample.payload().to_bytes()));
+    let cached_decode = decode_hash.and_then(|hash| decodes.get(hash));
+    let decode_cached = cached_decode.is_some();
+    if let Some(cached) = cached_decode {
+        record_decode(
+            &mut topic_data,
+            Ok(cached),
+            timestamp,
+            alpha,
+            config.keep_last_good_decode,
+        );
+        decode_limiter.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    debug!("Received data for topic '{}'", key_expr);
+    cache.insert(
+        key_expr,
+        TopicState {
+            data: topic_data,
+            intervals,
+            decodes,
+            seen,
+        },
+    );
+    drop(cache);
+
+    if decode && !decode_cached {
+        decode_limiter.spawn_decode(
+            decoders,
+            sample,
+            timestamp,
+            topic_cache.clone(),
+            decode_hash,
+            alpha,
+        );
+    }
+    if let Some(latency) = processing_latency {
+        latency.write().await.record(started.elapsed());
+    }
+}
+
+/// Keeps the subscriber running, respawning it with exponential backoff whenever it exits.
+/// At the shutdown signal, drops the subscriber and closes the session. Gives up with
+/// `StartupError::ZenohUnreachable` once `zenoh_open_attempts` sessions failed to open.
+#[allow(clippy::too_many_arguments)]
+async fn run_subscriber_watchdog(
+    topic_cache: TopicCache,
+    tombstones: Tombstones,
+    session: ZenohSession,
+    status: SubscriberState,
+    subscription: Subscription,
+    decoders: &'static [NamedDecoder],
+    decode_limiter: DecodeLimiter,
+    processing_latency: Option<ProcessingLatencies>,
+    notifiers: NotifierHub,
+    config: Arc<Config>,
+    session_config: zenoh::Config,
+    clock: SharedClock,
+    mut shutdown: ShutdownSignal,
+) -> Result<(), StartupError> {
+    let mut restarts: u32 = 0;
+    let (min_backoff_ms, max_backoff_ms) = config.watchdog_backoff_ms;
+    let mut backoff_ms = min_backoff_ms;
+    // Failures before the first session opened, counted against `zenoh_open_attempts`
+    let mut opened = false;
+    let mut open_failures: u32 = 0;
+
+    loop {
+        *status.write().await = SubscriberStatus::Connecting;
+        let started = get_timestamp();
+
+        let mut handle = tokio::spawn({
+            let topic_cache = topic_cache.clone();
+            let tombstones = tombstones.clone();
+            let session = session.clone();
+            let status = status.clone();
+            let subscription = subscription.clone();
+            let decode_limiter = decode_limiter.clone();
+            let processing_latency = processing_latency.clone();
+            let config = config.clone();
+            let session_config = session_config.clone();
+            let clock = clock.clone();
+            async move {
+                start_zenoh_subscriber(
+                    topic_cache,
+                    tombstones,
+                    session,
+                    status,
+                    subscription,
+                    decoders,
+                    decode_limiter,
+                    processing_latency,
+                    config,
+                    session_config,
+                    clock,
+                )
+                .await
+                .map_err(|e| e.to_string())
+            }
+        });
+
+        let result = tokio::select! {
+            result = &mut handle => result,
+            () = shutdown::stopping(&mut shutdown) => {
+                handle.abort();
+                let _ = handle.await;
+                if let Some(session) = session.write().await.take()
+                    && let Err(e) = session.close().await
+                {
+                    warn!("Failed to close Zenoh session: {}", e);
+                }
+                return Ok(());
+            }
+        };
+        let failure = match result {
+            Ok(Ok(())) => "the subscriber exited unexpectedly".to_string(),
+            Ok(Err(e)) => e,
+            Err(e) => format!("the subscriber task failed: {}", e),
+        };
+        error!("Zenoh subscriber error: {}", failure);
+
+        // Drop the dead session so nothing keeps publishing on it
+        opened |= session.write().await.take().is_some();
+        if !opened {
+            open_failures += 1;
+            if let Some(e) = StartupError::unreachable(&config, open_failures, &failure) {
+                return Err(e);
+            }
+        }
+
+        if get_timestamp().saturating_sub(started) >= WATCHDOG_STABLE_MS {
+            backoff_ms = min_backoff_ms;
+        }
+        restarts += 1;
+        let retry_at = get_timestamp() + backoff_ms;
+        *status.write().await = if opened {
+            SubscriberStatus::Restarting { restarts, retry_at }
+        } else {
+            SubscriberStatus::Unreachable {
+                attempts: open_failures,
+                retry_at,
+            }
+        };
+        notifiers
+            .dispatch(&notify::Event::SubscriberRestarting {
+                restarts,
+                retry_at,
+                timestamp: get_timestamp(),
+            })
+            .await;
+        warn!(
+            "Restarting Zenoh subscriber in {}ms (restart #{})",
+            backoff_ms, restarts
+        );
+
+        tokio::select! {
+            () = time::sleep(Duration::from_millis(backoff_ms)) => {}
+            () = shutdown::stopping(&mut shutdown) => return Ok(()),
+        }
+        backoff_ms = (backoff_ms * 2).min(max_backoff_ms);
+    }
+}
+
+/// A column of the topic table, configured by name in `UI_COLUMNS`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Column {
+    Key,
+    Size,
+    Hz,
+    /// Bytes per second, from the rate and the rolling average size
+    Bandwidth,
+    Timestamp,
+    /// Time since the last sample, computed on the server's clock
+    Age,
+    /// Bar of the topic's load relative to the busiest topic
+    Load,
+    /// Stability score, colour-coded
+    Stability,
+    /// Inter-arrival jitter, coloured beyond its `TIMING_THRESHOLDS` bound
+    Jitter,
+    /// Delay since the sample's Zenoh timestamp, coloured beyond its bound
+    Latency,
+    /// Publisher of the latest sample, red when several publish on the key
+    Source,
+    /// Live publishers announced by liveliness tokens, red when only one is expected
+    Publishers,
+    /// Monitor the topic was federated from, or "local"
+    Instance,
+    Decoded,
+    /// Alert state of the topic (firing, acknowledged or silenced)
+    Status,
+}
+
+impl Column {
+    const ALL: &[Column] = &[
+        Column::Key,
+        Column::Size,
+        Column::Hz,
+        Column::Bandwidth,
+        Column::Timestamp,
+        Column::Age,
+        Column::Load,
+        Column::Stability,
+        Column::Jitter,
+        Column::Latency,
+        Column::Source,
+        Column::Publishers,
+        Column::Instance,
+        Column::Decoded,
+        Column::Status,
+    ];
+
+    fn parse(name: &str) -> Result<Column, String> {
+        Column::ALL
+            .iter()
+            .copied()
+            .find(|column| column.name() == name)
+            .ok_or_else(|| format!("unknown UI column '{}'", name))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Column::Key => "key",
+            Column::Size => "size",
+            Column::Hz => "hz",
+            Column::Bandwidth => "bandwidth",
+            Column::Timestamp => "timestamp",
+            Column::Age => "age",
+            Column::Load => "load",
+            Column::Stability => "stability",
+            Column::Jitter => "jitter",
+            Column::Latency => "latency",
+            Column::Source => "source",
+            Column::Publishers => "publishers",
+            Column::Instance => "instance",
+            Column::Decoded => "decoded",
+            Column::Status => "status",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Key => "Topic",
+            Column::Size => "Message Size (B)",
+            Column::Hz => "Frequency (Hz)",
+            Column::Bandwidth => "Bandwidth",
+            Column::Timestamp => "Received Timestamp",
+            Column::Age => "Age",
+            Column::Load => "Load",
+            Column::Stability => "Stability",
+            Column::Jitter => "Jitter",
+            Column::Latency => "Latency",
+            Column::Source => "Source",
+            Column::Publishers => "Publishers",
+            Column::Instance => "Instance",
+            Column::Decoded => "Decoded Content",
+            Column::Status => "Status",
+        }
+    }
+
+    /// Relative width; the configured columns are scaled to fill the table
+    fn weight(&self) -> u32 {
+        match self {
+            Column::Key => 25,
+            Column::Size => 5,
+            Column::Hz => 7,
+            Column::Bandwidth => 8,
+            Column::Timestamp => 18,
+            Column::Age => 7,
+            Column::Load => 8,
+            Column::Stability => 6,
+            Column::Jitter => 7,
+            Column::Latency => 7,
+            Column::Source => 12,
+            Column::Publishers => 6,
+            Column::Instance => 8,
+            Column::Decoded => 45,
+            Column::Status => 8,
+        }
+    }
+}
+
+/// Metric the load bars are scaled by
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum LoadMetric {
+    /// Bytes per second, so a few large-payload topics stand out
+    Bandwidth,
+    /// Messages per second, regardless of payload size
+    Rate,
+}
+
+impl LoadMetric {
+    fn name(&self) -> &'static str {
+        match self {
+            LoadMetric::Bandwidth => "bandwidth",
+            LoadMetric::Rate => "rate",
+        }
+    }
+}
+
+/// How the frequency column shows rates
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RateDisplay {
+    Absolute,
+    /// As a percentage of the expected rate from `--manifest`, so every healthy
+    /// topic reads about 100% whatever its rate. Topics without an expected
+    /// rate are shown in Hz.
+    Relative,
+}
+
+impl RateDisplay {
+    fn name(&self) -> &'static str {
+        match self {
+            RateDisplay::Absolute => "absolute",
+            RateDisplay::Relative => "relative",
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Theme {
+    Light,
+    Dark,
+    Auto,
+    /// White on black with saturated status colours, for low vision
+    HighContrast,
+    /// Light, with status colours told apart without red and green
+    ColorBlind,
+}
+
+/// The stats bar, table header, buttons and footer, shared by light and dark
+const ACCENTS: &str = "--accent-bg: linear-gradient(135deg, #667eea 0%, #764ba2 100%); \
+    --header-bg: linear-gradient(135deg, #4CAF50 0%, #45a049 100%); \
+    --button-bg: linear-gradient(135deg, #e17055 0%, #d63031 100%); --button-fg: white; \
+    --info-bg: linear-gradient(135deg, #74b9ff 0%, #0984e3 100%); --on-accent: white; \
+    --ok: #27ae60; --warn: #f39c12; --bad: #c0392b; --focus: rgba(102,126,234,0.6);";
+
+const LIGHT_THEME: &str = "--bg: #f5f7fa; --fg: #333; --heading: #2c3e50; --subtle: #7f8c8d; \
+    --surface: white; --placeholder: #9aa4b2; --border: #e8ecf0; --row-hover: #f8f9fb; \
+    --topic: #3498db; --alert-bg: #fdecea; --alert-fg: #c0392b; --signal: #8e44ad; \
+    --highlight: #ffffa6; --stopped-bg: #f8d7da; --stopped-fg: #721c24; \
+    --silence-bg: #fff3cd; --silence-fg: #856404;";
+
+const DARK_THEME: &str = "--bg: #15181e; --fg: #d8dee9; --heading: #e5e9f0; --subtle: #8f9aa8; \
+    --surface: #1f242c; --placeholder: #6b7482; --border: #2e3440; --row-hover: #262c35; \
+    --topic: #6cb6ff; --alert-bg: #4a1f1f; --alert-fg: #ff8a80; --signal: #c39bd3; \
+    --highlight: #4d4a1f; --stopped-bg: #4a1f24; --stopped-fg: #f5b7b1; \
+    --silence-bg: #4a3f1a; --silence-fg: #f7dc6f;";
+
+const HIGH_CONTRAST_THEME: &str = "--bg: #000; --fg: #fff; --heading: #fff; --subtle: #d0d0d0; \
+    --surface: #000; --placeholder: #a0a0a0; --border: #fff; --row-hover: #222; \
+    --topic: #4dd0ff; --alert-bg: #330000; --alert-fg: #ff6b6b; --signal: #ff9cff; \
+    --highlight: #4d4d00; --stopped-bg: #330000; --stopped-fg: #ff6b6b; \
+    --silence-bg: #332b00; --silence-fg: #ffd400; --accent-bg: #000; --header-bg: #000; \
+    --button-bg: #ffd400; --button-fg: #000; --info-bg: #000; --on-accent: #fff; \
+    --ok: #00e676; --warn: #ffd400; --bad: #ff6b6b; --focus: #ffd400;";
+
+/// The Okabe-Ito palette: blue, orange and vermillion stay distinct with any
+/// common colour vision deficiency
+const COLOR_BLIND_THEME: &str = "--bg: #f5f7fa; --fg: #222; --heading: #1a1a1a; --subtle: #666; \
+    --surface: white; --placeholder: #8a8a8a; --border: #e0e0e0; --row-hover: #f2f6fa; \
+    --topic: #0072b2; --alert-bg: #fbe3d4; --alert-fg: #b34700; --signal: #a5547f; \
+    --highlight: #fff3b0; --stopped-bg: #fbe3d4; --stopped-fg: #8a3600; \
+    --silence-bg: #fdf0d5; --silence-fg: #8a5a00; --accent-bg: #0072b2; --header-bg: #005a8c; \
+    --button-bg: #e69f00; --button-fg: #000; --info-bg: #0072b2; --on-accent: white; \
+    --ok: #0072b2; --warn: #e69f00; --bad: #d55e00; --focus: #0072b2;";
+
+impl Theme {
+    const ALL: [Theme; 5] = [
+        Theme::Light,
+        Theme::Dark,
+        Theme::Auto,
+        Theme::HighContrast,
+        Theme::ColorBlind,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::Auto => "auto",
+            Theme::HighContrast => "high_contrast",
+            Theme::ColorBlind => "color_blind",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::Auto => "Auto",
+            Theme::HighContrast => "High contrast",
+            Theme::ColorBlind => "Colour-blind safe",
+        }
+    }
+
+    /// This theme's CSS variables, set on `selector`
+    fn rule(&self, selector: &str) -> String {
+        match self {
+            Theme::Light => format!("{} {{ {} {} }}", selector, LIGHT_THEME, ACCENTS),
+            Theme::Dark => format!("{} {{ {} {} }}", selector, DARK_THEME, ACCENTS),
+            Theme::Auto => format!(
+                "{}\n    @media (prefers-color-scheme: dark) {{ {} }}",
+                Theme::Light.rule(selector),
+                Theme::Dark.rule(selector)
+            ),
+            Theme::HighContrast => format!("{} {{ {} }}", selector, HIGH_CONTRAST_THEME),
+            Theme::ColorBlind => format!("{} {{ {} }}", selector, COLOR_BLIND_THEME),
+        }
+    }
+
+    /// This theme on `:root`, then every theme by name for the theme picker,
+    /// whose `data-theme` attribute wins by specificity
+    fn css(&self) -> String {
+        std::iter::once(self.rule(":root"))
+            .chain(
+                Theme::ALL
+                    .iter()
+                    .map(|theme| theme.rule(&format!(r#":root[data-theme="{}"]"#, theme.name()))),
+            )
+            .collect::<Vec<_>>()
+            .join("\n    ")
+    }
+}
+
+/// The page's built-in styles for `theme`, with `columns` sized by their weights.
+/// Shared by the live page and the static report, so both look the same.
+fn page_css(theme: Theme, columns: &[Column]) -> String {
+    let total_weight: u32 = columns.iter().map(Column::weight).sum();
+    let column_widths: String = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            format!(
+                "th:nth-child({n}), td:nth-child({n}) {{ width: {width:.1}%; }}",
+                n = index + 1,
+                width = f64::from(column.weight()) * 100.0 / f64::from(total_weight.max(1)),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    format!(
+        r#"{theme_css}
+    body {{
+        display: flex;
+        flex-direction: column;
+        height: 100vh;
+        margin: 0;
+        font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
+        background-color: var(--bg);
+        color: var(--fg);
+    }}
+    .header {{
+        text-align: center;
+        margin-bottom: 30px;
+        flex-shrink: 0;
+    }}
+    .throughput {{
+        display: flex;
+        justify-content: center;
+        align-items: center;
+        gap: 10px;
+        font-size: 0.8rem;
+        color: var(--placeholder);
+    }}
+    .throughput-chart {{
+        width: 300px;
+        height: 40px;
+        border-bottom: 1px solid var(--border);
+    }}
+    .throughput-chart polyline {{
+        fill: none;
+        stroke-width: 1.2;
+    }}
+    .throughput-rate {{
+        stroke: var(--topic);
+    }}
+    .throughput-bandwidth {{
+        stroke: var(--signal);
+    }}
+    .header h1 {{
+        color: var(--heading);
+        margin: 0;
+        font-size: 2.5rem;
+        font-weight: 300;
+    }}
+    .header .logo {{
+        height: 2.5rem;
+        vertical-align: middle;
+        margin-right: 12px;
+    }}
+    .header p {{
+        color: var(--subtle);
+        margin: 10px 0 0 0;
+        font-size: 1.1rem;
     }}
     .stats {{
-        background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
-        color: white;
+        background: var(--accent-bg);
+        color: var(--on-accent);
         padding: 20px;
         border-radius: 12px;
         margin-bottom: 25px;
@@ -211,6 +2352,43 @@ fn generate_html(has_decoder: bool) -> String {
         font-size: 0.9rem;
         opacity: 0.9;
     }}
+    .prefix-chips {{
+        display: flex;
+        flex-wrap: wrap;
+        justify-content: center;
+        gap: 4px;
+        max-width: 260px;
+    }}
+    .prefix-chip {{
+        background: rgba(255, 255, 255, 0.2);
+        border-radius: 10px;
+        padding: 2px 8px;
+        font-size: 0.75rem;
+    }}
+    .instance-down {{
+        background: var(--alert-fg);
+    }}
+    .column-chooser {{
+        position: relative;
+    }}
+    .column-menu {{
+        position: absolute;
+        top: 100%;
+        left: 50%;
+        transform: translateX(-50%);
+        z-index: 10;
+        display: flex;
+        flex-direction: column;
+        align-items: flex-start;
+        gap: 4px;
+        margin-top: 4px;
+        padding: 8px 12px;
+        background: var(--bg);
+        color: var(--fg);
+        border-radius: 6px;
+        box-shadow: 0 2px 8px rgba(0, 0, 0, 0.25);
+        white-space: nowrap;
+    }}
     .controls {{
         display: flex;
         justify-content: center;
@@ -220,8 +2398,8 @@ fn generate_html(has_decoder: bool) -> String {
         flex-shrink: 0;
     }}
     .sort-toggle {{
-        background: linear-gradient(135deg, #e17055 0%, #d63031 100%);
-        color: white;
+        background: var(--button-bg);
+        color: var(--button-fg);
         border: none;
         padding: 12px 20px;
         border-radius: 8px;
@@ -249,24 +2427,24 @@ fn generate_html(has_decoder: bool) -> String {
         min-width: 220px;
         box-shadow: 0 2px 6px rgba(0,0,0,0.06);
         transition: box-shadow 0.15s ease, transform 0.08s ease, border-color 0.15s ease;
-        background: white;
-        color: #333;
+        background: var(--surface);
+        color: var(--fg);
     }}
     .filter-input::placeholder {{
-        color: #9aa4b2;
+        color: var(--placeholder);
     }}
     .filter-input:focus {{
         outline: none;
-        box-shadow: 0 6px 18px rgba(102,126,234,0.12);
+        box-shadow: 0 6px 18px rgba(0,0,0,0.12);
         transform: translateY(-1px);
-        border-color: rgba(102,126,234,0.6);
+        border-color: var(--focus);
     }}
 
     .container {{
         flex: 1 1 auto;
         display: flex;
         flex-direction: column;
-        background: white;
+        background: var(--surface);
         border-radius: 12px;
         box-shadow: 0 4px 20px rgba(0,0,0,0.08);
         overflow: hidden;
@@ -280,8 +2458,8 @@ fn generate_html(has_decoder: bool) -> String {
     }}
     thead {{
         display: table-header-group;
-        background: linear-gradient(135deg, #4CAF50 0%, #45a049 100%);
-        color: white;
+        background: var(--header-bg);
+        color: var(--on-accent);
     }}
     tbody {{
         display: block;
@@ -294,8 +2472,8 @@ fn generate_html(has_decoder: bool) -> String {
         table-layout: fixed;
     }}
     th {{
-        background: linear-gradient(135deg, #4CAF50 0%, #45a049 100%);
-        color: white;
+        background: var(--header-bg);
+        color: var(--on-accent);
         padding: 8px 12px;
         margin: 0;
         text-align: centre;
@@ -303,99 +2481,438 @@ fn generate_html(has_decoder: bool) -> String {
         font-size: 0.95rem;
         text-transform: uppercase;
         letter-spacing: 0.5px;
-        border-bottom: 1px solid #e8ecf0;
+        border-bottom: 1px solid var(--border);
     }}
-    th:nth-child(1) {{ width: {topic_width}; }}
-    th:nth-child(2) {{ width: {size_width}; }}
-    th:nth-child(3) {{ width: {freq_width}; }}
-    th:nth-child(4) {{ width: {timestamp_width}; }}
-    {decoder_th_style}
+    {column_widths}
     td {{
         padding: 8px 12px;
         margin: 0;
-        border-bottom: 1px solid #e8ecf0;
+        border-bottom: 1px solid var(--border);
         vertical-align: top;
     }}
-    td:nth-child(1) {{ width: {topic_width}; }}
-    td:nth-child(2) {{ width: {size_width}; }}
-    td:nth-child(3) {{ width: {freq_width}; }}
-    td:nth-child(4) {{ width: {timestamp_width}; }}
-    {decoder_td_style}
     tr:hover {{
-        background-color: #f8f9fb;
+        background-color: var(--row-hover);
         transition: background-color 0.2s ease;
     }}
     .topic-cell {{
         font-family: 'Fira Code', 'Courier New', monospace;
         font-weight: 600;
-        color: #3498db;
+        color: var(--topic);
         word-break: break-all;
         position: relative;
         text-align: left;
         border-radius: 4px;
     }}
-    .size-cell {{
-        font-family: 'Fira Code', 'Courier New', monospace;
-        word-break: break-word;
-        background-color: transparent;
-        border-radius: 4px;
+    .mute-btn, .pin-btn {{
+        background: none;
+        border: none;
+        cursor: pointer;
+        opacity: 0.3;
+        padding: 0 6px 0 0;
+        font-size: 0.85rem;
     }}
-    .freq-cell {{
-        font-family: 'Fira Code', 'Courier New', monospace;
-        word-break: break-word;
-        background-color: transparent;
-        border-radius: 4px;
+    .mute-btn:hover, .pin-btn:hover {{
+        opacity: 1;
     }}
-    .timestamp-cell {{
-        font-family: 'Fira Code', 'Courier New', monospace;
-        white-space: nowrap;
-        background-color: transparent;
-        border-radius: 4px;
+    .pinned-row td {{
+        background-color: var(--row-hover);
     }}
-    .decoded-cell {{
-        font-family: 'Fira Code', 'Courier New', monospace;
-        background-color: transparent;
-        border-radius: 4px;
-        max-height: 100px;
-        overflow-y: auto;
-        word-break: break-word;
-        line-height: 1.3;
-        text-align: left;
+    .pinned-row .pin-btn {{
+        opacity: 1;
     }}
-    .refresh-info {{
-        text-align: center;
-        margin-top: 25px;
-        padding: 15px;
-        background: linear-gradient(135deg, #74b9ff 0%, #0984e3 100%);
-        color: white;
-        border-radius: 8px;
-        font-size: 0.9rem;
-        flex-shrink: 0;
+    .pinned-row + tr:not(.pinned-row) td {{
+        border-top: 2px solid var(--topic);
     }}
-    .no-data {{
-        text-align: center;
-        padding: 40px;
-        color: #6c757d;
+    .missing-row td {{
+        color: var(--alert-fg);
         font-style: italic;
-        font-size: 1.1rem;
     }}
-    .updated-row {{
-        animation: fade-highlight 0.5s ease-out;
+    .muted-row {{
+        opacity: 0.5;
     }}
-    @keyframes fade-highlight {{
-        from {{ background-color: #ffffa6; }}
-        to {{ background-color: transparent; }}
+    .stale-row td {{
+        color: var(--subtle);
     }}
-    /* Responsive design */
-    @media (max-width: 768px) {{
-        .stats {{
-            flex-direction: column;
-            gap: 15px;
-        }}
-        .controls {{
-            flex-direction: column;
-            gap: 10px;
-        }}
+    .source-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        word-break: break-all;
+    }}
+    .source-conflict {{
+        color: var(--alert-fg);
+        font-weight: 600;
+    }}
+    .stability-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        font-weight: 600;
+    }}
+    .stability-fair {{
+        color: var(--silence-fg);
+    }}
+    .stability-poor {{
+        color: var(--alert-fg);
+    }}
+    .jitter-cell, .latency-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+    }}
+    .timing-exceeded {{
+        color: var(--alert-fg);
+        font-weight: 600;
+    }}
+    .muted-row .mute-btn {{
+        opacity: 1;
+    }}
+    .alerting-row {{
+        background-color: var(--alert-bg);
+    }}
+    .alerting-row .topic-cell {{
+        color: var(--alert-fg);
+    }}
+    .alert-badge {{
+        margin-left: 8px;
+        font-size: 0.8rem;
+        font-weight: normal;
+        color: var(--subtle);
+    }}
+    .health-summary {{
+        margin-top: 12px;
+        padding: 6px 18px;
+        border: none;
+        border-radius: 16px;
+        font-size: 1rem;
+        font-weight: 600;
+        color: var(--on-accent);
+        cursor: pointer;
+    }}
+    .health-green {{
+        background: var(--ok);
+    }}
+    .health-yellow {{
+        background: var(--warn);
+    }}
+    .health-red {{
+        background: var(--bad);
+    }}
+    .health-details {{
+        margin: 10px auto 0;
+        max-width: 900px;
+        font-size: 0.85rem;
+        text-align: left;
+    }}
+    .health-details .prefix-chip {{
+        margin: 2px;
+        border: none;
+        background: var(--border);
+        color: var(--fg);
+        cursor: pointer;
+    }}
+    .stopped-banner {{
+        text-align: center;
+        margin: 0 20px 15px;
+        padding: 10px;
+        background: var(--stopped-bg);
+        color: var(--stopped-fg);
+        border-radius: 8px;
+        font-weight: 600;
+        flex-shrink: 0;
+    }}
+    .silence-banner {{
+        text-align: center;
+        margin: 0 20px 15px;
+        padding: 10px;
+        background: var(--silence-bg);
+        color: var(--silence-fg);
+        border-radius: 8px;
+        font-size: 0.9rem;
+        flex-shrink: 0;
+    }}
+    .query-panel {{
+        margin: 0 20px 15px;
+        padding: 10px;
+        background: var(--surface);
+        border-radius: 8px;
+        font-size: 0.85rem;
+        flex-shrink: 0;
+    }}
+    .query-panel summary {{
+        cursor: pointer;
+        color: var(--subtle);
+    }}
+    .query-form {{
+        display: flex;
+        gap: 8px;
+        margin-top: 8px;
+    }}
+    .query-form .filter-input {{
+        flex: 1;
+    }}
+    .query-result {{
+        max-height: 240px;
+        overflow: auto;
+        white-space: pre-wrap;
+        word-break: break-word;
+        font-family: 'Fira Code', 'Courier New', monospace;
+        margin: 8px 0 0;
+    }}
+    .size-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        word-break: break-word;
+        background-color: transparent;
+        border-radius: 4px;
+    }}
+    .size-cell.signal {{
+        font-style: italic;
+        color: var(--signal);
+    }}
+    .size-delta {{
+        margin-left: 6px;
+        font-size: 0.75rem;
+        color: var(--placeholder);
+    }}
+    .repeat-badge {{
+        margin-left: 6px;
+        padding: 0 5px;
+        border-radius: 8px;
+        font-size: 0.75rem;
+        background-color: var(--silence-bg);
+        color: var(--silence-fg);
+    }}
+    .freq-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        word-break: break-word;
+        background-color: transparent;
+        border-radius: 4px;
+    }}
+    .freq-expected {{
+        color: var(--subtle);
+    }}
+    .rate-warn .freq-value {{
+        color: var(--silence-fg);
+    }}
+    .rate-bad .freq-value {{
+        color: var(--alert-fg);
+    }}
+    .sparkline {{
+        display: block;
+        width: 60px;
+        height: 16px;
+        margin-top: 2px;
+    }}
+    .sparkline polyline {{
+        fill: none;
+        stroke: var(--topic);
+        stroke-width: 1.2;
+    }}
+    .load-bar {{
+        height: 8px;
+        border-radius: 4px;
+        background-color: var(--border);
+        overflow: hidden;
+    }}
+    .load-fill {{
+        height: 100%;
+        width: 0;
+        background-color: var(--topic);
+        transition: width 0.3s ease;
+    }}
+    .bandwidth-cell, .status-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        word-break: break-word;
+    }}
+    .timestamp-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        white-space: nowrap;
+        background-color: transparent;
+        border-radius: 4px;
+    }}
+    .decoded-cell {{
+        font-family: 'Fira Code', 'Courier New', monospace;
+        background-color: transparent;
+        border-radius: 4px;
+        max-height: 100px;
+        overflow-y: auto;
+        word-break: break-word;
+        /* Multi-line decoder output (pretty JSON, hex dumps) keeps its lines */
+        white-space: pre-wrap;
+        line-height: 1.3;
+        text-align: left;
+    }}
+    .decode-error-badge {{
+        display: inline-block;
+        margin-right: 6px;
+        padding: 0 6px;
+        border-radius: 8px;
+        font-size: 0.75rem;
+        background: var(--alert-bg);
+        color: var(--fg);
+    }}
+    .decode-error-badge.failing {{
+        color: var(--alert-fg);
+        font-weight: 600;
+    }}
+    .dismiss-error-btn {{
+        border: none;
+        background: none;
+        color: inherit;
+        cursor: pointer;
+        padding: 0 0 0 4px;
+    }}
+    /* Grid layout: the same rows restyled as large tiles of key and rate */
+    .grid-view thead {{
+        display: none;
+    }}
+    .grid-view tbody {{
+        display: grid;
+        grid-template-columns: repeat(auto-fill, minmax(260px, 1fr));
+        gap: 16px;
+        padding: 16px;
+    }}
+    .grid-view tbody tr {{
+        display: flex;
+        flex-direction: column;
+        width: auto;
+        border-left: 10px solid var(--ok);
+        border-radius: 8px;
+        background: var(--bg);
+    }}
+    .grid-view tbody td {{
+        width: auto;
+        border-bottom: none;
+    }}
+    .grid-view tbody td:not(.topic-cell):not(.freq-cell) {{
+        display: none;
+    }}
+    .grid-view .topic-cell {{
+        font-size: 1.3rem;
+    }}
+    .grid-view .freq-cell {{
+        font-size: 2.4rem;
+        font-weight: 700;
+    }}
+    .grid-view .freq-cell .sparkline {{
+        width: 100%;
+        height: 32px;
+    }}
+    /* The tile's colour is its worst condition, in increasing order of precedence */
+    .grid-view tbody tr:has(.rate-warn), .grid-view tbody tr:has(.timing-exceeded) {{
+        border-left-color: var(--silence-fg);
+    }}
+    .grid-view tbody tr.stale-row {{
+        border-left-color: var(--subtle);
+    }}
+    .grid-view tbody tr:has(.rate-bad), .grid-view tbody tr.alerting-row, .grid-view tbody tr.missing-row {{
+        border-left-color: var(--alert-fg);
+    }}
+    .grid-view tbody tr.detail-row {{
+        grid-column: 1 / -1;
+        border-left: none;
+    }}
+    .refresh-info {{
+        text-align: center;
+        margin-top: 25px;
+        padding: 15px;
+        background: var(--info-bg);
+        color: var(--on-accent);
+        border-radius: 8px;
+        font-size: 0.9rem;
+        flex-shrink: 0;
+    }}
+    .no-data {{
+        text-align: center;
+        padding: 40px;
+        color: var(--subtle);
+        font-style: italic;
+        font-size: 1.1rem;
+    }}
+    .updated-row {{
+        animation: fade-highlight 0.5s ease-out;
+    }}
+    .new-row {{
+        animation: fade-new 3s ease-out;
+    }}
+    .new-badge {{
+        margin-left: 6px;
+        padding: 0 5px;
+        border-radius: 8px;
+        font-size: 0.7rem;
+        color: white;
+        background-color: var(--topic);
+    }}
+    .new-badge.reappeared {{
+        background-color: var(--signal);
+    }}
+    tr[data-key] {{
+        cursor: pointer;
+    }}
+    .detail-panel {{
+        padding: 8px 4px;
+        font-size: 0.85rem;
+    }}
+    .detail-grid {{
+        display: grid;
+        grid-template-columns: repeat(auto-fit, minmax(220px, 1fr));
+        gap: 6px 16px;
+        margin-bottom: 8px;
+    }}
+    .detail-label {{
+        color: var(--subtle);
+        margin-right: 6px;
+    }}
+    .detail-decoded {{
+        white-space: pre-wrap;
+        word-break: break-word;
+        font-family: 'Fira Code', 'Courier New', monospace;
+        background-color: var(--bg);
+        padding: 6px;
+        border-radius: 4px;
+        margin: 0;
+    }}
+    .json-tree ul {{
+        list-style: none;
+        margin: 0;
+        padding-left: 18px;
+    }}
+    .json-tree summary {{
+        cursor: pointer;
+    }}
+    .json-tree details:not([open]) > summary::after {{
+        content: ' … ' attr(data-close);
+        color: var(--subtle);
+    }}
+    .json-count {{
+        color: var(--subtle);
+        margin-left: 6px;
+    }}
+    .json-key {{
+        color: var(--topic);
+    }}
+    .json-string {{
+        color: var(--signal);
+    }}
+    .json-number {{
+        color: var(--heading);
+    }}
+    .json-bool, .json-null {{
+        color: var(--subtle);
+        font-style: italic;
+    }}
+    @keyframes fade-highlight {{
+        from {{ background-color: var(--highlight); }}
+        to {{ background-color: transparent; }}
+    }}
+    @keyframes fade-new {{
+        from {{ background-color: var(--topic); }}
+        to {{ background-color: transparent; }}
+    }}
+    /* Responsive design */
+    @media (max-width: 768px) {{
+        .stats {{
+            flex-direction: column;
+            gap: 15px;
+        }}
+        .controls {{
+            flex-direction: column;
+            gap: 10px;
+        }}
         .topic-cell, .size-cell, .decoded-cell {{
             max-width: none;
         }}
@@ -409,12 +2926,55 @@ fn generate_html(has_decoder: bool) -> String {
         .filter-input {{
             min-width: 140px;
         }}
-    }}
+    }}"#,
+        column_widths = column_widths,
+        theme_css = theme.css(),
+    )
+}
+
+/// Generate HTML for the web UI.
+/// `columns`: the table columns, in order.
+/// `extra_css`: site-specific styles appended after the built-in ones.
+/// Returns the full HTML page as a `String`.
+fn generate_html(config: &Config, columns: &[Column], extra_css: &str) -> String {
+    let has_decoder = config.decoder_enabled && columns.contains(&Column::Decoded);
+    let branding = &config.branding;
+    let header_cells: String = columns
+        .iter()
+        .map(|column| format!("<th>{}</th>", column.title()))
+        .collect::<Vec<_>>()
+        .join("\n                ");
+
+    let column_names: Vec<&str> = columns.iter().map(Column::name).collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>{page_title}</title>
+<script>
+    // Before the body is drawn, so a theme picked in this browser doesn't flash the default
+    if (localStorage.getItem('theme')) document.documentElement.dataset.theme = localStorage.getItem('theme');
+</script>
+<style>
+    {page_css}
+</style>
+<style>
+{highlight_css}
+    {branding_css}
+</style>
+<style>
+{extra_css}
 </style>
+<style id="view-columns"></style>
 <script>
 document.addEventListener("DOMContentLoaded", function() {{
     const tableBody = document.querySelector('tbody');
-    const eventSource = new EventSource('/sse');
+    // Prefix of every URL, set when the monitor is served under a sub-path
+    const basePath = {base_path_js};
+    const eventSource = new EventSource(`${{basePath}}/sse`);
     const topics = new Map();
 
     // Explicit IDs for robustness
@@ -423,9 +2983,57 @@ document.addEventListener("DOMContentLoaded", function() {{
     const sortButton = document.getElementById('sort-toggle-btn');
     const filterInput = document.getElementById('filter-input');
     const filteredCount = document.getElementById('filtered-count');
-    const hasDecoder = {has_decoder_js};
+    const showMutedButton = document.getElementById('show-muted-btn');
+    const searchModeButton = document.getElementById('search-mode-btn');
+    const columns = {columns_js};
+    const hasDecoder = columns.includes('decoded');
+
+    // Muted topics are still tracked, just hidden unless "show muted" is on
+    const mutedTopics = new Set(JSON.parse(localStorage.getItem('mutedTopics') || '[]'));
+    let showMuted = false;
+
+    // Pins used to live in the browser; carry them over to the server once
+    const legacyPins = JSON.parse(localStorage.getItem('pinnedTopics') || '[]');
+    Promise.all(legacyPins.map(key => setPinned(key, true)))
+        .then(() => localStorage.removeItem('pinnedTopics'));
+
+    // Matching decoded content is slower, so key-only search is the default
+    let searchContent = false;
+
+    // Per-topic alert state, refreshed from /api/alerts
+    const alertStates = new Map();
+    const silenceBanner = document.getElementById('silence-banner');
+    const prefixChips = document.getElementById('prefix-chips');
+    const instanceChips = document.getElementById('instance-chips');
+
+    // Server-computed age per topic and the local time it was received, so the
+    // displayed age only depends on elapsed local time, never on clock skew
+    const ages = new Map();
+
+    // Recent rates per topic, refreshed from `hz_history` on a slower cadence
+    const hzHistories = new Map();
+    const staleAfterMs = {stale_after_ms};
+
+    // 'bandwidth' or 'rate'; load bars are scaled to the busiest topic every update
+    const loadMetric = '{load_metric}';
 
-    let sortMode = 'alphabetical'; // 'alphabetical' or 'timestamp'
+    // Expanded rows: topic key -> its detail row, kept right below the topic's row
+    const detailRows = new Map();
+
+    // Sort field ('key', 'timestamp', 'hz', 'size', 'stability' or 'age') and direction; the button
+    // cycles through the configured fields
+    const defaultSort = {default_sort_js};
+    const sortFields = {sort_fields_js};
+    let sortField = defaultSort.field;
+    let sortDescending = defaultSort.direction === 'desc';
+
+    // Watchlist of the active view as regexes; when non-empty, only matching topics are shown
+    let watchlist = [];
+
+    // Set by the server when the page is opened as `/?view=<name>`
+    const initialView = /*INITIAL_VIEW*/null;
+    let lastSeq = null;
+    let lastEventAt = Date.now();
 
     function updateStats() {{
         totalTopicsValue.textContent = topics.size;
@@ -433,399 +3041,4909 @@ document.addEventListener("DOMContentLoaded", function() {{
     }}
 
     function getRowByKey(topicKey) {{
-        const rows = tableBody.querySelectorAll('tr');
+        const rows = tableBody.querySelectorAll('tr[data-key]');
         for (const r of rows) {{
             if (r.dataset.key === topicKey) return r;
         }}
         return null;
     }}
 
+    function sortValue(topicData) {{
+        switch (sortField) {{
+            case 'timestamp': return topicData.received_timestamp;
+            case 'hz': return topicData.estimated_hz ?? 0;
+            case 'size': return topicData.last_data_size_bytes;
+            case 'stability': return topicData.stability;
+            // Ages all grow at the same pace, so their order is the reverse of the
+            // timestamps'; a stale topic keeps its place and its age cell ticks on
+            case 'age': return -topicData.received_timestamp;
+            default: return topicData.key_expr;
+        }}
+    }}
+
+    // Pinned first, then by the current sort field within each section
+    function compareTopics(a, b) {{
+        if (!!a.pinned !== !!b.pinned) return a.pinned ? -1 : 1;
+        const valueA = sortValue(a);
+        const valueB = sortValue(b);
+        const order = typeof valueA === 'string' ? valueA.localeCompare(valueB) : valueA - valueB;
+        return sortDescending ? -order : order;
+    }}
+
     function sortTopics() {{
         const topicArray = Array.from(topics.values());
-        if (sortMode === 'alphabetical') {{
-            topicArray.sort((a, b) => a.key_expr.localeCompare(b.key_expr));
-        }} else {{
-            topicArray.sort((a, b) => b.received_timestamp - a.received_timestamp);
-        }}
+        topicArray.sort(compareTopics);
         return topicArray;
     }}
 
-    function applyFilter() {{
-        const filter = (filterInput.value || '').toLowerCase();
-        let count = 0;
-        const rows = tableBody.querySelectorAll('tr');
-        rows.forEach(row => {{
-            const topicCell = row.querySelector('.topic-cell');
-            if (topicCell && topicCell.textContent.toLowerCase().includes(filter)) {{
-                row.style.display = "";
-                count++;
-            }} else {{
-                row.style.display = "none";
-            }}
-        }});
-        filteredCount.textContent = `${{count}} Topics`;
-    }}
+    function applyFilter() {{
+        const filter = (filterInput.value || '').toLowerCase();
+        let count = 0;
+        const rows = tableBody.querySelectorAll('tr[data-key]');
+        rows.forEach(row => {{
+            const key = row.dataset.key || '';
+            const muted = mutedTopics.has(key);
+            row.classList.toggle('muted-row', muted);
+            const alertState = alertStates.get(key);
+            row.classList.toggle('alerting-row', !!alertState && alertState.firing);
+            const badge = row.querySelector('.alert-badge');
+            if (badge) {{
+                badge.textContent = !alertState ? '' : alertState.silenced ? '🔕 silenced' : alertState.acked ? '✓ acked' : '';
+            }}
+            const statusCell = row.querySelector('.status-cell');
+            if (statusCell) {{
+                statusCell.textContent = !alertState ? 'ok'
+                    : alertState.silenced ? 'silenced'
+                    : alertState.firing ? (alertState.acked ? 'firing (acked)' : 'firing')
+                    : 'pending';
+            }}
+            const topicData = topics.get(key);
+            const watched = watchlist.length === 0 || watchlist.some(pattern => pattern.test(key));
+            const matches = key.toLowerCase().includes(filter)
+                || (searchContent && filter !== '' && !!topicData && !!topicData.decoded_content
+                    && unescapeText(topicData.decoded_content).toLowerCase().includes(filter));
+            if (watched && matches && (!muted || showMuted)) {{
+                row.style.display = "";
+                count++;
+            }} else {{
+                row.style.display = "none";
+            }}
+        }});
+        filteredCount.textContent = `${{count}} Topics`;
+        showMutedButton.textContent = `${{showMuted ? 'Hide' : 'Show'}} Muted (${{mutedTopics.size}})`;
+        placeDetailRows();
+    }}
+
+    // Long keys keep their start and end, which usually tell topics apart
+    const keyMaxChars = {key_max_chars};
+    function displayKey(key) {{
+        if (keyMaxChars === 0 || key.length <= keyMaxChars) return key;
+        const kept = keyMaxChars - 1;
+        return key.slice(0, Math.ceil(kept / 2)) + '…' + key.slice(key.length - Math.floor(kept / 2));
+    }}
+
+    // Quotes too, since the result also goes into attributes such as titles
+    function escapeHtml(text) {{
+        return String(text).replace(/[&<>"']/g, c => ({{ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }})[c]);
+    }}
+
+    // Decoded content arrives escaped by the server as text: only &, < and >
+    function unescapeText(html) {{
+        return html.replace(/&lt;/g, '<').replace(/&gt;/g, '>').replace(/&amp;/g, '&');
+    }}
+
+    function toggleDetail(topicKey) {{
+        if (detailRows.has(topicKey)) {{
+            detailRows.get(topicKey).remove();
+            detailRows.delete(topicKey);
+            return;
+        }}
+        const detailRow = document.createElement('tr');
+        detailRow.className = 'detail-row';
+        detailRow.innerHTML = `<td colspan="${{columns.length}}"><div class="detail-panel">Loading...</div></td>`;
+        detailRows.set(topicKey, detailRow);
+        placeDetailRows();
+        refreshDetail(topicKey);
+    }}
+
+    // Rows move on every sort and rebuild, so detail rows are re-placed after them
+    function placeDetailRows() {{
+        detailRows.forEach((detailRow, topicKey) => {{
+            const row = getRowByKey(topicKey);
+            if (row) {{
+                row.after(detailRow);
+                detailRow.style.display = row.style.display;
+            }} else {{
+                detailRow.remove();
+                detailRows.delete(topicKey);
+            }}
+        }});
+    }}
+
+    function refreshDetail(topicKey) {{
+        fetch(`${{basePath}}/api/topics/${{encodeURIComponent(topicKey)}}/detail`)
+            .then(response => response.ok ? response.json() : Promise.reject(response.status))
+            .then(detail => {{
+                const detailRow = detailRows.get(topicKey);
+                if (detailRow) detailRow.querySelector('.detail-panel').innerHTML = renderDetail(detail);
+            }})
+            .catch(status => {{
+                const detailRow = detailRows.get(topicKey);
+                if (detailRow) {{
+                    detailRow.querySelector('.detail-panel').textContent =
+                        status === 404 ? 'This topic is no longer in the cache.' : 'Failed to load details.';
+                }}
+            }});
+    }}
+
+    function renderDetail(detail) {{
+        const stats = detail.interval_stats;
+        const gaps = detail.recent_gaps.length === 0 ? 'none'
+            : detail.recent_gaps.slice().reverse()
+                .map(gap => `${{new Date(gap.started_at).toLocaleTimeString()}} (${{(gap.duration_ms / 1000).toFixed(1)}} s)`)
+                .join(', ');
+        const field = (label, value) => `<div><span class="detail-label">${{label}}</span>${{value}}</div>`;
+        return `<div class="detail-grid">`
+            + field('Key', escapeHtml(detail.key_expr))
+            + field('Size', `${{detail.size_display}} (avg ${{Math.round(detail.avg_data_size_bytes)}} B)`)
+            + field('Rate', `${{detail.hz_display}}`)
+            + field('Interval', `min ${{+stats.min_ms.toFixed(2)}} / avg ${{+stats.avg_ms.toFixed(2)}} / max ${{+stats.max_ms.toFixed(2)}} ms, jitter ${{+stats.jitter_ms.toFixed(2)}} ms`)
+            + field('Age', formatAge(detail.age_ms))
+            + field('Duplicates', detail.duplicate_samples)
+            + field('Decoder', escapeHtml(detail.decoded_by || '-'))
+            + field('Decode errors', `${{detail.decode_errors}}${{detail.last_decode_error ? ` (last: ${{escapeHtml(detail.last_decode_error)}})` : ''}}`)
+            + field('Recent gaps', `${{gaps}} (${{detail.gap_count}} in total)`)
+            + `</div>`
+            + (detail.decoded_content ? renderDecoded(detail.key_expr, detail.decoded_content) : '');
+    }}
+
+    // Paths of the JSON tree nodes collapsed per topic, so the 2 s refresh keeps them closed
+    const collapsedJson = new Map();
+
+    // decoded_content arrives HTML-escaped; JSON is parsed from the original text
+    // and every string is escaped again when rendered
+    function renderDecoded(topicKey, content) {{
+        const decoder = document.createElement('textarea');
+        decoder.innerHTML = content;
+        const text = decoder.value.trim();
+        let value;
+        if (text.startsWith('{{') || text.startsWith('[')) {{
+            try {{
+                value = JSON.parse(text);
+            }} catch (e) {{
+                value = undefined;
+            }}
+        }}
+        if (value === undefined) return `<pre class="detail-decoded">${{content}}</pre>`;
+        const collapsed = collapsedJson.get(topicKey) || new Set();
+        return `<div class="detail-decoded json-tree" data-key="${{escapeHtml(topicKey)}}">${{jsonNode(value, '', collapsed)}}</div>`;
+    }}
+
+    function jsonNode(value, path, collapsed) {{
+        if (value === null) return '<span class="json-null">null</span>';
+        if (typeof value === 'string') return `<span class="json-string">"${{escapeHtml(value)}}"</span>`;
+        if (typeof value === 'number') return `<span class="json-number">${{value}}</span>`;
+        if (typeof value === 'boolean') return `<span class="json-bool">${{value}}</span>`;
+        const isArray = Array.isArray(value);
+        const entries = isArray ? value.map((item, index) => [index, item]) : Object.entries(value);
+        const [open, close] = isArray ? ['[', ']'] : ['{{', '}}'];
+        if (entries.length === 0) return open + close;
+        const items = entries.map(([key, item]) => {{
+            const label = isArray ? '' : `<span class="json-key">"${{escapeHtml(key)}}"</span>: `;
+            return `<li>${{label}}${{jsonNode(item, `${{path}}/${{encodeURIComponent(key)}}`, collapsed)}}</li>`;
+        }}).join('');
+        const count = `${{entries.length}} ${{isArray ? 'items' : 'keys'}}`;
+        return `<details data-path="${{escapeHtml(path)}}"${{collapsed.has(path) ? '' : ' open'}}>`
+            + `<summary data-close="${{close}}">${{open}}<span class="json-count">${{count}}</span></summary>`
+            + `<ul>${{items}}</ul>${{close}}</details>`;
+    }}
+
+    // toggle does not bubble, so it is caught on the way down
+    tableBody.addEventListener('toggle', event => {{
+        const node = event.target;
+        const tree = node.closest && node.closest('.json-tree');
+        if (!tree) return;
+        if (!collapsedJson.has(tree.dataset.key)) collapsedJson.set(tree.dataset.key, new Set());
+        const collapsed = collapsedJson.get(tree.dataset.key);
+        if (node.open) collapsed.delete(node.dataset.path);
+        else collapsed.add(node.dataset.path);
+    }}, true);
+
+    setInterval(() => detailRows.forEach((_, topicKey) => refreshDetail(topicKey)), 2000);
+
+    // One-shot Zenoh get; replies come back decoded and HTML-escaped
+    function runQuery(event) {{
+        event.preventDefault();
+        const selector = document.getElementById('query-input').value.trim();
+        const result = document.getElementById('query-result');
+        if (!selector) return;
+        result.textContent = 'Querying...';
+        fetch(`${{basePath}}/api/query`, {{
+            method: 'POST',
+            headers: {{ 'Content-Type': 'application/json' }},
+            body: JSON.stringify({{ selector }}),
+        }})
+            .then(response => response.json())
+            .then(body => {{
+                if (body.error) {{
+                    result.textContent = body.error;
+                    return;
+                }}
+                const lines = body.replies.map(reply => reply.key_expr
+                    ? `${{escapeHtml(reply.key_expr)}} (${{reply.size_bytes}} B${{reply.decoded_by ? ', ' + escapeHtml(reply.decoded_by) : ''}}): ${{reply.decoded_content || reply.error}}`
+                    : `error reply: ${{reply.error}}`);
+                const summary = `${{body.replies.length}} repl${{body.replies.length === 1 ? 'y' : 'ies'}} in ${{body.elapsed_ms}} ms${{body.truncated ? ' (truncated)' : ''}}`;
+                result.innerHTML = [summary, ...lines].join('\n');
+            }})
+            .catch(() => {{ result.textContent = 'Query failed.'; }});
+    }}
+    document.getElementById('query-form').addEventListener('submit', runQuery);
+
+    function toggleMute(topicKey) {{
+        if (mutedTopics.has(topicKey)) {{
+            mutedTopics.delete(topicKey);
+        }} else {{
+            mutedTopics.add(topicKey);
+        }}
+        localStorage.setItem('mutedTopics', JSON.stringify(Array.from(mutedTopics)));
+        applyFilter();
+    }}
+
+    // Pins are kept by the server; the row moves when the update comes back over SSE
+    function setPinned(topicKey, pinned) {{
+        return fetch(`${{basePath}}/api/pins`, {{
+            method: pinned ? 'POST' : 'DELETE',
+            headers: {{ 'Content-Type': 'application/json' }},
+            body: JSON.stringify({{ key_expr: topicKey }}),
+        }}).catch(error => console.error('Failed to update pin:', error));
+    }}
+
+    function togglePin(topicKey) {{
+        const topicData = topics.get(topicKey);
+        setPinned(topicKey, !(topicData && topicData.pinned));
+    }}
+
+    function toggleShowMuted() {{
+        showMuted = !showMuted;
+        applyFilter();
+    }}
+
+    function toggleSearchMode() {{
+        searchContent = !searchContent;
+        searchModeButton.textContent = searchContent ? 'Keys + Content' : 'Keys';
+        filterInput.placeholder = searchContent ? 'Filter topics and decoded content...' : 'Filter topics...';
+        applyFilter();
+    }}
+
+    // Silenced alerts don't colour their row; acknowledged ones still do
+    function refreshAlerts() {{
+        Promise.all([
+            fetch(`${{basePath}}/api/alerts`).then(response => response.json()),
+            fetch(`${{basePath}}/api/silences`).then(response => response.json()),
+        ]).then(([report, silences]) => {{
+            alertStates.clear();
+            report.alerts.forEach(alert => {{
+                const state = alertStates.get(alert.key_expr) || {{ firing: false, silenced: false, acked: false }};
+                const firing = alert.state === 'firing' || alert.state === 'resolving';
+                if (alert.silenced_by !== undefined) {{
+                    state.silenced = true;
+                }} else if (firing) {{
+                    state.firing = true;
+                }}
+                if (alert.acknowledged) state.acked = true;
+                alertStates.set(alert.key_expr, state);
+            }});
+
+            if (silences.length > 0) {{
+                const details = silences.map(silence => {{
+                    const target = [silence.key_expr, silence.rule].filter(Boolean).join(' / ');
+                    return `${{target}} until ${{new Date(silence.expires_at).toLocaleTimeString()}}`;
+                }});
+                silenceBanner.textContent = `🔕 ${{silences.length}} active silence(s): ${{details.join(', ')}}`;
+                silenceBanner.style.display = '';
+            }} else {{
+                silenceBanner.style.display = 'none';
+            }}
+
+            applyFilter();
+        }}).catch(error => console.error("Error fetching alerts:", error));
+    }}
+    setInterval(refreshAlerts, {reload_ms});
+
+    function refreshStats() {{
+        fetch(`${{basePath}}/api/stats`)
+            .then(response => response.json())
+            .then(stats => {{
+                prefixChips.innerHTML = '';
+                Object.entries(stats.by_prefix)
+                    .sort((a, b) => b[1] - a[1])
+                    .forEach(([prefix, count]) => {{
+                        const chip = document.createElement('span');
+                        chip.className = 'prefix-chip';
+                        chip.textContent = `${{prefix}}: ${{count}}`;
+                        prefixChips.appendChild(chip);
+                    }});
+            }})
+            .catch(error => console.error("Error fetching stats:", error));
+    }}
+    setInterval(refreshStats, {reload_ms});
+
+    // One chip per federated monitor, red while it is unreachable
+    function refreshFederation() {{
+        fetch(`${{basePath}}/api/federation`)
+            .then(response => response.json())
+            .then(upstreams => {{
+                instanceChips.innerHTML = '';
+                upstreams.forEach(upstream => {{
+                    const chip = document.createElement('span');
+                    chip.className = upstream.connected ? 'prefix-chip' : 'prefix-chip instance-down';
+                    chip.textContent = `${{upstream.connected ? '●' : '○'}} ${{upstream.name}}: ${{upstream.topics}}`;
+                    chip.title = upstream.connected
+                        ? `${{upstream.url}}, clock offset ${{upstream.clock_offset_ms}} ms`
+                        : `${{upstream.url}} unreachable: ${{upstream.last_error || 'not connected yet'}}`;
+                    instanceChips.appendChild(chip);
+                }});
+            }})
+            .catch(error => console.error("Error fetching federation status:", error));
+    }}
+    setInterval(refreshFederation, {reload_ms});
+
+    function rebuildTable() {{
+        tableBody.innerHTML = '';
+        sortTopics().forEach(topicData => createAndInsertRow(topicData));
+        applyFilter();
+    }}
+
+    function formatSize(topicData) {{
+        return topicData.empty_payload ? 'signal, 0 bytes' : topicData.size_display;
+    }}
+
+    // Scales the points to the 60x16 sparkline box, highest rate at the top
+    function sparklinePoints(points) {{
+        if (!points || points.length < 2) return '';
+        const max = Math.max(...points, 0.01);
+        const step = 60 / (points.length - 1);
+        return points.map((hz, i) => `${{(i * step).toFixed(1)}},${{(15 - (hz / max) * 14).toFixed(1)}}`).join(' ');
+    }}
+
+    // Header chart of the global totals: history from /api/throughput, then
+    // one point per SSE update. Each line is scaled to its own maximum.
+    const throughputWindowMs = 300000;
+    let throughputPoints = [];
+
+    function throughputLine(field) {{
+        if (throughputPoints.length < 2) return '';
+        const end = throughputPoints[throughputPoints.length - 1].at;
+        const max = Math.max(...throughputPoints.map(point => point[field]), 0.01);
+        return throughputPoints
+            .map(point => `${{((point.at - end + throughputWindowMs) / throughputWindowMs * 300).toFixed(1)}},${{(39 - point[field] / max * 38).toFixed(1)}}`)
+            .join(' ');
+    }}
+
+    function drawThroughput() {{
+        document.querySelector('.throughput-rate').setAttribute('points', throughputLine('messages_per_sec'));
+        document.querySelector('.throughput-bandwidth').setAttribute('points', throughputLine('bytes_per_sec'));
+    }}
+
+    function addThroughputPoint(point) {{
+        const last = throughputPoints[throughputPoints.length - 1];
+        if (last && point.at <= last.at) return;
+        throughputPoints.push(point);
+        throughputPoints = throughputPoints.filter(p => p.at >= point.at - throughputWindowMs);
+        document.getElementById('throughput-label').textContent =
+            `${{point.rate_display}} · ${{point.bandwidth_display}} · ${{point.topics}} topics`;
+        drawThroughput();
+    }}
+
+    fetch(`${{basePath}}/api/throughput?window=${{throughputWindowMs / 1000}}`)
+        .then(response => response.json())
+        .then(points => {{
+            // Points that arrived over SSE in the meantime are newer than the history
+            const latest = points.length ? points[points.length - 1].at : 0;
+            throughputPoints = points.concat(throughputPoints.filter(point => point.at > latest));
+            drawThroughput();
+        }})
+        .catch(error => console.error("Error fetching throughput:", error));
+
+    function updateSparklines() {{
+        tableBody.querySelectorAll('tr[data-key]').forEach(row => {{
+            const polyline = row.querySelector('.sparkline polyline');
+            if (polyline) polyline.setAttribute('points', sparklinePoints(hzHistories.get(row.dataset.key)));
+        }});
+    }}
+
+    function loadOf(topicData) {{
+        const hz = topicData.estimated_hz || 0;
+        return loadMetric === 'rate' ? hz : hz * (topicData.avg_data_size_bytes || 0);
+    }}
+
+    function updateLoadBars() {{
+        if (!columns.includes('load')) return;
+        let max = 0;
+        let total = 0;
+        topics.forEach(topicData => {{
+            const load = loadOf(topicData);
+            max = Math.max(max, load);
+            total += load;
+        }});
+        tableBody.querySelectorAll('tr[data-key]').forEach(row => {{
+            const fill = row.querySelector('.load-fill');
+            const topicData = topics.get(row.dataset.key);
+            if (!fill || !topicData) return;
+            const load = loadOf(topicData);
+            fill.style.width = max > 0 ? `${{(load / max * 100).toFixed(1)}}%` : '0';
+            fill.parentElement.title = total > 0 ? `${{(load / total * 100).toFixed(1)}}% of total ${{loadMetric}}` : '';
+        }});
+    }}
+
+    // Highlight classes come from the server's rules; the stylesheet has one per class
+    function applyHighlight(row, topicData) {{
+        [...row.classList].filter(name => name.startsWith('highlight-')).forEach(name => row.classList.remove(name));
+        if (topicData.highlight) row.classList.add(`highlight-${{topicData.highlight}}`);
+    }}
+
+    function stabilityClass(topicData) {{
+        if (topicData.stability >= 80) return 'stability-cell';
+        return topicData.stability >= 50 ? 'stability-cell stability-fair' : 'stability-cell stability-poor';
+    }}
+
+    // Change from the previous payload, e.g. "+128 B (+12.5%)"; nothing when unchanged
+    function sizeDelta(topicData) {{
+        const delta = topicData.size_delta_bytes;
+        if (!delta) return '';
+        const sign = delta > 0 ? '+' : '−';
+        return `<span class="size-delta">${{sign}}${{Math.abs(delta)}} B (${{sign}}${{Math.abs(topicData.size_delta_pct).toFixed(1)}}%)</span>`;
+    }}
+
+    // A latched publisher re-sending the same message shows as "×412" rather than as fresh data
+    function repeatBadge(topicData) {{
+        const count = topicData.identical_repeat_count;
+        return count > 0
+            ? `<span class="repeat-badge" title="Same payload repeated ${{count}}× (${{topicData.distinct_payloads}} distinct)">×${{count}}</span>`
+            : '';
+    }}
+
+    // Rates in Hz, or as a percentage of the expected rate where there is one
+    let rateDisplay = localStorage.getItem('rateDisplay') || '{rate_display}';
+    function isRelative(topicData) {{
+        return rateDisplay === 'relative' && topicData.expected_hz > 0 && topicData.estimated_hz !== null;
+    }}
+
+    function formatHz(topicData) {{
+        return isRelative(topicData)
+            ? `${{Math.round(topicData.estimated_hz / topicData.expected_hz * 100)}}%`
+            : topicData.hz_display;
+    }}
+
+    // With a manifest, the rate reads "47.8 Hz / 50 Hz" (or "96% of 50 Hz"),
+    // coloured by how far off it is
+    function formatExpectedHz(topicData) {{
+        if (topicData.expected_hz === undefined) return '';
+        const expected = `${{+topicData.expected_hz.toFixed(2)}} Hz`;
+        return isRelative(topicData) ? ` of ${{expected}}` : ` / ${{expected}}`;
+    }}
+
+    function freqClass(topicData) {{
+        const deviation = Math.abs(topicData.hz_deviation_pct || 0);
+        if (deviation > {rate_bad_pct}) return 'freq-cell rate-bad';
+        return deviation > {rate_warn_pct} ? 'freq-cell rate-warn' : 'freq-cell';
+    }}
+
+    function timingClass(cell, exceeded) {{
+        return exceeded ? `${{cell}} timing-exceeded` : cell;
+    }}
+
+    function sourceClass(topicData) {{
+        return topicData.source_count > 1 ? 'source-cell source-conflict' : 'source-cell';
+    }}
+
+    function sourceTitle(topicData) {{
+        return `${{topicData.source_count}} publisher(s) in the last {source_window_s} s`;
+    }}
+
+    function publishersClass(topicData) {{
+        return topicData.publisher_conflict ? 'publishers-cell source-conflict' : 'publishers-cell';
+    }}
+
+    function publishersTitle(topicData) {{
+        return topicData.publisher_conflict ? 'Only one publisher is expected on this key' : 'Live publishers (liveliness tokens)';
+    }}
+
+    // Decode errors are flagged beside the last good content until dismissed;
+    // a newer error brings the badge back
+    const dismissedDecodeErrors = new Map();
+
+    function decodeErrorBadge(topicData) {{
+        const at = topicData.last_decode_error_at;
+        if (!at || dismissedDecodeErrors.get(topicData.key_expr) >= at) return '';
+        const failing = topicData.decode_success_streak === 0;
+        return `<span class="decode-error-badge${{failing ? ' failing' : ''}}" title="${{escapeHtml(topicData.last_decode_error || '')}}">`
+            + `⚠ ${{failing ? 'decode failing' : 'decode error'}}<button class="dismiss-error-btn" title="Dismiss">×</button></span>`;
+    }}
+
+    function decodedHtml(topicData) {{
+        return decodeErrorBadge(topicData) + (topicData.decoded_content || (topicData.empty_payload ? '(signal)' : '-'));
+    }}
+
+    function decodedByTitle(topicData) {{
+        return topicData.decoded_by ? `Decoded by ${{escapeHtml(topicData.decoded_by)}}` : '';
+    }}
+
+    function formatTimestamp(topicData) {{
+        if (topicData.received_timestamp === 0) return 'never received';
+        return new Date(topicData.received_timestamp).toISOString().replace('T', ' ').replace('Z', ' UTC');
+    }}
+
+    // One builder per configurable column, in the order given by the server
+    const cellBuilders = {{
+        key: topicData => `<td class="topic-cell"><button class="pin-btn" title="Pin/unpin topic">📌</button><button class="mute-btn" title="Mute/unmute topic">🔇</button><span class="topic-key" title="${{escapeHtml(topicData.key_expr)}}">${{escapeHtml(displayKey(topicData.key_expr))}}</span><span class="alert-badge"></span></td>`,
+        size: topicData => `<td class="size-cell${{topicData.empty_payload ? ' signal' : ''}}">${{formatSize(topicData)}}${{sizeDelta(topicData)}}${{repeatBadge(topicData)}}</td>`,
+        hz: topicData => `<td class="${{freqClass(topicData)}}"><span class="freq-value">${{formatHz(topicData)}}</span><span class="freq-expected">${{formatExpectedHz(topicData)}}</span><svg class="sparkline" viewBox="0 0 60 16" preserveAspectRatio="none"><polyline points="${{sparklinePoints(hzHistories.get(topicData.key_expr))}}"/></svg></td>`,
+        bandwidth: topicData => `<td class="bandwidth-cell">${{topicData.bandwidth_display}}</td>`,
+        timestamp: topicData => `<td class="timestamp-cell">${{formatTimestamp(topicData)}}</td>`,
+        age: topicData => `<td class="age-cell">-</td>`,
+        load: topicData => `<td class="load-cell"><div class="load-bar"><div class="load-fill"></div></div></td>`,
+        decoded: topicData => `<td class="decoded-cell" title="${{decodedByTitle(topicData)}}">${{decodedHtml(topicData)}}</td>`,
+        stability: topicData => `<td class="${{stabilityClass(topicData)}}">${{topicData.stability}}</td>`,
+        jitter: topicData => `<td class="${{timingClass('jitter-cell', topicData.jitter_exceeded)}}">${{topicData.jitter_display}}</td>`,
+        latency: topicData => `<td class="${{timingClass('latency-cell', topicData.latency_exceeded)}}">${{topicData.latency_display}}</td>`,
+        source: topicData => `<td class="${{sourceClass(topicData)}}" title="${{sourceTitle(topicData)}}">${{escapeHtml(topicData.last_source || '-')}}</td>`,
+        publishers: topicData => `<td class="${{publishersClass(topicData)}}" title="${{publishersTitle(topicData)}}">${{topicData.publisher_count ?? '-'}}</td>`,
+        instance: topicData => `<td class="instance-cell">${{escapeHtml(topicData.instance || 'local')}}</td>`,
+        status: topicData => `<td class="status-cell"></td>`,
+    }};
+
+    function createAndInsertRow(topicData) {{
+        const row = document.createElement('tr');
+        row.dataset.key = topicData.key_expr;
+        row.dataset.timestamp = topicData.received_timestamp;
+        row.classList.toggle('pinned-row', !!topicData.pinned);
+        row.classList.toggle('missing-row', topicData.received_timestamp === 0);
+        applyHighlight(row, topicData);
+        row.innerHTML = columns.map(column => cellBuilders[column](topicData)).join('');
+        tableBody.appendChild(row);
+    }}
+
+    function updateRow(topicData) {{
+        let row = getRowByKey(topicData.key_expr);
+
+        if (row) {{
+            const sizeCell = row.querySelector('.size-cell');
+            if (sizeCell) {{
+                sizeCell.innerHTML = formatSize(topicData) + sizeDelta(topicData) + repeatBadge(topicData);
+                sizeCell.classList.toggle('signal', !!topicData.empty_payload);
+            }}
+            const freqValue = row.querySelector('.freq-value');
+            if (freqValue) {{
+                freqValue.textContent = formatHz(topicData);
+                freqValue.nextElementSibling.textContent = formatExpectedHz(topicData);
+                freqValue.parentElement.className = freqClass(topicData);
+            }}
+            const bandwidthCell = row.querySelector('.bandwidth-cell');
+            if (bandwidthCell) bandwidthCell.textContent = topicData.bandwidth_display;
+            const timestampCell = row.querySelector('.timestamp-cell');
+            if (timestampCell) timestampCell.textContent = formatTimestamp(topicData);
+            const sourceCell = row.querySelector('.source-cell');
+            if (sourceCell) {{
+                sourceCell.textContent = topicData.last_source || '-';
+                sourceCell.className = sourceClass(topicData);
+                sourceCell.title = sourceTitle(topicData);
+            }}
+            const publishersCell = row.querySelector('.publishers-cell');
+            if (publishersCell) {{
+                publishersCell.textContent = topicData.publisher_count ?? '-';
+                publishersCell.className = publishersClass(topicData);
+                publishersCell.title = publishersTitle(topicData);
+            }}
+            const stabilityCell = row.querySelector('.stability-cell');
+            if (stabilityCell) {{
+                stabilityCell.textContent = topicData.stability;
+                stabilityCell.className = stabilityClass(topicData);
+            }}
+            const jitterCell = row.querySelector('.jitter-cell');
+            if (jitterCell) {{
+                jitterCell.textContent = topicData.jitter_display;
+                jitterCell.className = timingClass('jitter-cell', topicData.jitter_exceeded);
+            }}
+            const latencyCell = row.querySelector('.latency-cell');
+            if (latencyCell) {{
+                latencyCell.textContent = topicData.latency_display;
+                latencyCell.className = timingClass('latency-cell', topicData.latency_exceeded);
+            }}
+            row.dataset.timestamp = topicData.received_timestamp;
+            const pinChanged = row.classList.contains('pinned-row') !== !!topicData.pinned;
+            row.classList.toggle('pinned-row', !!topicData.pinned);
+            row.classList.toggle('missing-row', topicData.received_timestamp === 0);
+            applyHighlight(row, topicData);
+
+            if (hasDecoder) {{
+                const decodedCell = row.querySelector('.decoded-cell');
+                if (decodedCell) {{
+                    decodedCell.innerHTML = decodedHtml(topicData);
+                    decodedCell.title = decodedByTitle(topicData);
+                }}
+            }}
+
+            if (!topicData.repeated_payload) {{
+                row.classList.add('updated-row');
+                setTimeout(() => row.classList.remove('updated-row'), 500);
+            }}
+
+            if (sortField !== 'key' || pinChanged) {{
+                row.remove();
+                insertRowInOrder(row, topicData);
+            }}
+        }} else {{
+            createAndInsertRow(topicData);
+
+            // Also places a pinned topic in the pinned section
+            row = getRowByKey(topicData.key_expr);
+            if (row) {{
+                row.remove();
+                insertRowInOrder(row, topicData);
+            }}
+        }}
+    }}
+
+    // A topic seen for the first time gets a longer flash and a "new" badge for
+    // a while; one that had been removed earlier says "back" instead
+    function markNewRow(topicKey, reappeared) {{
+        const row = getRowByKey(topicKey);
+        if (!row) return;
+        row.classList.remove('updated-row');
+        row.classList.add('new-row');
+        setTimeout(() => row.classList.remove('new-row'), 3000);
+        const badge = document.createElement('span');
+        badge.className = reappeared ? 'new-badge reappeared' : 'new-badge';
+        badge.textContent = reappeared ? 'back' : 'new';
+        badge.title = reappeared ? 'Reappeared after being removed' : 'First seen this session';
+        row.querySelector('.topic-key').after(badge);
+        setTimeout(() => badge.remove(), 10000);
+    }}
+
+    function insertRowInOrder(row, topicData) {{
+        const existingRows = tableBody.querySelectorAll('tr[data-key]');
+        let inserted = false;
+
+        for (const existingRow of existingRows) {{
+            const existingTopic = topics.get(existingRow.dataset.key);
+            const shouldInsertBefore = !!existingTopic && compareTopics(topicData, existingTopic) < 0;
+
+            if (shouldInsertBefore) {{
+                tableBody.insertBefore(row, existingRow);
+                inserted = true;
+                break;
+            }}
+        }}
+
+        if (!inserted) {{
+            tableBody.appendChild(row);
+        }}
+    }}
+
+    function removeRow(topicKey) {{
+        tableBody.querySelectorAll('tr[data-key]').forEach(row => {{
+            if (row.dataset.key === topicKey) row.remove();
+        }});
+    }}
+
+    // After every delta: exactly one row per topic, and the count matches the visible rows
+    function checkFilteredCount() {{
+        const rows = [...tableBody.querySelectorAll('tr[data-key]')];
+        const visible = rows.filter(row => row.style.display !== 'none').length;
+        console.assert(rows.length === topics.size, `${{rows.length}} rows for ${{topics.size}} topics`);
+        console.assert(filteredCount.textContent === `${{visible}} Topics`,
+            `filtered count "${{filteredCount.textContent}}" but ${{visible}} rows visible`);
+    }}
+
+    function updateSortButton() {{
+        if (sortField === 'key' && !sortDescending) {{
+            sortButton.textContent = 'Sort: Alphabetical';
+        }} else if (sortField === 'timestamp' && sortDescending) {{
+            sortButton.textContent = 'Sort: Most Recent First';
+        }} else if (sortField === 'age' && sortDescending) {{
+            sortButton.textContent = 'Sort: Stalest First';
+        }} else {{
+            sortButton.textContent = `Sort: ${{sortField}} ${{sortDescending ? '↓' : '↑'}}`;
+        }}
+    }}
+
+    function toggleSort() {{
+        sortField = sortFields[(sortFields.indexOf(sortField) + 1) % sortFields.length];
+        sortDescending = sortField === defaultSort.field
+            ? defaultSort.direction === 'desc'
+            : !['key', 'stability'].includes(sortField);
+        updateSortButton();
+        rebuildTable();
+    }}
+
+    function refreshTimestamp(topicKey, receivedTimestamp) {{
+        const topicData = topics.get(topicKey);
+        if (!topicData || topicData.received_timestamp === receivedTimestamp) return;
+        topicData.received_timestamp = receivedTimestamp;
+        const row = getRowByKey(topicKey);
+        if (!row) return;
+        row.dataset.timestamp = receivedTimestamp;
+        const timestampCell = row.querySelector('.timestamp-cell');
+        if (timestampCell) timestampCell.textContent = formatTimestamp(topicData);
+    }}
+
+    function currentAge(topicKey) {{
+        const topicData = topics.get(topicKey);
+        if (topicData && topicData.received_timestamp === 0) return null;
+        const age = ages.get(topicKey);
+        return age ? age.ageMs + (Date.now() - age.at) : null;
+    }}
+
+    function formatAge(ageMs) {{
+        if (ageMs === null) return '-';
+        if (ageMs < 10000) return `${{(ageMs / 1000).toFixed(1)}} s ago`;
+        if (ageMs < 60000) return `${{Math.floor(ageMs / 1000)}} s ago`;
+        if (ageMs < 3600000) return `${{Math.floor(ageMs / 60000)}} m ago`;
+        return `${{Math.floor(ageMs / 3600000)}} h ago`;
+    }}
+
+    function refreshAges() {{
+        tableBody.querySelectorAll('tr[data-key]').forEach(row => {{
+            const age = currentAge(row.dataset.key);
+            row.classList.toggle('stale-row', age !== null && age > staleAfterMs);
+            const ageCell = row.querySelector('.age-cell');
+            if (ageCell) ageCell.textContent = formatAge(age);
+        }});
+    }}
+    setInterval(refreshAges, 1000);
+
+    function decayFrequencies() {{
+        const rows = tableBody.querySelectorAll('tr[data-key]');
+        rows.forEach(row => {{
+            const freqValue = row.querySelector('.freq-value');
+            const elapsed = currentAge(row.dataset.key);
+            if (freqValue && elapsed !== null) {{
+                if (elapsed > staleAfterMs) {{
+                    let currentHz = parseFloat(freqValue.textContent) || 0;
+                    const unit = freqValue.textContent.endsWith('%') ? '%' : ' Hz';
+                    // apply exponential decay factor for faster drop
+                    currentHz *= 0.5; // halve every tick (1s)
+                    freqValue.textContent = currentHz > 0.01 ? `${{currentHz.toFixed(unit === '%' ? 0 : 2)}}${{unit}}` : `0${{unit}}`;
+                }}
+            }}
+        }});
+    }}
+    setInterval(decayFrequencies, 1000);
+
+    // Saved views: filter, sort, visible columns and watchlist, shared as `/?view=<name>`
+    const viewSelect = document.getElementById('view-select');
+    const viewNotice = document.getElementById('view-notice');
+
+    function keyExprRegex(pattern) {{
+        const escaped = pattern.split('/')
+            .map(chunk => chunk === '**' ? '.*' : chunk.replace(/[.+?^${{}}()|[\]\\]/g, '\\$&').replace(/\*/g, '[^/]*'))
+            .join('/');
+        return new RegExp(`^${{escaped}}$`);
+    }}
+
+    function hideColumns(visible) {{
+        document.getElementById('view-columns').textContent = columns
+            .map((column, i) => visible.includes(column) ? ''
+                : `thead th:nth-child(${{i + 1}}), tr[data-key] td:nth-child(${{i + 1}}) {{ display: none; }}`)
+            .join('\n');
+    }}
+
+    // Column chooser: hidden columns are remembered per browser, unless a view sets them
+    const columnMenu = document.getElementById('column-menu');
+    let hiddenColumns = new Set(JSON.parse(localStorage.getItem('hiddenColumns') || '[]'));
+
+    function visibleColumns() {{
+        return columns.filter(column => !hiddenColumns.has(column));
+    }}
+
+    function applyHiddenColumns() {{
+        hideColumns(visibleColumns());
+        columnMenu.querySelectorAll('input').forEach(checkbox => {{
+            checkbox.checked = !hiddenColumns.has(checkbox.value);
+        }});
+    }}
+
+    const columnTitles = Array.from(document.querySelectorAll('thead th'), th => th.textContent);
+    columnMenu.innerHTML = columns
+        .map((column, i) => column === 'key' ? ''
+            : `<label><input type="checkbox" value="${{column}}"> ${{escapeHtml(columnTitles[i])}}</label>`)
+        .join('');
+    columnMenu.addEventListener('change', event => {{
+        if (event.target.checked) {{
+            hiddenColumns.delete(event.target.value);
+        }} else {{
+            hiddenColumns.add(event.target.value);
+        }}
+        localStorage.setItem('hiddenColumns', JSON.stringify(Array.from(hiddenColumns)));
+        applyHiddenColumns();
+    }});
+    document.getElementById('columns-btn').addEventListener('click', () => {{
+        columnMenu.style.display = columnMenu.style.display === 'none' ? '' : 'none';
+    }});
+    document.addEventListener('click', event => {{
+        if (!event.target.closest('.column-chooser')) columnMenu.style.display = 'none';
+    }});
+    applyHiddenColumns();
+
+    // Table or grid of tiles for wall displays; both are the same rows, restyled,
+    // so sorting, filtering and updates need no changes
+    const layoutButton = document.getElementById('layout-btn');
+    let layout = localStorage.getItem('layout') || 'table';
+    function applyLayout() {{
+        document.querySelector('.container').classList.toggle('grid-view', layout === 'grid');
+        layoutButton.textContent = layout === 'grid' ? 'Grid' : 'Table';
+    }}
+    layoutButton.addEventListener('click', () => {{
+        layout = layout === 'grid' ? 'table' : 'grid';
+        localStorage.setItem('layout', layout);
+        applyLayout();
+    }});
+    applyLayout();
+
+    const themeSelect = document.getElementById('theme-select');
+    themeSelect.value = document.documentElement.dataset.theme || '{default_theme}';
+    themeSelect.addEventListener('change', () => {{
+        document.documentElement.dataset.theme = themeSelect.value;
+        localStorage.setItem('theme', themeSelect.value);
+    }});
+
+    const rateDisplayButton = document.getElementById('rate-display-btn');
+    function applyRateDisplay() {{
+        rateDisplayButton.textContent = rateDisplay === 'relative' ? '% of expected' : 'Hz';
+        tableBody.querySelectorAll('tr[data-key]').forEach(row => {{
+            const topicData = topics.get(row.dataset.key);
+            const freqValue = row.querySelector('.freq-value');
+            if (!topicData || !freqValue) return;
+            freqValue.textContent = formatHz(topicData);
+            freqValue.nextElementSibling.textContent = formatExpectedHz(topicData);
+        }});
+    }}
+    rateDisplayButton.addEventListener('click', () => {{
+        rateDisplay = rateDisplay === 'relative' ? 'absolute' : 'relative';
+        localStorage.setItem('rateDisplay', rateDisplay);
+        applyRateDisplay();
+    }});
+    applyRateDisplay();
+
+    function applyView(view) {{
+        filterInput.value = view.filter || '';
+        if (view.sort) {{
+            sortField = view.sort.field;
+            sortDescending = view.sort.direction === 'desc';
+            updateSortButton();
+        }}
+        watchlist = (view.watchlist || []).map(keyExprRegex);
+        if (view.columns) {{
+            hiddenColumns = new Set(columns.filter(column => !view.columns.includes(column)));
+            applyHiddenColumns();
+        }}
+    }}
+
+    function refreshViews() {{
+        fetch(`${{basePath}}/api/views`)
+            .then(response => response.json())
+            .then(views => {{
+                viewSelect.innerHTML = '<option value="">Default view</option>' + views
+                    .map(view => `<option value="${{escapeHtml(view.name)}}">${{escapeHtml(view.name)}}</option>`)
+                    .join('');
+                if (initialView && initialView.view) viewSelect.value = initialView.view.name;
+            }})
+            .catch(() => {{}});
+    }}
+
+    // Saves the current filter, sort and visible columns, keeping the watchlist of the open view
+    function saveView() {{
+        const name = prompt('Save the current filter, sort and columns as view:', viewSelect.value || '');
+        if (!name) return;
+        const view = {{
+            name,
+            filter: filterInput.value || null,
+            sort: {{ field: sortField, direction: sortDescending ? 'desc' : 'asc' }},
+            columns: hiddenColumns.size ? visibleColumns() : null,
+        }};
+        if (initialView && initialView.view) {{
+            view.watchlist = initialView.view.watchlist;
+        }}
+        fetch(`${{basePath}}/api/views`, {{
+            method: 'POST',
+            headers: {{ 'Content-Type': 'application/json' }},
+            body: JSON.stringify(view),
+        }})
+            .then(response => response.json())
+            .then(body => {{
+                if (body.error) {{
+                    alert(body.error);
+                }} else {{
+                    window.location.search = `?view=${{encodeURIComponent(body.name)}}`;
+                }}
+            }})
+            .catch(() => alert('Failed to save the view.'));
+    }}
+
+    viewSelect.addEventListener('change', () => {{
+        window.location.search = viewSelect.value ? `?view=${{encodeURIComponent(viewSelect.value)}}` : '';
+    }});
+    document.getElementById('save-view-btn').addEventListener('click', saveView);
+    if (initialView && initialView.view) {{
+        applyView(initialView.view);
+    }} else if (initialView) {{
+        viewNotice.textContent = `The view "${{initialView.missing}}" no longer exists; showing the default view.`;
+        viewNotice.style.display = '';
+    }}
+    refreshViews();
+
+    // Event handlers
+    updateSortButton();
+    sortButton.addEventListener('click', toggleSort);
+    filterInput.addEventListener('input', applyFilter);
+    showMutedButton.addEventListener('click', toggleShowMuted);
+    searchModeButton.addEventListener('click', toggleSearchMode);
+    if (!hasDecoder) searchModeButton.parentElement.style.display = 'none';
+    tableBody.addEventListener('click', event => {{
+        const dismissButton = event.target.closest('.dismiss-error-btn');
+        if (dismissButton) {{
+            const topicData = topics.get(dismissButton.closest('tr').dataset.key);
+            dismissedDecodeErrors.set(topicData.key_expr, topicData.last_decode_error_at);
+            dismissButton.closest('.decode-error-badge').remove();
+            return;
+        }}
+        const muteButton = event.target.closest('.mute-btn');
+        if (muteButton) toggleMute(muteButton.closest('tr').dataset.key);
+        const pinButton = event.target.closest('.pin-btn');
+        if (pinButton) togglePin(pinButton.closest('tr').dataset.key);
+        const row = event.target.closest('tr[data-key]');
+        if (row && !muteButton && !pinButton) toggleDetail(row.dataset.key);
+    }});
+
+    eventSource.addEventListener("message", function(event) {{
+        lastEventAt = Date.now();
+        try {{
+            const delta = JSON.parse(event.data);
+            const updated = delta.updated || [];
+            const removed = delta.removed || [];
+
+            // A new stream (e.g. after a reconnect) starts at seq 1 with a full
+            // snapshot, so local state is dropped and rebuilt from it. Any other
+            // jump means deltas were lost and only a fresh stream can recover.
+            if (delta.seq !== undefined) {{
+                if (delta.seq === 1) {{
+                    topics.clear();
+                    tableBody.innerHTML = '';
+                }} else if (lastSeq !== null && delta.seq !== lastSeq + 1) {{
+                    console.warn(`SSE sequence gap (${{lastSeq}} -> ${{delta.seq}}), resyncing`);
+                    eventSource.close();
+                    window.location.reload();
+                    return;
+                }}
+                lastSeq = delta.seq;
+            }}
+
+            const histories = Object.entries(delta.hz_history || {{}});
+            histories.forEach(([topicKey, points]) => hzHistories.set(topicKey, points));
+            if (delta.throughput) addThroughputPoint(delta.throughput);
+
+            const receivedAt = Date.now();
+            // A new sample that changes nothing shown arrives only as an age
+            Object.entries(delta.ages || {{}}).forEach(([topicKey, ageMs]) => {{
+                ages.set(topicKey, {{ ageMs, at: receivedAt }});
+                refreshTimestamp(topicKey, delta.server_time - ageMs);
+            }});
+
+            updated.forEach(topicData => {{
+                // Decoded content arrives separately and stays until its event replaces it
+                if (decodedEvent && topics.has(topicData.key_expr)) {{
+                    topicData.decoded_content = topics.get(topicData.key_expr).decoded_content;
+                }}
+                topics.set(topicData.key_expr, topicData);
+                ages.set(topicData.key_expr, {{
+                    ageMs: Math.max(0, delta.server_time - topicData.received_timestamp),
+                    at: receivedAt,
+                }});
+                updateRow(topicData);
+            }});
+
+            const reappeared = new Set(delta.reappeared || []);
+            (delta.added || []).forEach(topicData => {{
+                topics.set(topicData.key_expr, topicData);
+                ages.set(topicData.key_expr, {{
+                    ageMs: Math.max(0, delta.server_time - topicData.received_timestamp),
+                    at: receivedAt,
+                }});
+                updateRow(topicData);
+                markNewRow(topicData.key_expr, reappeared.has(topicData.key_expr));
+            }});
+
+            removed.forEach(topicKey => {{
+                topics.delete(topicKey);
+                ages.delete(topicKey);
+                hzHistories.delete(topicKey);
+                removeRow(topicKey);
+            }});
+
+            // Filtered once the whole delta is applied, so the count never reflects a
+            // half-applied burst of updates and removals
+            applyFilter();
+            checkFilteredCount();
+
+            if (histories.length > 0) updateSparklines();
+            updateLoadBars();
+            updateStats();
+            refreshAges();
+        }} catch (error) {{
+            console.error("Error processing SSE message:", error);
+        }}
+    }});
+
+    // A planned stop: show it instead of silently freezing, and reload once the monitor is back
+    eventSource.addEventListener("shutdown", function() {{
+        eventSource.close();
+        document.getElementById('stopped-banner').style.display = '';
+        setInterval(() => {{
+            fetch(`${{basePath}}/api/status`)
+                .then(response => {{ if (response.ok) window.location.reload(); }})
+                .catch(() => {{}});
+        }}, 5000);
+    }});
+
+    // Ticks where nothing changed send nothing, and a quiet server sends a heartbeat
+    // instead, so only a stream silent for three heartbeats is taken as lost
+    eventSource.addEventListener("heartbeat", () => {{ lastEventAt = Date.now(); }});
+
+    // With SSE_DECODED_EVENT, each update is followed by the decoded content of its topics
+    const decodedEvent = {decoded_event};
+    eventSource.addEventListener("decoded", function(event) {{
+        lastEventAt = Date.now();
+        try {{
+            Object.entries(JSON.parse(event.data)).forEach(([topicKey, content]) => {{
+                const topicData = topics.get(topicKey);
+                if (!topicData) return;
+                if (content === null) delete topicData.decoded_content;
+                else topicData.decoded_content = content;
+                const row = getRowByKey(topicKey);
+                const decodedCell = row && row.querySelector('.decoded-cell');
+                if (decodedCell) decodedCell.innerHTML = decodedHtml(topicData);
+            }});
+            if (searchContent) applyFilter();
+        }} catch (error) {{
+            console.error("Error processing decoded content:", error);
+        }}
+    }});
+    setInterval(() => {{
+        if (eventSource.readyState !== EventSource.CLOSED && Date.now() - lastEventAt > 3 * {heartbeat_ms}) {{
+            console.warn("No SSE events for three heartbeats, reconnecting");
+            eventSource.close();
+            window.location.reload();
+        }}
+    }}, {heartbeat_ms});
+
+    // Receiving trouble is shown until the subscriber is back, so a frozen table
+    // is never mistaken for a quiet network
+    const subscriberBanner = document.getElementById('subscriber-banner');
+    function refreshSubscriberStatus() {{
+        fetch(`${{basePath}}/api/status`)
+            .then(response => response.json())
+            .then(status => {{
+                const subscriber = status.subscriber;
+                if (status.subscriber_healthy) {{
+                    subscriberBanner.style.display = 'none';
+                    return;
+                }}
+                const retry = subscriber.retry_at
+                    ? `, retrying at ${{new Date(subscriber.retry_at).toLocaleTimeString()}}` : '';
+                subscriberBanner.textContent = subscriber.state === 'resubscribing'
+                    ? `⚠ Zenoh subscriber stopped receiving, re-subscribing (attempt ${{subscriber.attempt}}${{retry}})`
+                    : subscriber.state === 'restarting'
+                        ? `⚠ Zenoh subscriber down, restarting (restart #${{subscriber.restarts}}${{retry}})`
+                        : subscriber.state === 'unreachable'
+                            ? `⚠ Can't reach Zenoh yet (${{subscriber.attempts}} failed attempts${{retry}}). Topics appear once a session opens.`
+                            : '⚠ Connecting to Zenoh…';
+                subscriberBanner.style.display = '';
+            }})
+            .catch(() => {{}});
+    }}
+    setInterval(refreshSubscriberStatus, 5000);
+
+    // One light for the whole network; clicking it lists the topics behind the colour,
+    // and clicking a topic filters the table to it
+    const healthButton = document.getElementById('health-summary');
+    const healthDetails = document.getElementById('health-details');
+    function refreshHealth() {{
+        fetch(`${{basePath}}/api/health-summary`)
+            .then(response => response.ok ? response.json() : null)
+            .then(summary => {{
+                if (!summary) {{
+                    healthButton.style.display = 'none';
+                    healthDetails.style.display = 'none';
+                    return;
+                }}
+                healthButton.className = `health-summary health-${{summary.level}}`;
+                healthButton.textContent = summary.level === 'green'
+                    ? `● All clear, ${{summary.topics}} topics`
+                    : `● ${{summary.reasons.join(', ')}}`;
+                healthButton.style.display = '';
+                healthDetails.innerHTML = '';
+                [['Critical', summary.critical], ['Warning', summary.warning], ['Stale', summary.stale]]
+                    .filter(([, keys]) => keys.length > 0)
+                    .forEach(([label, keys]) => {{
+                        const group = document.createElement('div');
+                        group.innerHTML = `<strong>${{label}}</strong> `;
+                        keys.forEach(key => {{
+                            const chip = document.createElement('button');
+                            chip.className = 'prefix-chip';
+                            chip.textContent = key;
+                            chip.addEventListener('click', () => {{
+                                filterInput.value = key;
+                                applyFilter();
+                            }});
+                            group.appendChild(chip);
+                        }});
+                        healthDetails.appendChild(group);
+                    }});
+                if (healthDetails.childElementCount === 0) {{
+                    healthDetails.textContent = 'Nothing needs attention.';
+                }}
+            }})
+            .catch(() => {{}});
+    }}
+    healthButton.addEventListener('click', () => {{
+        healthDetails.style.display = healthDetails.style.display === 'none' ? '' : 'none';
+    }});
+    setInterval(refreshHealth, {reload_ms});
+
+    // initial render state
+    updateStats();
+    applyFilter();
+    refreshAlerts();
+    refreshStats();
+    refreshFederation();
+    refreshSubscriberStatus();
+    refreshHealth();
+}});
+</script>
+</head>
+<body>
+<div class="header">
+    <h1>{logo}{heading}</h1>
+    <p>{subtitle}{decoder_subtitle}</p>
+    <div class="throughput" title="Total messages and bytes per second over all topics">
+        <svg class="throughput-chart" viewBox="0 0 300 40" preserveAspectRatio="none"><polyline class="throughput-rate"/><polyline class="throughput-bandwidth"/></svg>
+        <span id="throughput-label"></span>
+    </div>
+    <button id="health-summary" class="health-summary" title="Show the topics behind this status" style="display: none"></button>
+    <div id="health-details" class="health-details" style="display: none"></div>
+</div>
+<div id="subscriber-banner" class="stopped-banner" style="display: none"></div>
+<div id="stopped-banner" class="stopped-banner" style="display: none">⏹ Monitor stopped. The data below is no longer live; the page will reload when the monitor is back.</div>
+<div id="silence-banner" class="silence-banner" style="display: none"></div>
+<div id="view-notice" class="silence-banner" style="display: none"></div>
+<div class="stats">
+    <div class="stat-item">
+        <span class="stat-value" id="topic-count">0</span>
+        <span class="stat-label">Topics</span>
+        <div class="prefix-chips" id="prefix-chips"></div>
+        <div class="prefix-chips" id="instance-chips"></div>
+    </div>
+
+    <div class="stat-item">
+        <button id="sort-toggle-btn" class="sort-toggle">Alphabetical</button>
+        <span class="stat-label">Sort Order</span>
+    </div>
+
+    <div class="stat-item">
+        <div>
+            <select id="view-select" class="sort-toggle"><option value="">Default view</option></select>
+            <button id="save-view-btn" class="sort-toggle" title="Save the current filter, sort and columns as a named view">Save</button>
+        </div>
+        <span class="stat-label">View</span>
+    </div>
+
+    <div class="stat-item column-chooser">
+        <button id="columns-btn" class="sort-toggle" title="Show or hide columns">⚙</button>
+        <div id="column-menu" class="column-menu" style="display: none"></div>
+        <span class="stat-label">Columns</span>
+    </div>
+
+    <div class="stat-item">
+        <button id="layout-btn" class="sort-toggle" title="Switch between the table and large tiles">Table</button>
+        <span class="stat-label">Layout</span>
+    </div>
+
+    <div class="stat-item">
+        <select id="theme-select" class="sort-toggle" title="Colour theme, remembered in this browser">{theme_options}</select>
+        <span class="stat-label">Theme</span>
+    </div>
+
+    <div class="stat-item">
+        <button id="rate-display-btn" class="sort-toggle" title="Show rates in Hz or as a percentage of each topic's expected rate">Hz</button>
+        <span class="stat-label">Rates</span>
+    </div>
+
+    <div class="stat-item">
+        <!-- Search box above the filtered count (no extra label) -->
+        <input
+            type="text"
+            id="filter-input"
+            class="filter-input"
+            placeholder="Filter topics..."
+        />
+        <span class="stat-label" id="filtered-count">0 Topics</span>
+    </div>
+
+    <div class="stat-item">
+        <button id="search-mode-btn" class="sort-toggle" title="Also match decoded content (slower)">Keys</button>
+        <span class="stat-label">Search In</span>
+    </div>
+
+    <div class="stat-item">
+        <button id="show-muted-btn" class="sort-toggle">Show Muted (0)</button>
+        <span class="stat-label">Muted Topics</span>
+    </div>
+
+    <div class="stat-item">
+        <span class="stat-value" id="last-updated-value"></span>
+        <span class="stat-label">Last Updated</span>
+    </div>
+</div>
+<details class="query-panel">
+    <summary>Query a key expression</summary>
+    <form class="query-form" id="query-form">
+        <input type="text" id="query-input" class="filter-input" placeholder="robot/1/config or robot/**?param=value" />
+        <button type="submit" class="sort-toggle">Query</button>
+    </form>
+    <pre class="query-result" id="query-result"></pre>
+</details>
+<div class="container">
+    <table>
+        <thead>
+            <tr>
+                {header_cells}
+            </tr>
+        </thead>
+        <tbody></tbody>
+    </table>
+</div>
+<div class="refresh-info">📊 Updates every {}ms | Built with Zenoh + Rust + Warp</div>
+</body>
+</html>"#,
+        config.reload_period_ms,
+        reload_ms = config.reload_period_ms,
+        page_css = page_css(config.theme, columns),
+        default_theme = config.theme.name(),
+        theme_options = Theme::ALL
+            .iter()
+            .map(|theme| format!(
+                r#"<option value="{}">{}</option>"#,
+                theme.name(),
+                theme.title()
+            ))
+            .collect::<String>(),
+        stale_after_ms = STALE_AFTER_MS,
+        heartbeat_ms = config.sse_heartbeat_ms,
+        decoded_event = config.sse_decoded_event,
+        key_max_chars = config.key_display_max_chars,
+        load_metric = config.load_bar_metric.name(),
+        rate_display = config.rate_display.name(),
+        default_sort_js =
+            serde_json::to_string(&config.default_sort).unwrap_or_else(|_| "null".to_string()),
+        sort_fields_js =
+            serde_json::to_string(config.sort_fields).unwrap_or_else(|_| "[]".to_string()),
+        highlight_css = highlight::css(config.highlight_rules),
+        rate_warn_pct = RATE_DEVIATION_WARN_PCT,
+        source_window_s = config.source_window_ms / 1000,
+        rate_bad_pct = RATE_DEVIATION_BAD_PCT,
+        base_path_js = serde_json::to_string(config.base_path.unwrap_or(""))
+            .unwrap_or_else(|_| "''".to_string()),
+        branding_css = branding.css(),
+        page_title = html_escape::encode_text(branding.page_title),
+        heading = html_escape::encode_text(branding.heading),
+        subtitle = html_escape::encode_text(branding.subtitle),
+        logo = branding
+            .logo_data_uri()
+            .map(|uri| format!(r#"<img class="logo" src="{}" alt="">"#, uri))
+            .unwrap_or_default(),
+        extra_css = extra_css,
+        columns_js = serde_json::to_string(&column_names).unwrap_or_else(|_| "[]".to_string()),
+        decoder_subtitle = if has_decoder {
+            " with custom decoder"
+        } else {
+            ""
+        },
+        header_cells = header_cells,
+    )
+}
+
+/// A copy of the cache's topic data, shared by every stream of deltas
+#[derive(Debug, Clone, Default)]
+struct CacheSnapshot {
+    topics: Arc<HashMap<String, TopicData>>,
+    /// `TombstoneStore::removals` when the copy was taken
+    removals: u64,
+}
+
+impl CacheSnapshot {
+    async fn of(cache: &TopicCache, tombstones: &Tombstones) -> Self {
+        let cache = cache.read().await;
+        // Interval windows stay behind; the streams only send topic data
+        let topics = cache
+            .iter()
+            .map(|(key, topic)| (key.clone(), topic.data.clone()))
+            .collect();
+        // Read under the cache lock, which removals hold, so it matches the copy
+        let removals = tombstones.read().await.removals;
+        Self {
+            topics: Arc::new(topics),
+            removals,
+        }
+    }
+}
+
+impl std::ops::Deref for CacheSnapshot {
+    type Target = HashMap<String, TopicData>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.topics
+    }
+}
+
+/// Copies the cache once every `period_ms` for all streams of deltas, so they
+/// share one timer and one read of the cache per tick instead of one each.
+/// A slow stream skips to the latest copy rather than working through a backlog.
+async fn run_delta_ticker(
+    cache: TopicCache,
+    tombstones: Tombstones,
+    ticks: watch::Sender<CacheSnapshot>,
+    period_ms: u64,
+) {
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        ticks.send_replace(CacheSnapshot::of(&cache, &tombstones).await);
+    }
+}
+
+/// What every stream of deltas is computed from
+#[derive(Clone)]
+struct DeltaSources {
+    /// Ticked by `run_delta_ticker` every `Config::reload_period_ms`
+    ticks: watch::Receiver<CacheSnapshot>,
+    metric_history: MetricHistory,
+    send_sparklines: bool,
+    throughput: Throughput,
+    display_units: format::Units,
+    tombstones: Tombstones,
+    /// Longest silence of an SSE stream, from `Config::sse_heartbeat_ms`
+    heartbeat_ms: u64,
+    /// SSE streams send decoded content as separate `decoded` events, from
+    /// `Config::sse_decoded_event`
+    decoded_event: bool,
+    /// Updates carry a `seq`, from `Config::sse_seq_numbers`
+    seq_numbers: bool,
+}
+
+impl DeltaSources {
+    /// The shared ticks for a new stream. The current copy counts as unseen,
+    /// so the stream's first update, the full snapshot, goes out at once.
+    fn ticks(&self) -> watch::Receiver<CacheSnapshot> {
+        let mut ticks = self.ticks.clone();
+        ticks.mark_changed();
+        ticks
+    }
+}
+
+/// One client's view of the cache. Each `next` returns what changed since the
+/// previous one, starting with a full snapshot, or `None` if nothing did.
+struct DeltaTracker {
+    sources: DeltaSources,
+    last_snapshot: CacheSnapshot,
+    history_revision: u64,
+    seq: u64,
+}
+
+impl DeltaTracker {
+    fn new(sources: DeltaSources) -> Self {
+        Self {
+            sources,
+            last_snapshot: CacheSnapshot::default(),
+            history_revision: 0,
+            seq: 0,
+        }
+    }
+
+    /// Compares `current_cache`, the latest tick's copy, with the previous one
+    async fn next(&mut self, current_cache: CacheSnapshot) -> Option<DeltaUpdate> {
+        let tombstones = self.sources.tombstones.read().await;
+        let (updated, added, removed, refreshed) = {
+            let mut updated: Vec<TopicData> = Vec::new();
+            let mut added: Vec<TopicData> = Vec::new();
+            let mut removed: Vec<String> = Vec::new();
+            let mut refreshed: Vec<(String, u64)> = Vec::new();
+
+            let current_keys: HashSet<_> = current_cache.keys().collect();
+            let last_keys: HashSet<_> = self.last_snapshot.keys().collect();
+
+            // A steady topic's new sample only moves its receive time and
+            // counters, so it sends the topic's age instead of the whole row,
+            // which goes out for a change the page shows.
+            for (key, value) in current_cache.iter() {
+                match self.last_snapshot.get(key) {
+                    // Removed and back between two ticks, so new again
+                    Some(_)
+                        if self.seq > 0
+                            && tombstones.removed_since(key, self.last_snapshot.removals) =>
+                    {
+                        added.push(value.clone());
+                    }
+                    Some(old) => {
+                        if value.differs_from(old) {
+                            updated.push(value.clone());
+                        } else if old.received_timestamp != value.received_timestamp {
+                            refreshed.push((key.clone(), value.received_timestamp));
+                        }
+                    }
+                    // The first update is a snapshot, where nothing is new
+                    None if self.seq == 0 => updated.push(value.clone()),
+                    None => added.push(value.clone()),
+                }
+            }
+
+            for key in last_keys.difference(&current_keys) {
+                removed.push(key.to_string());
+            }
+
+            self.last_snapshot = current_cache.clone();
+
+            (updated, added, removed, refreshed)
+        };
+        // Histories change on their own, slower cadence and travel apart from
+        // the rows, so a new sparkline point never resends a whole topic
+        let hz_history: BTreeMap<String, Vec<f32>> = {
+            let history = self.sources.metric_history.read().await;
+            if self.sources.send_sparklines && history.revision > self.history_revision {
+                self.history_revision = history.revision;
+                history
+                    .topics
+                    .keys()
+                    .map(|key| (key.clone(), history.sparkline(key)))
+                    .collect()
+            } else {
+                BTreeMap::new()
+            }
+        };
+        // The throughput chart and the ages of quiet topics can wait for the
+        // next change; the page extrapolates ages on its own. No sequence
+        // number is used up.
+        if self.seq > 0
+            && updated.is_empty()
+            && added.is_empty()
+            && removed.is_empty()
+            && refreshed.is_empty()
+            && hz_history.is_empty()
+        {
+            return None;
+        }
+
+        let reappeared: Vec<String> = added
+            .iter()
+            .filter(|topic| tombstones.get(&topic.key_expr).is_some())
+            .map(|topic| topic.key_expr.clone())
+            .collect();
+        drop(tombstones);
+
+        self.seq += 1;
+        let server_time = get_timestamp();
+        let ages = if self.seq % AGE_REFRESH_TICKS == 1 {
+            self.last_snapshot
+                .values()
+                .map(|topic| {
+                    (
+                        topic.key_expr.clone(),
+                        server_time.saturating_sub(topic.received_timestamp),
+                    )
+                })
+                .collect()
+        } else {
+            refreshed
+                .into_iter()
+                .map(|(key, received)| (key, server_time.saturating_sub(received)))
+                .collect()
+        };
+        let throughput = self
+            .sources
+            .throughput
+            .read()
+            .await
+            .latest()
+            .map(|point| LatestThroughput::new(point, self.sources.display_units));
+        Some(DeltaUpdate {
+            seq: self.sources.seq_numbers.then_some(self.seq),
+            updated,
+            added,
+            reappeared,
+            removed,
+            server_time,
+            ages,
+            hz_history,
+            throughput,
+        })
+    }
+}
+
+/// Number of open SSE streams, reported at `/api/status`
+#[derive(Clone, Default)]
+struct SseClients(Arc<AtomicU64>);
+
+impl SseClients {
+    fn connect(&self) -> SseClient {
+        let open = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!("SSE client connected, {} open", open);
+        SseClient {
+            clients: self.clone(),
+            connected_at: time::Instant::now(),
+        }
+    }
+
+    fn open(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Held in an SSE stream's state. hyper notices a closed connection while the
+/// stream waits for the next tick (or at the latest when a keep-alive write
+/// fails) and drops the stream, which drops this with the stream's snapshot.
+struct SseClient {
+    clients: SseClients,
+    connected_at: time::Instant,
+}
+
+impl Drop for SseClient {
+    fn drop(&mut self) {
+        let open = self.clients.0.fetch_sub(1, Ordering::Relaxed) - 1;
+        debug!(
+            "SSE client disconnected after {} s, {} open",
+            self.connected_at.elapsed().as_secs(),
+            open
+        );
+    }
+}
+
+/// One client's SSE stream between events
+struct SseState {
+    tracker: DeltaTracker,
+    ticks: watch::Receiver<CacheSnapshot>,
+    shutdown: ShutdownSignal,
+    last_sent: time::Instant,
+    /// The shutdown event went out; the stream ends after it
+    stopped: bool,
+    /// The `decoded` event following the update just sent
+    pending: Option<sse::Event>,
+    /// Counted while the stream is open
+    _client: SseClient,
+}
+
+/// Moves the decoded content of every topic in `delta` into a map for the
+/// `decoded` event. A topic without decoded content maps to `null`.
+fn take_decoded_content(delta: &mut DeltaUpdate) -> BTreeMap<String, Option<String>> {
+    delta
+        .updated
+        .iter_mut()
+        .chain(delta.added.iter_mut())
+        .map(|topic| (topic.key_expr.clone(), topic.decoded_content.take()))
+        .collect()
+}
+
+async fn sse_handler(
+    sources: DeltaSources,
+    clients: SseClients,
+    _has_decoder: bool,
+    shutdown: ShutdownSignal,
+    last_event_id: Option<String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Per-client state is not kept across connections, so a resuming client
+    // always starts a new sequence with a full snapshot and resyncs from it.
+    if let Some(id) = last_event_id {
+        debug!(
+            "SSE client reconnected after event {}, sending full snapshot",
+            id
+        );
+    }
+
+    let stream = sse_events(sources, &clients, shutdown);
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
+}
+
+/// One client's SSE events: a full snapshot, then deltas and heartbeats, and
+/// the shutdown event last. The client counts as open until the stream drops.
+fn sse_events(
+    sources: DeltaSources,
+    clients: &SseClients,
+    shutdown: ShutdownSignal,
+) -> impl futures::Stream<Item = Result<sse::Event, warp::Error>> + use<> {
+    // The stream waits on its own subscription to `run_delta_ticker`'s shared
+    // snapshots, kept in its state so each update waits for the next tick
+    let ticks = sources.ticks();
+    let heartbeat = Duration::from_millis(sources.heartbeat_ms);
+    let decoded_event = sources.decoded_event;
+    let state = SseState {
+        tracker: DeltaTracker::new(sources),
+        ticks,
+        shutdown,
+        last_sent: time::Instant::now(),
+        stopped: false,
+        pending: None,
+        _client: clients.connect(),
+    };
+    futures::stream::unfold(state, move |mut state| async move {
+        // The shutdown event is the last one; the stream ends after it
+        if state.stopped {
+            return None;
+        }
+        if let Some(event) = state.pending.take() {
+            return Some((Ok(event), state));
+        }
+        let shutdown_event = || {
+            sse::Event::default()
+                .event("shutdown")
+                .data(r#"{"reason":"monitor stopping"}"#)
+        };
+        if *state.shutdown.borrow() {
+            state.stopped = true;
+            return Some((Ok(shutdown_event()), state));
+        }
+
+        // Idle ticks send nothing, so an unchanged cache costs idle clients
+        // one small heartbeat per `heartbeat_ms`
+        let event = loop {
+            tokio::select! {
+                changed = state.ticks.changed() => {
+                    if changed.is_err() {
+                        return None;
+                    }
+                }
+                _ = state.shutdown.changed() => {
+                    state.stopped = true;
+                    return Some((Ok(shutdown_event()), state));
+                }
+            }
+
+            let current = state.ticks.borrow_and_update().clone();
+            if let Some(mut delta) = state.tracker.next(current).await {
+                // Taken from the same delta, so the `decoded` event that follows
+                // matches the update it belongs to
+                let decoded = decoded_event.then(|| take_decoded_content(&mut delta));
+                // JSON escapes the newlines of multi-line decoded content,
+                // so an update is always a single `data:` line
+                let mut event = match sse::Event::default().event("message").json_data(&delta) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        error!("Failed to serialise SSE update: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(seq) = delta.seq {
+                    event = event.id(seq.to_string());
+                }
+                state.pending = decoded
+                    .filter(|decoded| !decoded.is_empty())
+                    .and_then(|decoded| {
+                        sse::Event::default()
+                            .event("decoded")
+                            .json_data(&decoded)
+                            .map_err(|e| error!("Failed to serialise decoded content: {}", e))
+                            .ok()
+                    });
+                break event;
+            }
+            if state.last_sent.elapsed() >= heartbeat {
+                break sse::Event::default()
+                    .event("heartbeat")
+                    .data(format!(r#"{{"server_time":{}}}"#, get_timestamp()));
+            }
+        };
+        state.last_sent = time::Instant::now();
+
+        Some((Ok::<_, warp::Error>(event), state))
+    })
+}
+
+async fn alerts_handler(alerts: AlertStore) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct AlertsReport<'a> {
+        rules: &'a [AlertRule],
+        composite_rules: &'a [CompositeRule],
+        alerts: Vec<alerts::AlertView>,
+    }
+
+    let engine = alerts.read().await;
+    Ok(warp::reply::json(&AlertsReport {
+        rules: engine.rules(),
+        composite_rules: engine.composites(),
+        alerts: engine.active(get_timestamp()),
+    }))
+}
+
+async fn alert_history_handler(
+    alerts: AlertStore,
+    query: HistoryQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&alerts.read().await.history(&query)))
+}
+
+fn json_error(status: StatusCode, message: &str) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "error": message })),
+        status,
+    )
+}
+
+async fn alert_ack_handler(
+    id: u64,
+    alerts: AlertStore,
+    request: AckRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if alerts
+        .write()
+        .await
+        .acknowledge(id, request.by, get_timestamp())
+    {
+        info!("Alert {} acknowledged", id);
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "acknowledged": id })),
+            StatusCode::OK,
+        ))
+    } else {
+        Ok(json_error(
+            StatusCode::NOT_FOUND,
+            "no active alert with this id",
+        ))
+    }
+}
+
+async fn silences_handler(alerts: AlertStore) -> Result<impl warp::Reply, warp::Rejection> {
+    let engine = alerts.read().await;
+    Ok(warp::reply::json(&engine.silences().list(get_timestamp())))
+}
+
+async fn create_silence_handler(
+    alerts: AlertStore,
+    request: SilenceRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match alerts
+        .write()
+        .await
+        .silences_mut()
+        .create(request, get_timestamp())
+    {
+        Ok(silence) => {
+            info!(
+                "Silence {} created until {} (key_expr {:?}, rule {:?})",
+                silence.id, silence.expires_at, silence.key_expr, silence.rule
+            );
+            Ok(warp::reply::with_status(
+                warp::reply::json(&silence),
+                StatusCode::CREATED,
+            ))
+        }
+        Err(e) => Ok(json_error(StatusCode::BAD_REQUEST, &e)),
+    }
+}
+
+type ViewStore = Arc<RwLock<Views>>;
+type PinStore = Arc<RwLock<Pins>>;
+
+/// Replaced in the pre-generated page with the view opened by `/?view=<name>`
+const INITIAL_VIEW_MARKER: &str = "/*INITIAL_VIEW*/null";
+
+async fn index_handler(
+    query: HashMap<String, String>,
+    page: Arc<String>,
+    views: ViewStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(name) = query.get("view") else {
+        return Ok(warp::reply::html(page.as_str().to_string()));
+    };
+    // A bookmarked view that was deleted falls back to the default with a notice
+    let initial = match views.read().await.get(name) {
+        Some(view) => serde_json::json!({ "view": view }),
+        None => serde_json::json!({ "missing": name }),
+    };
+    // Inside a <script>, so nothing in the JSON may close the tag
+    let initial = initial.to_string().replace('<', "\\u003c");
+    Ok(warp::reply::html(
+        page.replace(INITIAL_VIEW_MARKER, &initial),
+    ))
+}
+
+async fn views_handler(views: ViewStore) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&views.read().await.list()))
+}
+
+async fn put_view_handler(
+    views: ViewStore,
+    columns: Arc<Vec<Column>>,
+    view: View,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match views.write().await.put(view, &columns) {
+        Ok(view) => {
+            info!("View '{}' saved", view.name);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&view),
+                StatusCode::CREATED,
+            ))
+        }
+        Err(e) => Ok(json_error(StatusCode::BAD_REQUEST, &e)),
+    }
+}
+
+async fn delete_view_handler(
+    name: String,
+    views: ViewStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if views.write().await.delete(&name) {
+        info!("View '{}' deleted", name);
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "deleted": name })),
+            StatusCode::OK,
+        ))
+    } else {
+        Ok(json_error(StatusCode::NOT_FOUND, "no view with this name"))
+    }
+}
+
+async fn query_handler(
+    session: ZenohSession,
+    config: Arc<Config>,
+    request: query::QueryRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Clone the handle so a slow query doesn't hold the lock
+    let Some(session) = session.read().await.clone() else {
+        return Ok(json_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "not connected to Zenoh",
+        ));
+    };
+    match query::run(&session, request, config.decoder_chain()).await {
+        Ok(result) => Ok(warp::reply::with_status(
+            warp::reply::json(&result),
+            StatusCode::OK,
+        )),
+        Err(e) => Ok(json_error(StatusCode::BAD_REQUEST, &e)),
+    }
+}
+
+/// Body of `POST /api/subscribe`
+#[derive(Debug, Deserialize)]
+struct SubscribeRequest {
+    key_expr: String,
+}
+
+async fn subscription_handler(
+    subscription: Subscription,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(
+        &serde_json::json!({ "key_expr": *subscription.borrow() }),
+    ))
+}
+
+/// Moves the subscriber to a new key expression; topics outside it are dropped
+async fn subscribe_handler(
+    subscription: Subscription,
+    request: SubscribeRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Err(e) = zenoh::key_expr::keyexpr::new(&request.key_expr) {
+        return Ok(json_error(
+            StatusCode::BAD_REQUEST,
+            &format!("invalid key expression '{}': {}", request.key_expr, e),
+        ));
+    }
+    let previous = subscription.send_replace(request.key_expr.clone());
+    info!(
+        "Subscription changed from '{}' to '{}'",
+        previous, request.key_expr
+    );
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "key_expr": request.key_expr,
+            "previous": previous,
+        })),
+        StatusCode::OK,
+    ))
+}
+
+async fn delete_silence_handler(
+    id: u64,
+    alerts: AlertStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if alerts.write().await.silences_mut().delete(id) {
+        info!("Silence {} deleted", id);
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "deleted": id })),
+            StatusCode::OK,
+        ))
+    } else {
+        Ok(json_error(StatusCode::NOT_FOUND, "no silence with this id"))
+    }
+}
+
+async fn stats_handler(
+    cache: TopicCache,
+    config: Arc<Config>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct StatsReport {
+        total_topics: usize,
+        prefix_depth: usize,
+        by_prefix: BTreeMap<String, usize>,
+    }
+
+    let topics = cache.read().await;
+    Ok(warp::reply::json(&StatsReport {
+        total_topics: topics.len(),
+        prefix_depth: config.prefix_group_depth,
+        by_prefix: prefix_counts(topics.keys(), config.prefix_group_depth),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicsQuery {
+    /// Case-insensitive substring of the key expression
+    key: Option<String>,
+    /// Case-insensitive substring of the decoded content; scans every payload, so slower
+    content: Option<String>,
+    /// Order of the results; `DEFAULT_SORT` when absent, so the API matches the page
+    sort: Option<SortField>,
+    /// Defaults to the field's natural direction: keys A-Z, numbers largest first
+    direction: Option<SortDirection>,
+}
+
+async fn topics_handler(
+    cache: TopicCache,
+    default_sort: Sort,
+    query: TopicsQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let sort = match query.sort {
+        Some(field) => Sort {
+            field,
+            direction: query.direction.unwrap_or(field.natural_direction()),
+        },
+        None => Sort {
+            direction: query.direction.unwrap_or(default_sort.direction),
+            ..default_sort
+        },
+    };
+    let key = query.key.map(|key| key.to_lowercase());
+    // Decoded content is stored HTML-escaped, so the query is escaped the same way
+    let content = query
+        .content
+        .map(|content| html_escape_string(&content.to_lowercase()));
+    #[derive(Serialize)]
+    struct ListedTopic<'a> {
+        #[serde(flatten)]
+        topic: &'a TopicData,
+        /// Time since the last sample on the server's clock; `null` if never received
+        age_ms: Option<u64>,
+    }
+
+    let now = get_timestamp();
+    let topics = cache.read().await;
+    let mut matched: Vec<&TopicData> = topics
+        .values()
+        .map(|topic| &topic.data)
+        .filter(|topic| {
+            key.as_deref()
+                .is_none_or(|key| topic.key_expr.to_lowercase().contains(key))
+        })
+        .filter(|topic| {
+            content.as_deref().is_none_or(|content| {
+                topic
+                    .decoded_content
+                    .as_deref()
+                    .is_some_and(|decoded| decoded.to_lowercase().contains(content))
+            })
+        })
+        .collect();
+    matched.sort_by(|a, b| sort.compare(a, b));
+    let listed: Vec<ListedTopic> = matched
+        .into_iter()
+        .map(|topic| ListedTopic {
+            topic,
+            age_ms: (!topic.is_placeholder()).then(|| now.saturating_sub(topic.received_timestamp)),
+        })
+        .collect();
+    // Federating monitors use the server clock to correct for skew
+    Ok(warp::reply::with_header(
+        warp::reply::json(&listed),
+        federation::SERVER_TIME_HEADER,
+        now.to_string(),
+    ))
+}
+
+/// `GET /api/topics/{key}/detail`, the key percent-encoded as one segment
+fn topic_detail_route(
+    cache: TopicCache,
+    metric_history: MetricHistory,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("api" / "topics" / String / "detail")
+        .and(warp::get())
+        .and(warp::any().map(move || cache.clone()))
+        .and(warp::any().map(move || metric_history.clone()))
+        .and_then(topic_detail_handler)
+}
+
+/// Everything the expanded row shows; fetched on demand rather than sent with every delta
+async fn topic_detail_handler(
+    key: String,
+    cache: TopicCache,
+    metric_history: MetricHistory,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct TopicDetail {
+        #[serde(flatten)]
+        topic: TopicData,
+        interval_stats: IntervalStats,
+        recent_gaps: VecDeque<Gap>,
+        hz_history: Vec<f32>,
+        age_ms: u64,
+    }
+
+    // Keys contain slashes, so clients send them as one percent-encoded segment
+    let key = match percent_encoding::percent_decode_str(&key).decode_utf8() {
+        Ok(key) => key.into_owned(),
+        Err(_) => {
+            return Ok(json_error(
+                StatusCode::BAD_REQUEST,
+                "topic key is not valid UTF-8",
+            ));
+        }
+    };
+    // Clone under a short read lock; decodes in flight never block this
+    let Some(topic) = cache.read().await.get(&key).map(|topic| topic.data.clone()) else {
+        return Ok(json_error(
+            StatusCode::NOT_FOUND,
+            &format!("no topic '{}'", key),
+        ));
+    };
+    let hz_history = metric_history.read().await.sparkline(&key);
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&TopicDetail {
+            interval_stats: topic.interval_stats,
+            recent_gaps: topic.recent_gaps.clone(),
+            hz_history,
+            age_ms: get_timestamp().saturating_sub(topic.received_timestamp),
+            topic,
+        }),
+        StatusCode::OK,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum GrafanaMetric {
+    #[default]
+    Hz,
+    /// Latest payload size in bytes
+    Size,
+    /// Bytes per second, from the rate and the rolling average size
+    Bandwidth,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum GrafanaFormat {
+    /// One row per topic, for the Infinity datasource's table/JSON parser
+    #[default]
+    Table,
+    /// SimpleJSON-style `{target, datapoints: [[value, time]]}` per topic
+    Series,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrafanaQuery {
+    #[serde(default)]
+    metric: GrafanaMetric,
+    #[serde(default)]
+    format: GrafanaFormat,
+}
+
+async fn grafana_handler(
+    cache: TopicCache,
+    query: GrafanaQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let now = get_timestamp();
+    let topics = cache.read().await;
+    let mut rows: Vec<(&str, f64)> = topics
+        .values()
+        .map(|topic| {
+            let hz = alerts::effective_hz(topic, now);
+            let value = match query.metric {
+                GrafanaMetric::Hz => hz,
+                GrafanaMetric::Size => topic.last_data_size_bytes as f64,
+                GrafanaMetric::Bandwidth => hz * topic.avg_data_size_bytes,
+            };
+            (topic.key_expr.as_str(), value)
+        })
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(b.0));
+
+    let body = match query.format {
+        GrafanaFormat::Table => serde_json::Value::from_iter(rows.iter().map(|(topic, value)| {
+            serde_json::json!({ "topic": topic, "value": value, "timestamp": now })
+        })),
+        GrafanaFormat::Series => serde_json::Value::from_iter(rows.iter().map(|(topic, value)| {
+            serde_json::json!({ "target": topic, "datapoints": [[value, now]] })
+        })),
+    };
+    Ok(warp::reply::json(&body))
+}
+
+/// Rendered SVGs by `key|metric|window`, with when they were rendered
+type ChartCache = Arc<RwLock<HashMap<String, (u64, String)>>>;
+
+#[derive(Debug, Deserialize)]
+struct ChartQuery {
+    #[serde(default)]
+    metric: GrafanaMetric,
+    /// "30s", "10m", "1h" or milliseconds; the whole history when absent
+    window: Option<String>,
+}
+
+/// Parses a chart window, capped at what the history keeps
+fn parse_window(window: &str) -> Result<u64, String> {
+    let (number, scale) = match window.char_indices().last() {
+        Some((i, 's')) => (&window[..i], 1000),
+        Some((i, 'm')) => (&window[..i], 60_000),
+        Some((i, 'h')) => (&window[..i], 3_600_000),
+        _ => (window, 1),
+    };
+    match number.parse::<u64>() {
+        Ok(n) if n > 0 => Ok((n * scale).min(METRIC_HISTORY_MS)),
+        _ => Err(format!(
+            "invalid window '{}', expected e.g. 30s, 10m or 1h",
+            window
+        )),
+    }
+}
+
+/// A topic's recent rate, size or bandwidth as an SVG line chart, for
+/// embedding in wikis and dashboards
+async fn chart_handler(
+    segment: String,
+    query: ChartQuery,
+    metric_history: MetricHistory,
+    chart_cache: ChartCache,
+    units: format::Units,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let Some(key) = segment.strip_suffix(".svg") else {
+        return Ok(
+            json_error(StatusCode::NOT_FOUND, "charts are served as {key}.svg").into_response(),
+        );
+    };
+    let key = match percent_encoding::percent_decode_str(key).decode_utf8() {
+        Ok(key) => key.into_owned(),
+        Err(_) => {
+            return Ok(
+                json_error(StatusCode::BAD_REQUEST, "topic key is not valid UTF-8").into_response(),
+            );
+        }
+    };
+    let window_ms = match query.window.as_deref().map(parse_window) {
+        None => METRIC_HISTORY_MS,
+        Some(Ok(window_ms)) => window_ms,
+        Some(Err(e)) => return Ok(json_error(StatusCode::BAD_REQUEST, &e).into_response()),
+    };
+
+    let now = get_timestamp();
+    let cache_key = format!("{}|{:?}|{}", key, query.metric, window_ms);
+    let cached = chart_cache
+        .read()
+        .await
+        .get(&cache_key)
+        .filter(|(rendered_at, _)| now.saturating_sub(*rendered_at) < CHART_CACHE_MS)
+        .map(|(_, svg)| svg.clone());
+    let svg = match cached {
+        Some(svg) => svg,
+        None => {
+            let (title, unit) = match query.metric {
+                GrafanaMetric::Hz => (format!("{} rate", key), chart::Unit::Hz),
+                GrafanaMetric::Size => (format!("{} size", key), chart::Unit::Bytes(units)),
+                GrafanaMetric::Bandwidth => (
+                    format!("{} bandwidth", key),
+                    chart::Unit::BytesPerSecond(units),
+                ),
+            };
+            let points: Vec<(u64, f64)> = metric_history
+                .read()
+                .await
+                .topics
+                .get(&key)
+                .map(|points| {
+                    points
+                        .iter()
+                        .map(|point| {
+                            let value = match query.metric {
+                                GrafanaMetric::Hz => point.hz,
+                                GrafanaMetric::Size => point.size,
+                                GrafanaMetric::Bandwidth => point.bandwidth,
+                            };
+                            (point.at, value as f64)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let svg = chart::render(&title, unit, &points, window_ms, now);
+            let mut charts = chart_cache.write().await;
+            charts.retain(|_, (rendered_at, _)| now.saturating_sub(*rendered_at) < CHART_CACHE_MS);
+            charts.insert(cache_key, (now, svg.clone()));
+            svg
+        }
+    };
+
+    let reply = warp::reply::with_header(svg, "content-type", "image/svg+xml");
+    let reply = warp::reply::with_header(
+        reply,
+        "cache-control",
+        format!("max-age={}", CHART_CACHE_MS / 1000),
+    );
+    Ok(reply.into_response())
+}
+
+async fn notifications_handler(
+    notifiers: NotifierHub,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&notifiers.report().await))
+}
+
+async fn config_handler(
+    config: Arc<Config>,
+    pins: PinStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct ConfigReport<'a> {
+        #[serde(flatten)]
+        config: &'a Config,
+        /// Pinned topic keys, changed at runtime through `/api/pins`
+        pins: Vec<&'a String>,
+    }
+
+    let pins = pins.read().await;
+    Ok(warp::reply::json(&ConfigReport {
+        config: &config,
+        pins: pins.list(),
+    }))
+}
+
+async fn pins_handler(pins: PinStore) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&pins.read().await.list()))
+}
+
+async fn pin_handler(
+    pins: PinStore,
+    cache: TopicCache,
+    request: PinRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match pins.write().await.add(&request.key_expr) {
+        Ok(added) => {
+            if added {
+                info!("Pinned '{}'", request.key_expr);
+            }
+            pins::apply(&cache, &request.key_expr, true).await;
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({ "pinned": request.key_expr })),
+                if added {
+                    StatusCode::CREATED
+                } else {
+                    StatusCode::OK
+                },
+            ))
+        }
+        Err(e) => Ok(json_error(StatusCode::BAD_REQUEST, &e)),
+    }
+}
+
+async fn unpin_handler(
+    pins: PinStore,
+    cache: TopicCache,
+    request: PinRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if pins.write().await.remove(&request.key_expr) {
+        info!("Unpinned '{}'", request.key_expr);
+        pins::apply(&cache, &request.key_expr, false).await;
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "unpinned": request.key_expr })),
+            StatusCode::OK,
+        ))
+    } else {
+        Ok(json_error(StatusCode::NOT_FOUND, "this key is not pinned"))
+    }
+}
+
+async fn status_handler(
+    status: SubscriberState,
+    decode_limiter: DecodeLimiter,
+    sse_clients: SseClients,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct StatusReport {
+        subscriber: SubscriberStatus,
+        subscriber_healthy: bool,
+        decode_concurrency: usize,
+        decodes_skipped: u64,
+        /// Decodes saved by the per-topic decode cache
+        decode_cache_hits: u64,
+        /// The memory guard is shedding load
+        shedding_load: bool,
+        sse_clients: u64,
+    }
+
+    let subscriber = *status.read().await;
+    Ok(warp::reply::json(&StatusReport {
+        subscriber,
+        subscriber_healthy: subscriber.is_healthy(),
+        decode_concurrency: decode_limiter.concurrency,
+        decodes_skipped: decode_limiter.skipped.load(Ordering::Relaxed),
+        decode_cache_hits: decode_limiter.cache_hits.load(Ordering::Relaxed),
+        shedding_load: decode_limiter.suspended.load(Ordering::Relaxed),
+        sse_clients: sse_clients.open(),
+    }))
+}
+
+/// The monitor's own health, as opposed to the topics'
+async fn diagnostics_handler(
+    processing_latency: Option<ProcessingLatencies>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    #[derive(Serialize)]
+    struct Diagnostics {
+        /// `Config::measure_processing_latency`
+        processing_latency_enabled: bool,
+        /// Per-sample time in the subscribe loop; `null` when off or before
+        /// the first sample
+        processing_latency: Option<LatencyReport>,
+    }
+
+    let report = match &processing_latency {
+        Some(latency) => latency.read().await.report(),
+        None => None,
+    };
+    Ok(warp::reply::json(&Diagnostics {
+        processing_latency_enabled: processing_latency.is_some(),
+        processing_latency: report,
+    }))
+}
+
+/// Dumps the cache as SIGUSR1 does, for platforms without the signal
+async fn dump_handler(
+    cache: TopicCache,
+    config: Arc<Config>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match dump::write(&cache, config.cache_dump_dir, config.instance_name).await {
+        Ok(path) => {
+            info!("Wrote cache dump to {}", path.display());
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({ "path": path })),
+                StatusCode::CREATED,
+            ))
+        }
+        Err(e) => {
+            warn!("Cache dump failed: {}", e);
+            Ok(json_error(StatusCode::INTERNAL_SERVER_ERROR, &e))
+        }
+    }
+}
+
+/// 200 while samples are being received, 503 while the subscriber is down or
+/// recovering, for load balancers and orchestrators
+async fn readyz_handler(status: SubscriberState) -> Result<impl warp::Reply, warp::Rejection> {
+    let subscriber = *status.read().await;
+    let code = if subscriber.is_healthy() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "ready": subscriber.is_healthy(),
+            "subscriber": subscriber,
+        })),
+        code,
+    ))
+}
+
+async fn health_summary_handler(
+    config: Arc<Config>,
+    cache: TopicCache,
+    alerts: AlertStore,
+    status: SubscriberState,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let Some(thresholds) = config.health_summary else {
+        return Ok(
+            json_error(StatusCode::NOT_FOUND, "the health summary is turned off").into_response(),
+        );
+    };
+    let now = get_timestamp();
+    let subscriber_healthy = status.read().await.is_healthy();
+    let alerts = alerts.read().await.active(now);
+    let topics = cache.read().await;
+    let summary = health::summarize(&thresholds, &topics, &alerts, subscriber_healthy, now);
+    Ok(warp::reply::json(&summary).into_response())
+}
+
+async fn throughput_handler(
+    throughput: Throughput,
+    query: ThroughputQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let points = throughput
+        .read()
+        .await
+        .window(query.window_ms(), get_timestamp());
+    Ok(warp::reply::json(&points))
+}
+
+async fn report_handler(
+    query: ReportQuery,
+    config: Arc<Config>,
+    extra_css: Arc<String>,
+    cache: TopicCache,
+    alerts: AlertStore,
+    throughput: Throughput,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let columns = match query.columns(config.columns()) {
+        Ok(columns) => columns,
+        Err(e) => return Ok(json_error(StatusCode::BAD_REQUEST, &e).into_response()),
+    };
+    let sort = query.sort(config.default_sort);
+    let now = get_timestamp();
+    let alerts = alerts.read().await.active(now);
+    let latest = throughput.read().await.latest();
+    let topics = cache.read().await;
+    // Pinned first, like the page
+    let mut matched: Vec<&TopicData> = topics
+        .values()
+        .map(|topic| &topic.data)
+        .filter(|topic| query.matches(topic))
+        .collect();
+    matched.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| sort.compare(a, b)));
+    let html = report::render(
+        &config, &extra_css, &columns, &matched, &alerts, latest, sort, now,
+    );
+    Ok(warp::reply::html(html).into_response())
+}
+
+async fn federation_handler(
+    federation: FederationState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&*federation.read().await))
+}
+
+/// Identifies this monitor, for tools that talk to several instances
+async fn about_handler(config: Arc<Config>) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&serde_json::json!({
+        "instance": config.instance_name,
+        "title": config.branding.page_title,
+        "version": env!("CARGO_PKG_VERSION"),
+    })))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn start_web_server(
+    config: Arc<Config>,
+    cache: TopicCache,
+    alerts: AlertStore,
+    notifiers: NotifierHub,
+    session: ZenohSession,
+    status: SubscriberState,
+    decode_limiter: DecodeLimiter,
+    processing_latency: Option<ProcessingLatencies>,
+    federation: FederationState,
+    subscription: Subscription,
+    tombstones: Tombstones,
+    pins: PinStore,
+    shutdown: ShutdownSignal,
+) {
+    let port = config.port;
+    let reload_period_ms = config.reload_period_ms;
+    let has_decoder = config.decoder_enabled;
+    let columns = config.columns();
+    let extra_css = match config.extra_css_path {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_else(|e| {
+            warn!("Failed to read extra CSS from {}: {}", path, e);
+            String::new()
+        }),
+        None => String::new(),
+    };
+    let page = Arc::new(generate_html(&config, &columns, &extra_css));
+    let theme = config.theme;
+    let load_metric = config.load_bar_metric;
+    let default_sort = config.default_sort;
+    let base_path = config.base_path.unwrap_or("");
+
+    let views: ViewStore = Arc::new(RwLock::new(Views::load(config.views_file)));
+    // Always sampled, since charts use it; `hz_history` only controls the SSE sparklines
+    let metric_history = MetricHistory::default();
+    tokio::spawn(run_metric_history_sampler(
+        cache.clone(),
+        metric_history.clone(),
+    ));
+    let throughput = Throughput::default();
+    tokio::spawn(throughput::run_throughput_sampler(
+        cache.clone(),
+        throughput.clone(),
+    ));
+    let display_units = config.display_units;
+    let (tick_sender, ticks) = watch::channel(CacheSnapshot::default());
+    tokio::spawn(run_delta_ticker(
+        cache.clone(),
+        tombstones.clone(),
+        tick_sender,
+        config.reload_period_ms,
+    ));
+    let delta_sources = DeltaSources {
+        ticks,
+        metric_history: metric_history.clone(),
+        send_sparklines: config.hz_history,
+        throughput: throughput.clone(),
+        display_units,
+        tombstones,
+        heartbeat_ms: config.sse_heartbeat_ms,
+        decoded_event: config.sse_decoded_event,
+        seq_numbers: config.sse_seq_numbers,
+    };
+    #[cfg(feature = "grpc")]
+    let grpc_server = config
+        .grpc_port
+        .map(|port| tokio::spawn(grpc::serve(delta_sources.clone(), port, shutdown.clone())));
+    #[cfg(not(feature = "grpc"))]
+    let grpc_server: Option<tokio::task::JoinHandle<()>> = None;
+    let throughput_filter = warp::any().map(move || throughput.clone());
+    let chart_cache = ChartCache::default();
+    let (detail_cache, detail_history) = (cache.clone(), metric_history.clone());
+    let metric_history_filter = warp::any().map(move || metric_history.clone());
+    let mut stopping = shutdown.clone();
+    let shutdown_filter = warp::any().map(move || shutdown.clone());
+    let config_filter = warp::any().map(move || config.clone());
+    let cache_filter = warp::any().map(move || cache.clone());
+    let alerts_filter = warp::any().map(move || alerts.clone());
+    let notifiers_filter = warp::any().map(move || notifiers.clone());
+    let session_filter = warp::any().map(move || session.clone());
+    let status_filter = warp::any().map(move || status.clone());
+    let decode_limiter_filter = warp::any().map(move || decode_limiter.clone());
+    let processing_latency_filter = warp::any().map(move || processing_latency.clone());
+    let sse_clients = SseClients::default();
+    let sse_clients_filter = warp::any().map(move || sse_clients.clone());
+    let decoder_filter = warp::any().map(move || has_decoder);
+
+    let views_filter = warp::any().map(move || views.clone());
+    let index = warp::path::end()
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::any().map(move || page.clone()))
+        .and(views_filter.clone())
+        .and_then(index_handler)
+        .boxed();
+
+    let view_columns = Arc::new(columns.clone());
+    let views_route = warp::path!("api" / "views")
+        .and(warp::get())
+        .and(views_filter.clone())
+        .and_then(views_handler)
+        .boxed();
+
+    let put_view_route = warp::path!("api" / "views")
+        .and(warp::post())
+        .and(views_filter.clone())
+        .and(warp::any().map(move || view_columns.clone()))
+        .and(warp::body::content_length_limit(16 * 1024))
+        .and(warp::body::json::<View>())
+        .and_then(put_view_handler)
+        .boxed();
+
+    let delete_view_route = warp::path!("api" / "views" / String)
+        .and(warp::delete())
+        .and(views_filter)
+        .and_then(delete_view_handler)
+        .boxed();
+
+    let ui_config_route = warp::path!("api" / "ui-config")
+        .and(warp::get())
+        .map(move || {
+            let columns: Vec<_> = columns
+                .iter()
+                .map(|column| serde_json::json!({ "key": column, "title": column.title() }))
+                .collect();
+            warp::reply::json(&serde_json::json!({
+                "columns": columns,
+                "theme": theme,
+                "load_bar_metric": load_metric,
+                "reload_period_ms": reload_period_ms,
+            }))
+        })
+        .boxed();
+
+    let sse_route = warp::path("sse")
+        .and(warp::any().map(move || delta_sources.clone()))
+        .and(sse_clients_filter.clone())
+        .and(decoder_filter)
+        .and(shutdown_filter)
+        .and(warp::header::optional::<String>("last-event-id"))
+        .and_then(sse_handler)
+        .boxed();
+
+    let alerts_route = warp::path!("api" / "alerts")
+        .and(warp::get())
+        .and(alerts_filter.clone())
+        .and_then(alerts_handler)
+        .boxed();
+
+    let alert_history_route = warp::path!("api" / "alerts" / "history")
+        .and(warp::get())
+        .and(alerts_filter.clone())
+        .and(warp::query::<HistoryQuery>())
+        .and_then(alert_history_handler)
+        .boxed();
+
+    // The acknowledger's name is optional, so an empty body is accepted
+    let alert_ack_route = warp::path!("api" / "alerts" / u64 / "ack")
+        .and(warp::post())
+        .and(alerts_filter.clone())
+        .and(
+            warp::body::content_length_limit(16 * 1024)
+                .and(warp::body::json::<AckRequest>())
+                .or(warp::any().map(AckRequest::default))
+                .unify(),
+        )
+        .and_then(alert_ack_handler)
+        .boxed();
+
+    let silences_route = warp::path!("api" / "silences")
+        .and(warp::get())
+        .and(alerts_filter.clone())
+        .and_then(silences_handler)
+        .boxed();
+
+    let create_silence_route = warp::path!("api" / "silences")
+        .and(warp::post())
+        .and(alerts_filter.clone())
+        .and(warp::body::content_length_limit(16 * 1024))
+        .and(warp::body::json::<SilenceRequest>())
+        .and_then(create_silence_handler)
+        .boxed();
+
+    let query_route = warp::path!("api" / "query")
+        .and(warp::post())
+        .and(session_filter)
+        .and(config_filter.clone())
+        .and(warp::body::content_length_limit(16 * 1024))
+        .and(warp::body::json::<query::QueryRequest>())
+        .and_then(query_handler)
+        .boxed();
+
+    let subscription_filter = warp::any().map(move || subscription.clone());
+    let subscription_route = warp::path!("api" / "subscribe")
+        .and(warp::get())
+        .and(subscription_filter.clone())
+        .and_then(subscription_handler)
+        .boxed();
+
+    let subscribe_route = warp::path!("api" / "subscribe")
+        .and(warp::post())
+        .and(subscription_filter)
+        .and(warp::body::content_length_limit(16 * 1024))
+        .and(warp::body::json::<SubscribeRequest>())
+        .and_then(subscribe_handler)
+        .boxed();
+
+    let delete_silence_route = warp::path!("api" / "silences" / u64)
+        .and(warp::delete())
+        .and(alerts_filter.clone())
+        .and_then(delete_silence_handler)
+        .boxed();
+
+    let topic_detail_route = topic_detail_route(detail_cache, detail_history).boxed();
+
+    let chart_route = warp::path!("api" / "chart" / String)
+        .and(warp::get())
+        .and(warp::query::<ChartQuery>())
+        .and(metric_history_filter)
+        .and(warp::any().map(move || chart_cache.clone()))
+        .and(warp::any().map(move || display_units))
+        .and_then(chart_handler)
+        .boxed();
+
+    let topics_route = warp::path!("api" / "topics")
+        .and(warp::get())
+        .and(cache_filter.clone())
+        .and(warp::any().map(move || default_sort))
+        .and(warp::query::<TopicsQuery>())
+        .and_then(topics_handler)
+        .boxed();
+
+    let health_summary_route = warp::path!("api" / "health-summary")
+        .and(warp::get())
+        .and(config_filter.clone())
+        .and(cache_filter.clone())
+        .and(alerts_filter.clone())
+        .and(status_filter.clone())
+        .and_then(health_summary_handler)
+        .boxed();
+
+    let report_css = Arc::new(extra_css);
+    let report_route = warp::path!("api" / "report.html")
+        .and(warp::get())
+        .and(warp::query::<ReportQuery>())
+        .and(config_filter.clone())
+        .and(warp::any().map(move || report_css.clone()))
+        .and(cache_filter.clone())
+        .and(alerts_filter)
+        .and(throughput_filter.clone())
+        .and_then(report_handler)
+        .boxed();
+
+    let throughput_route = warp::path!("api" / "throughput")
+        .and(warp::get())
+        .and(throughput_filter)
+        .and(warp::query::<ThroughputQuery>())
+        .and_then(throughput_handler)
+        .boxed();
+
+    let stats_route = warp::path!("api" / "stats")
+        .and(warp::get())
+        .and(cache_filter.clone())
+        .and(config_filter.clone())
+        .and_then(stats_handler)
+        .boxed();
+
+    let grafana_route = warp::path!("api" / "grafana")
+        .and(warp::get())
+        .and(cache_filter.clone())
+        .and(warp::query::<GrafanaQuery>())
+        .and_then(grafana_handler)
+        .boxed();
+
+    let notifications_route = warp::path!("api" / "notifications")
+        .and(warp::get())
+        .and(notifiers_filter)
+        .and_then(notifications_handler)
+        .boxed();
+
+    let pins_filter = warp::any().map(move || pins.clone());
+    let config_route = warp::path!("api" / "config")
+        .and(warp::get())
+        .and(config_filter.clone())
+        .and(pins_filter.clone())
+        .and_then(config_handler)
+        .boxed();
+
+    let pins_route = warp::path!("api" / "pins")
+        .and(warp::get())
+        .and(pins_filter.clone())
+        .and_then(pins_handler)
+        .boxed();
+
+    let pin_route = warp::path!("api" / "pins")
+        .and(warp::post())
+        .and(pins_filter.clone())
+        .and(cache_filter.clone())
+        .and(warp::body::content_length_limit(4 * 1024))
+        .and(warp::body::json::<PinRequest>())
+        .and_then(pin_handler)
+        .boxed();
+
+    let unpin_route = warp::path!("api" / "pins")
+        .and(warp::delete())
+        .and(pins_filter)
+        .and(cache_filter.clone())
+        .and(warp::body::content_length_limit(4 * 1024))
+        .and(warp::body::json::<PinRequest>())
+        .and_then(unpin_handler)
+        .boxed();
+
+    let about_route = warp::path!("api" / "about")
+        .and(warp::get())
+        .and(config_filter.clone())
+        .and_then(about_handler)
+        .boxed();
+
+    let dump_route = warp::path!("api" / "dump")
+        .and(warp::post())
+        .and(cache_filter)
+        .and(config_filter)
+        .and_then(dump_handler)
+        .boxed();
+
+    let federation_route = warp::path!("api" / "federation")
+        .and(warp::get())
+        .and(warp::any().map(move || federation.clone()))
+        .and_then(federation_handler)
+        .boxed();
+
+    let readyz_route = warp::path!("readyz")
+        .and(warp::get())
+        .and(status_filter.clone())
+        .and_then(readyz_handler)
+        .boxed();
+
+    let status_route = warp::path!("api" / "status")
+        .and(warp::get())
+        .and(status_filter)
+        .and(decode_limiter_filter)
+        .and(sse_clients_filter)
+        .and_then(status_handler)
+        .boxed();
+
+    let diagnostics_route = warp::path!("api" / "diagnostics")
+        .and(warp::get())
+        .and(processing_latency_filter)
+        .and_then(diagnostics_handler)
+        .boxed();
+
+    let routes = index
+        .or(sse_route)
+        .or(ui_config_route)
+        .or(alerts_route)
+        .or(alert_history_route)
+        .or(alert_ack_route)
+        .or(silences_route)
+        .or(create_silence_route)
+        .or(delete_silence_route)
+        .or(query_route)
+        .or(subscription_route)
+        .or(subscribe_route)
+        .or(views_route)
+        .or(put_view_route)
+        .or(delete_view_route)
+        .or(pins_route)
+        .or(pin_route)
+        .or(unpin_route)
+        .or(topics_route)
+        .or(topic_detail_route)
+        .or(chart_route)
+        .or(stats_route)
+        .or(throughput_route)
+        .or(report_route)
+        .or(health_summary_route)
+        .or(grafana_route)
+        .or(notifications_route)
+        .or(config_route)
+        .or(about_route)
+        .or(dump_route)
+        .or(federation_route)
+        .or(status_route)
+        .or(diagnostics_route)
+        .or(readyz_route);
+
+    // Each segment of the base path must match before any route is tried
+    let prefix = base_path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .fold(warp::any().boxed(), |prefix, segment| {
+            prefix.and(warp::path(segment.to_string())).boxed()
+        });
+    let routes = prefix.and(routes);
+
+    info!(
+        "Starting web server on http://localhost:{}{}/",
+        port, base_path
+    );
+    // Open SSE streams end after their shutdown event, so this returns once
+    // they have all been sent
+    let (_, server) =
+        warp::serve(routes).bind_with_graceful_shutdown(([127, 0, 0, 1], port), async move {
+            shutdown::stopping(&mut stopping).await;
+        });
+    server.await;
+    if let Some(grpc_server) = grpc_server
+        && let Err(e) = grpc_server.await
+    {
+        warn!("gRPC server task failed: {}", e);
+    }
+}
+
+/// Exit codes of the monitor; `--check` mode has its own in `check`
+const EXIT_CONFIG: i32 = 1;
+const EXIT_ZENOH_UNREACHABLE: i32 = 3;
+
+/// Why the monitor gave up starting, each with its own exit code so a
+/// supervisor can tell a bad configuration from an unreachable network
+#[derive(Debug)]
+enum StartupError {
+    /// An invalid argument, configuration value or manifest
+    Config(String),
+    /// `ZENOH_OPEN_ATTEMPTS` sessions failed to open, none having opened before
+    ZenohUnreachable {
+        target: String,
+        attempts: u32,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for StartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StartupError::Config(e) => write!(f, "Invalid configuration: {}", e),
+            StartupError::ZenohUnreachable {
+                target,
+                attempts,
+                error,
+            } => write!(
+                f,
+                "Could not open a Zenoh session to {} in {} attempts, giving up: {}",
+                target, attempts, error
+            ),
+        }
+    }
+}
+
+impl StartupError {
+    /// Giving up after `open_failures` sessions failed to open, none having
+    /// opened before, once `Config::zenoh_open_attempts` is reached
+    fn unreachable(config: &Config, open_failures: u32, error: &str) -> Option<Self> {
+        let attempts = config
+            .zenoh_open_attempts
+            .filter(|&attempts| open_failures >= attempts)?;
+        Some(StartupError::ZenohUnreachable {
+            target: config.zenoh_target(),
+            attempts,
+            error: error.to_string(),
+        })
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            StartupError::Config(_) => EXIT_CONFIG,
+            StartupError::ZenohUnreachable { .. } => EXIT_ZENOH_UNREACHABLE,
+        }
+    }
+
+    /// Logs the error and exits with its code
+    fn exit(self) -> ! {
+        error!("{}", self);
+        log::logger().flush();
+        std::process::exit(self.exit_code());
+    }
+}
+
+/// Writes log lines to stderr when the file logger can't be set up
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{} {} [{}] {}",
+                format::timestamp(get_timestamp()),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Logs to the console and to daily files in `logs/`. If that fails, e.g. on a
+/// read-only working directory, logs go to stderr alone with a warning.
+fn init_logger(level: LevelFilter) {
+    let result = std::fs::create_dir_all("logs")
+        .map_err(|e| e.to_string())
+        .and_then(|()| {
+            Ftail::new()
+                .console(level)
+                .daily_file(Path::new("logs"), level)
+                .retention_days(3)
+                .timezone(ftail::Tz::UTC)
+                .datetime_format("%Y-%m-%d_%H:%M:%S%.3f")
+                .init()
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        match log::set_logger(&StderrLogger) {
+            Ok(()) => {
+                log::set_max_level(level);
+                warn!("Failed to initialise logger, logging to stderr only: {}", e);
+            }
+            // Ftail set itself up before failing, so logging still works
+            Err(_) => warn!("Failed to initialise logger: {}", e),
+        }
+    }
+}
+
+/// Validates `config` and builds its Zenoh session configuration, so a bad
+/// value stops the monitor before it opens anything
+fn check_startup(config: Config) -> Result<(Config, zenoh::Config), String> {
+    config.validate()?;
+    let session_config = config.zenoh()?;
+    Ok((config, session_config))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse_or_exit();
+    let config = config::resolve(&cli, |name| std::env::var(name).ok());
+    init_logger(config.as_ref().map_or(LOG_LEVEL, Config::log_filter));
+    let checked = config.and_then(check_startup);
+
+    // `--check <manifest.toml> [--duration <seconds>]` runs once without the web server
+    if let Some(manifest_path) = &cli.check {
+        let (config, session_config) = checked.unwrap_or_else(|e| {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(check::EXIT_USAGE);
+        });
+        let code = match check::load_manifest(manifest_path) {
+            Ok(manifest) => check::run(manifest, cli.duration, config, session_config).await,
+            Err(e) => {
+                eprintln!("Failed to load check manifest: {}", e);
+                check::EXIT_USAGE
+            }
+        };
+        std::process::exit(code);
+    }
+
+    info!("Starting Zenoh DDS Web Monitor...");
+
+    let (config, session_config) = checked.unwrap_or_else(|e| StartupError::Config(e).exit());
+    let config = Arc::new(config);
+    match serde_json::to_string(&*config) {
+        Ok(json) => info!("Effective configuration: {}", json),
+        Err(e) => warn!("Failed to serialise configuration: {}", e),
+    }
+
+    if config.decoder_enabled {
+        info!("Decoder chain enabled: {}", config.decoders.join(" -> "));
+    } else {
+        info!("Running in standard mode (no custom decoder)");
+    }
+
+    let topic_cache: TopicCache = Arc::new(RwLock::new(HashMap::new()));
+    let pins: PinStore = Arc::new(RwLock::new(Pins::load(config.pins_file)));
+    for key in pins.read().await.list() {
+        pins::apply(&topic_cache, key, true).await;
+    }
+    let alert_store: AlertStore = Arc::new(RwLock::new(AlertEngine::new(
+        config.alert_rules,
+        config.composite_alert_rules,
+        config.alert_history_retention,
+        Silences::load(config.silences_file),
+        config.instance_name,
+    )));
+    let zenoh_session: ZenohSession = Arc::new(RwLock::new(None));
+    let notifiers = Notifiers::start(config.notifiers, config.instance_name);
+    let decode_limiter = DecodeLimiter::new(&config);
+    let processing_latency = config
+        .measure_processing_latency
+        .then(ProcessingLatencies::default);
+    let subscriber_status: SubscriberState = Arc::new(RwLock::new(SubscriberStatus::Connecting));
+    let tombstones = Tombstones::default();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut tasks = Tasks::new(shutdown_rx);
+
+    if let Some(limit_bytes) = config.memory_limit_bytes {
+        tasks.spawn(
+            "memory guard",
+            memory::run_memory_guard(
+                topic_cache.clone(),
+                tombstones.clone(),
+                decode_limiter.clone(),
+                limit_bytes,
+                config.reload_period_ms,
+            ),
+        );
+    }
+
+    let subscription: Subscription =
+        Arc::new(watch::channel(config.subscribe_key_expr.to_string()).0);
+    let federation = federation::new_state(config.federation_upstreams);
+    for (index, upstream) in config.federation_upstreams.iter().enumerate() {
+        tasks.spawn(
+            "federation upstream",
+            federation::run_upstream(
+                topic_cache.clone(),
+                tombstones.clone(),
+                federation.clone(),
+                index,
+                *upstream,
+                config.federation_poll_ms,
+            ),
+        );
+    }
+
+    if let Some(audit) = config.audit_log {
+        tasks.spawn_graceful("audit log", |shutdown| {
+            audit::run_audit_log(topic_cache.clone(), audit, config.instance_name, shutdown)
+        });
+    }
+
+    #[cfg(unix)]
+    tasks.spawn(
+        "cache dump",
+        dump::run_dump_on_signal(
+            topic_cache.clone(),
+            config.cache_dump_dir,
+            config.instance_name,
+        ),
+    );
+
+    if let Some(prefix) = config.publisher_liveliness
+        && !config.federation_only
+    {
+        tasks.spawn(
+            "publisher liveliness",
+            liveliness::run_publisher_liveliness(
+                topic_cache.clone(),
+                zenoh_session.clone(),
+                prefix,
+                config.multi_publisher_keys,
+                config.reload_period_ms,
+            ),
+        );
+    }
+
+    tasks.spawn(
+        "topic events",
+        notify::run_topic_events(
+            topic_cache.clone(),
+            notifiers.clone(),
+            tombstones.clone(),
+            config.reload_period_ms,
+        ),
+    );
+
+    if let Some(path) = &config.expected_topics_manifest {
+        match check::load_manifest(path) {
+            Ok(manifest) => {
+                info!("Showing expected rates from {}", path);
+                tasks.spawn(
+                    "rate expectations",
+                    check::run_rate_expectations(
+                        topic_cache.clone(),
+                        manifest,
+                        config.reload_period_ms,
+                    ),
+                );
+            }
+            Err(e) => StartupError::Config(format!(
+                "failed to load expected-topics manifest {}: {}",
+                path, e
+            ))
+            .exit(),
+        }
+    }
+
+    if !config.highlight_rules.is_empty() {
+        tasks.spawn(
+            "highlighter",
+            highlight::run_highlighter(
+                topic_cache.clone(),
+                config.highlight_rules,
+                config.reload_period_ms,
+            ),
+        );
+    }
+
+    tasks.spawn(
+        "alert evaluator",
+        alerts::run_alert_evaluator(
+            topic_cache.clone(),
+            alert_store.clone(),
+            zenoh_session.clone(),
+            notifiers.clone(),
+            config.reload_period_ms,
+            config.alert_publish_prefix,
+            config.alert_republish_ms,
+            config.alert_publish_debounce_ms,
+        ),
+    );
+
+    if config.federation_only {
+        info!("Federation only: not subscribing to Zenoh locally");
+        *subscriber_status.write().await = SubscriberStatus::Disabled;
+    }
+
+    tasks.spawn_graceful("web server", |shutdown| {
+        start_web_server(
+            config.clone(),
+            topic_cache.clone(),
+            alert_store.clone(),
+            notifiers.clone(),
+            zenoh_session.clone(),
+            subscriber_status.clone(),
+            decode_limiter.clone(),
+            processing_latency.clone(),
+            federation,
+            subscription.clone(),
+            tombstones.clone(),
+            pins,
+            shutdown,
+        )
+    });
+
+    // Spawned after the web server, so the page is up to report a Zenoh outage
+    // at startup rather than sitting empty while the first session is retried
+    if !config.federation_only {
+        tasks.spawn_graceful("Zenoh subscriber", |shutdown| {
+            let watchdog = run_subscriber_watchdog(
+                topic_cache.clone(),
+                tombstones.clone(),
+                zenoh_session.clone(),
+                subscriber_status.clone(),
+                subscription.clone(),
+                config.decoder_chain(),
+                decode_limiter.clone(),
+                processing_latency.clone(),
+                notifiers.clone(),
+                config.clone(),
+                session_config.clone(),
+                Arc::new(SystemClock),
+                shutdown,
+            );
+            async move {
+                if let Err(e) = watchdog.await {
+                    e.exit();
+                }
+            }
+        });
+    }
+
+    tokio::signal::ctrl_c().await?;
+
+    warn!("Zenoh DDS Web Monitor stopping.");
+
+    // Browsers are told this is a planned stop rather than a dropped connection,
+    // the last audit snapshot is written and the Zenoh session is closed
+    let _ = shutdown_tx.send(true);
+    tasks.join(Duration::from_millis(SHUTDOWN_TIMEOUT_MS)).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::{Clock, ManualClock};
+    use std::sync::atomic::{AtomicU32, AtomicUsize};
+    use zenoh::key_expr::KeyExpr;
+    use zenoh::sample::SampleBuilder;
+
+    /// Twenty intervals and a gap at three times the average, without an absolute cap
+    fn arrive(
+        window: &mut IntervalWindow,
+        clock: &ManualClock,
+    ) -> (f64, IntervalStats, Option<Gap>) {
+        arrive_in(window, RateWindow::Samples(20), clock)
+    }
+
+    fn arrive_in(
+        window: &mut IntervalWindow,
+        rate_window: RateWindow,
+        clock: &ManualClock,
+    ) -> (f64, IntervalStats, Option<Gap>) {
+        record_arrival(
+            window,
+            rate_window,
+            3.0,
+            None,
+            clock.monotonic_ns(),
+            clock.now_ms(),
+            true,
+        )
+    }
+
+    /// Feeds `count` samples `interval_ms` apart and returns the last rate
+    fn arrive_every(
+        window: &mut IntervalWindow,
+        clock: &ManualClock,
+        interval_ms: u64,
+        count: usize,
+    ) -> f64 {
+        let mut hz = 0.0;
+        for _ in 0..count {
+            clock.advance(interval_ms);
+            let (rate, _, gap) = arrive(window, clock);
+            assert_eq!(gap, None);
+            hz = rate;
+        }
+        hz
+    }
+
+    #[test]
+    fn record_arrival_gives_exact_rates_for_a_fixed_sequence() {
+        let clock = ManualClock::new(1_000_000);
+        let mut window = IntervalWindow::default();
+
+        let (hz, stats, gap) = arrive(&mut window, &clock);
+        assert_eq!((hz, gap), (0.0, None), "the first sample has no interval");
+        assert_eq!(stats, IntervalStats::default());
+
+        assert_eq!(arrive_every(&mut window, &clock, 100, 4), 10.0);
+        let (_, stats, _) = arrive(&mut window, &clock);
+        assert_eq!(stats.avg_ms, 80.0, "a batched arrival is a 0 ms interval");
+        assert_eq!(stats.min_ms, 0.0);
+        assert_eq!(stats.max_ms, 100.0);
+    }
+
+    #[test]
+    fn record_arrival_is_reproducible() {
+        let run = || {
+            let clock = ManualClock::new(0);
+            let mut window = IntervalWindow::default();
+            [100, 120, 80, 100, 0, 90, 110, 500]
+                .iter()
+                .map(|&interval_ms| {
+                    clock.advance(interval_ms);
+                    arrive(&mut window, &clock)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn sample_window_keeps_the_latest_intervals() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        let samples = RateWindow::Samples(4);
+
+        arrive_in(&mut window, samples, &clock);
+        for _ in 0..10 {
+            clock.advance(100);
+            arrive_in(&mut window, samples, &clock);
+        }
+        assert_eq!(window.intervals.len(), 4);
+        let mut hz = 0.0;
+        for _ in 0..3 {
+            clock.advance(50);
+            hz = arrive_in(&mut window, samples, &clock).0;
+        }
+        // One 100 ms interval is left beside three 50 ms ones
+        assert_eq!(window.intervals.len(), 4);
+        assert_eq!(hz, 16.0);
+        clock.advance(50);
+        assert_eq!(arrive_in(&mut window, samples, &clock).0, 20.0);
+    }
+
+    #[test]
+    fn duration_window_drops_intervals_older_than_the_window() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        let one_second = RateWindow::Duration { ms: 1000 };
+
+        arrive_in(&mut window, one_second, &clock);
+        for _ in 0..20 {
+            clock.advance(200);
+            arrive_in(&mut window, one_second, &clock);
+        }
+        // Intervals ending in the last second, including the one on its edge
+        assert_eq!(window.intervals.len(), 6);
+        let (hz, _, _) = arrive_in(&mut window, one_second, &clock);
+        assert_eq!(
+            window.intervals.len(),
+            7,
+            "a batched arrival joins the window"
+        );
+        assert!((hz - 7.0 / 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_gap_clears_the_window_and_reports_the_rate_before_it() {
+        let clock = ManualClock::new(10_000);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        assert_eq!(arrive_every(&mut window, &clock, 100, 5), 10.0);
+
+        // The gap is dated on the wall clock, whatever it reads
+        clock.set(60_000);
+        clock.advance(2000);
+        let (hz, stats, gap) = arrive(&mut window, &clock);
+        assert_eq!(hz, 10.0);
+        assert_eq!(stats.avg_ms, 100.0);
+        assert_eq!(
+            gap,
+            Some(Gap {
+                started_at: 60_000,
+                duration_ms: 2000
+            })
+        );
+        assert!(window.intervals.is_empty());
+        assert_eq!(window.last_arrival, Some(clock.monotonic_ns()));
+    }
+
+    #[test]
+    fn an_arrival_earlier_than_the_last_counts_as_a_zero_interval() {
+        let mut window = IntervalWindow::default();
+        for timestamp in [0, 100, 200] {
+            record_arrival(
+                &mut window,
+                RateWindow::Samples(20),
+                3.0,
+                None,
+                timestamp * NANOS_PER_MS,
+                timestamp,
+                true,
+            );
+        }
+        let (hz, stats, gap) = record_arrival(
+            &mut window,
+            RateWindow::Samples(20),
+            3.0,
+            None,
+            150 * NANOS_PER_MS,
+            150,
+            true,
+        );
+        assert_eq!(gap, None);
+        assert_eq!(stats.min_ms, 0.0);
+        assert_eq!(hz, 1.0 / (0.2 / 3.0));
+        assert_eq!(window.last_arrival, Some(150 * NANOS_PER_MS));
+    }
+
+    #[test]
+    fn samples_not_counting_toward_the_rate_leave_the_window_alone() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        arrive_every(&mut window, &clock, 100, 3);
+
+        clock.advance(30);
+        let (hz, _, gap) = record_arrival(
+            &mut window,
+            RateWindow::Samples(20),
+            3.0,
+            None,
+            clock.monotonic_ns(),
+            clock.now_ms(),
+            false,
+        );
+        assert_eq!((hz, gap), (10.0, None));
+        assert_eq!(window.intervals.len(), 3);
+        clock.advance(70);
+        assert_eq!(arrive(&mut window, &clock).0, 10.0);
+    }
+
+    #[test]
+    fn steady_topic_keeps_its_rate_without_gaps() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        for _ in 0..100 {
+            clock.advance(100);
+            let (hz, _, gap) = arrive(&mut window, &clock);
+            assert_eq!((hz, gap), (10.0, None));
+        }
+        assert_eq!(window.intervals.len(), 20);
+    }
+
+    #[test]
+    fn rate_recovers_right_after_a_pause() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        arrive_every(&mut window, &clock, 100, 20);
+
+        clock.advance(5000);
+        let (hz, _, gap) = arrive(&mut window, &clock);
+        assert_eq!(
+            hz, 10.0,
+            "the sample ending the pause reports the rate before it"
+        );
+        assert_eq!(gap.map(|gap| gap.duration_ms), Some(5000));
+
+        // Without the reset, the 5 s interval would hold the rate near 3 Hz
+        // for the next twenty samples
+        for _ in 0..20 {
+            clock.advance(100);
+            let (hz, _, gap) = arrive(&mut window, &clock);
+            assert_eq!((hz, gap), (10.0, None));
+        }
+    }
+
+    #[test]
+    fn slow_topic_is_not_a_gap() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        assert_eq!(arrive_every(&mut window, &clock, 5000, 10), 0.2);
+
+        // An absolute cap catches a long silence even before there is an average
+        let mut window = IntervalWindow::default();
+        let mut arrive_capped = |clock: &ManualClock| {
+            record_arrival(
+                &mut window,
+                RateWindow::Samples(20),
+                3.0,
+                Some(10_000),
+                clock.monotonic_ns(),
+                clock.now_ms(),
+                true,
+            )
+        };
+        arrive_capped(&clock);
+        clock.advance(5000);
+        assert_eq!(arrive_capped(&clock).2, None);
+        clock.advance(20_000);
+        assert_eq!(
+            arrive_capped(&clock).2.map(|gap| gap.duration_ms),
+            Some(20_000)
+        );
+    }
+
+    #[test]
+    fn rate_converges_after_a_rate_change() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        arrive_every(&mut window, &clock, 100, 20);
+
+        // Faster: the estimate rises with each shorter interval until the
+        // window holds only the new ones
+        let mut previous = 10.0;
+        for _ in 0..19 {
+            clock.advance(50);
+            let (hz, _, gap) = arrive(&mut window, &clock);
+            assert_eq!(gap, None);
+            assert!(hz > previous && hz < 20.0);
+            previous = hz;
+        }
+        assert_eq!(arrive_every(&mut window, &clock, 50, 1), 20.0);
+        previous = 20.0;
+
+        // Slower, but by less than the gap factor: the estimate falls to the new rate
+        for _ in 0..19 {
+            clock.advance(100);
+            let (hz, _, gap) = arrive(&mut window, &clock);
+            assert_eq!(gap, None);
+            assert!(hz < previous && hz > 10.0);
+            previous = hz;
+        }
+        assert_eq!(arrive_every(&mut window, &clock, 100, 1), 10.0);
+    }
+
+    /// Feeds `count` samples at the given nanosecond intervals, in turn
+    fn arrive_at_ns(
+        window: &mut IntervalWindow,
+        clock: &ManualClock,
+        intervals_ns: &[u64],
+        count: usize,
+    ) -> f64 {
+        let mut hz = 0.0;
+        for interval_ns in intervals_ns.iter().cycle().take(count) {
+            clock.advance_ns(*interval_ns);
+            let (rate, _, gap) = arrive(window, clock);
+            assert_eq!(gap, None);
+            hz = rate;
+        }
+        hz
+    }
+
+    fn assert_within_1_pct(hz: f64, expected: f64) {
+        assert!(
+            (hz - expected).abs() <= expected * 0.01,
+            "{} Hz is not within 1% of {} Hz",
+            hz,
+            expected
+        );
+    }
+
+    #[test]
+    fn one_khz_topic_is_estimated_within_1_pct() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        assert_within_1_pct(arrive_at_ns(&mut window, &clock, &[1_000_000], 100), 1000.0);
+        // Jitter around 1 ms, which whole milliseconds would round to 0 or 1
+        assert_within_1_pct(
+            arrive_at_ns(
+                &mut window,
+                &clock,
+                &[900_000, 1_100_000, 700_000, 1_300_000],
+                100,
+            ),
+            1000.0,
+        );
+    }
+
+    #[test]
+    fn five_khz_topic_is_estimated_within_1_pct() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        assert_within_1_pct(arrive_at_ns(&mut window, &clock, &[200_000], 100), 5000.0);
+        // Delivered in pairs: every other interval is 0 and still counts
+        assert_within_1_pct(
+            arrive_at_ns(&mut window, &clock, &[0, 400_000], 100),
+            5000.0,
+        );
+    }
+
+    #[test]
+    fn wall_clock_step_back_leaves_the_rate_alone() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        assert_eq!(arrive_every(&mut window, &clock, 100, 20), 10.0);
+
+        // An NTP correction 30 s back, between two samples
+        clock.set(clock.now_ms() - 30_000);
+        for _ in 0..20 {
+            clock.advance(100);
+            let (hz, stats, gap) = arrive(&mut window, &clock);
+            assert_eq!((hz, gap), (10.0, None));
+            assert_eq!((stats.min_ms, stats.max_ms), (100.0, 100.0));
+        }
+    }
+
+    #[test]
+    fn one_sample_has_no_rate_and_two_do() {
+        let clock = ManualClock::new(0);
+        let mut window = IntervalWindow::default();
+
+        let (hz, _, _) = arrive(&mut window, &clock);
+        assert_eq!(measured_hz(1, hz), None);
+        assert_eq!(format::hz_pending(1), "– (1 msg)");
+
+        clock.advance(250);
+        let (hz, _, _) = arrive(&mut window, &clock);
+        assert_eq!(measured_hz(2, hz), Some(4.0));
+    }
+
+    #[test]
+    fn topic_that_died_after_a_burst_keeps_its_rate_and_decays() {
+        let clock = ManualClock::new(50_000);
+        let mut window = IntervalWindow::default();
+        arrive(&mut window, &clock);
+        let hz = arrive_every(&mut window, &clock, 100, 9);
+
+        let topic = TopicData {
+            estimated_hz: measured_hz(10, hz),
+            message_count: 10,
+            received_timestamp: clock.now_ms(),
+            ..TopicData::default()
+        };
+        assert_eq!(topic.estimated_hz, Some(10.0));
+        // Silent for 10 s: the alert rules see the silence, not the stored rate
+        assert_eq!(alerts::effective_hz(&topic, clock.now_ms() + 10_000), 0.1);
+
+        let never_measured = TopicData {
+            estimated_hz: measured_hz(1, 0.0),
+            message_count: 1,
+            ..topic.clone()
+        };
+        assert_eq!(never_measured.estimated_hz, None);
+        assert_eq!(alerts::effective_hz(&never_measured, clock.now_ms()), 0.0);
+    }
+
+    fn sample(key: &str, payload: &[u8], timestamp: Option<u64>) -> Sample {
+        let timestamp = timestamp.map(|time| {
+            zenoh::time::Timestamp::new(
+                zenoh::time::NTP64(time),
+                zenoh::time::TimestampId::try_from([1u8]).unwrap(),
+            )
+        });
+        SampleBuilder::put(
+            KeyExpr::try_from(key.to_string()).unwrap(),
+            payload.to_vec(),
+        )
+        .timestamp(timestamp)
+        .into()
+    }
+
+    /// Checks a sample against the topic's fingerprints as the subscribe loop
+    /// does, remembering it unless it is a duplicate
+    fn is_duplicate(
+        seen: &mut SeenSamples,
+        mode: DuplicateSamples,
+        sample: &Sample,
+        now: u64,
+    ) -> bool {
+        let Some(fingerprint) = Fingerprint::of(mode, sample) else {
+            return false;
+        };
+        if seen.contains(fingerprint, now) {
+            return true;
+        }
+        seen.record(fingerprint, now);
+        false
+    }
+
+    /// The same publication reaching the monitor over two routes, as on a
+    /// network with both a router and a direct peer link
+    #[test]
+    fn duplicate_over_a_second_route_is_recognised_by_its_timestamp() {
+        let mode = DuplicateSamples::DropByTimestamp;
+        let (mut odom, mut imu) = (SeenSamples::default(), SeenSamples::default());
+        let via_router = sample("robot/odom", b"pose", Some(1));
+        let via_peer = sample("robot/odom", b"pose", Some(1));
+        assert!(!is_duplicate(&mut odom, mode, &via_router, 1000));
+        assert!(is_duplicate(&mut odom, mode, &via_peer, 1001));
+        // However late the second copy arrives
+        assert!(is_duplicate(&mut odom, mode, &via_peer, 60_000));
+
+        let next = sample("robot/odom", b"pose", Some(2));
+        assert!(!is_duplicate(&mut odom, mode, &next, 1100));
+        // The same timestamp on another topic is another sample
+        assert!(!is_duplicate(&mut imu, mode, &via_peer, 1100));
+        // Without a timestamp there is nothing to match
+        let untimed = sample("robot/odom", b"pose", None);
+        assert!(!is_duplicate(&mut odom, mode, &untimed, 1200));
+        assert!(!is_duplicate(&mut odom, mode, &untimed, 1201));
+    }
+
+    #[test]
+    fn untimestamped_duplicate_is_recognised_by_payload_within_the_window() {
+        let mode = DuplicateSamples::DropByPayload { window_ms: 50 };
+        let mut seen = SeenSamples::default();
+        let copy = sample("robot/odom", b"pose", None);
+        assert!(!is_duplicate(&mut seen, mode, &copy, 1000));
+        assert!(is_duplicate(&mut seen, mode, &copy, 1050));
+        // Outside the window the same payload is a new sample, e.g. a keep-alive
+        assert!(!is_duplicate(&mut seen, mode, &copy, 1051));
+        let other = sample("robot/odom", b"other", None);
+        assert!(!is_duplicate(&mut seen, mode, &other, 1052));
+
+        let timed = sample("robot/odom", b"pose", Some(1));
+        let mut kept = SeenSamples::default();
+        assert!(!is_duplicate(
+            &mut kept,
+            DuplicateSamples::Keep,
+            &timed,
+            1000
+        ));
+        assert!(!is_duplicate(
+            &mut kept,
+            DuplicateSamples::Keep,
+            &timed,
+            1000
+        ));
+    }
+
+    #[test]
+    fn fingerprints_are_bounded_per_topic() {
+        let mut seen = SeenSamples::default();
+        for time in 0..1000 {
+            let sample = sample("robot/odom", b"pose", Some(time));
+            assert!(!is_duplicate(
+                &mut seen,
+                DuplicateSamples::DropByTimestamp,
+                &sample,
+                time
+            ));
+        }
+        assert_eq!(seen.entries.len(), DUPLICATE_MEMORY);
+    }
+
+    /// A topic as the subscribe loop leaves it: a window of intervals and
+    /// the fingerprint of its latest sample
+    fn churned_topic(key: &str, time: u64) -> TopicState {
+        let mut topic = TopicState::from(TopicData {
+            key_expr: key.to_string(),
+            received_timestamp: time + 1,
+            ..TopicData::default()
+        });
+        topic.intervals.last_arrival = Some(time * NANOS_PER_MS);
+        topic
+            .intervals
+            .intervals
+            .push_back((time * NANOS_PER_MS, NANOS_PER_MS));
+        let fingerprint = Fingerprint::of(
+            DuplicateSamples::DropByTimestamp,
+            &sample(key, b"", Some(time)),
+        );
+        topic.seen.record(fingerprint.unwrap(), time);
+        topic
+    }
+
+    #[tokio::test]
+    async fn churning_keys_leaves_no_per_topic_state_behind() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        for i in 0..100_000 {
+            let key = format!("churn/{}", i);
+            cache
+                .write()
+                .await
+                .insert(key.clone(), churned_topic(&key, i));
+            remove_deleted(&cache, &tombstones, &key).await;
+        }
+        assert!(cache.read().await.is_empty());
+        let tombstones = tombstones.read().await;
+        assert_eq!(tombstones.len(), TOMBSTONE_CAPACITY);
+        assert!(tombstones.order.len() <= 2 * TOMBSTONE_CAPACITY);
+        // The newest removals are the ones remembered
+        assert!(tombstones.get("churn/99999").is_some());
+        assert!(tombstones.get("churn/0").is_none());
+    }
+
+    #[tokio::test]
+    async fn churning_one_key_keeps_one_tombstone() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        for i in 0..100_000 {
+            let topic = churned_topic("churn/same", i);
+            cache.write().await.insert("churn/same".to_string(), topic);
+            remove_deleted(&cache, &tombstones, "churn/same").await;
+        }
+        let tombstones = tombstones.read().await;
+        assert_eq!(tombstones.len(), 1);
+        assert!(tombstones.order.len() <= 2 * TOMBSTONE_CAPACITY);
+    }
+
+    fn delta_tracker(tombstones: &Tombstones) -> DeltaTracker {
+        DeltaTracker::new(DeltaSources {
+            ticks: watch::channel(CacheSnapshot::default()).1,
+            metric_history: MetricHistory::default(),
+            send_sparklines: false,
+            throughput: Throughput::default(),
+            display_units: format::Units::Si,
+            tombstones: tombstones.clone(),
+            heartbeat_ms: 15_000,
+            decoded_event: false,
+            seq_numbers: true,
+        })
+    }
+
+    #[tokio::test]
+    async fn topic_removed_and_back_between_ticks_is_reappeared() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        let mut tracker = delta_tracker(&tombstones);
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 1));
+        tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await;
+
+        remove_deleted(&cache, &tombstones, "robot/lidar").await;
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 2));
+        let update = tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await
+            .unwrap();
+        assert_eq!(update.added.len(), 1);
+        assert_eq!(update.reappeared, ["robot/lidar"]);
+        assert!(update.updated.is_empty() && update.removed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn updates_carry_no_seq_when_the_config_turns_it_off() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 1));
+        let mut tracker = DeltaTracker::new(DeltaSources {
+            seq_numbers: false,
+            ..delta_tracker(&tombstones).sources
+        });
+        let update = tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await
+            .unwrap();
+        assert_eq!(update.seq, None);
+        assert!(!serde_json::to_string(&update).unwrap().contains("\"seq\""));
+    }
+
+    #[tokio::test]
+    async fn idle_ticks_send_nothing() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 1));
+        let (ticks, snapshots) = watch::channel(CacheSnapshot::of(&cache, &tombstones).await);
+        let mut tracker = delta_tracker(&tombstones);
+
+        let tick = snapshots.borrow().clone();
+        let first = tracker.next(tick).await.unwrap();
+        assert_eq!(first.seq, Some(1));
+        assert_eq!(first.updated.len(), 1);
+        for _ in 0..3 {
+            let unchanged = snapshots.borrow().clone();
+            ticks.send_replace(unchanged);
+            let tick = snapshots.borrow().clone();
+            assert!(tracker.next(tick).await.is_none());
+        }
+        // The sequence only moves for an update that was sent
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 2));
+        let next = tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await
+            .unwrap();
+        assert_eq!(next.seq, Some(2));
+    }
+
+    #[tokio::test]
+    async fn dropping_an_sse_stream_releases_its_client() {
+        use futures::StreamExt;
+
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), churned_topic("robot/lidar", 1));
+        let snapshot = CacheSnapshot::of(&cache, &tombstones).await;
+        let (ticks, receiver) = watch::channel(snapshot.clone());
+        let sources = DeltaSources {
+            ticks: receiver,
+            ..delta_tracker(&tombstones).sources
+        };
+        let held = Arc::strong_count(&snapshot.topics);
+        let clients = SseClients::default();
+        let (_stop, shutdown) = watch::channel(false);
+
+        let mut stream = Box::pin(sse_events(sources, &clients, shutdown));
+        assert_eq!(clients.open(), 1);
+        assert!(stream.next().await.is_some());
+        // The stream's tracker keeps the snapshot it compared against
+        assert!(Arc::strong_count(&snapshot.topics) > held);
+
+        drop(stream);
+        assert_eq!(clients.open(), 0);
+        assert_eq!(Arc::strong_count(&snapshot.topics), held);
+        drop(ticks);
+    }
+
+    /// The `data` of one encoded SSE event, as a browser's `EventSource` joins it
+    fn sse_data(event: &sse::Event) -> String {
+        let frame = event.to_string();
+        assert!(frame.ends_with("\n\n"));
+        let data: Vec<&str> = frame
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .collect();
+        assert_eq!(data.len(), 1, "one data line per event");
+        data.join("\n")
+    }
+
+    #[tokio::test]
+    async fn decoded_content_round_trips_through_sse_framing() {
+        use futures::StreamExt;
+
+        let contents = [
+            "line one\nline two\n\nline four".to_string(),
+            "windows\r\nline endings\r\n".to_string(),
+            "data: not a field\nevent: neither\n".to_string(),
+            "x".repeat(1_000_000),
+        ];
+        for decoded_event in [false, true] {
+            let cache: TopicCache = Arc::default();
+            let tombstones = Tombstones::default();
+            for (index, content) in contents.iter().enumerate() {
+                let key = format!("robot/{}", index);
+                let mut topic = churned_topic(&key, 1);
+                topic.data.decoded_content = Some(content.clone());
+                cache.write().await.insert(key, topic);
+            }
+            let (_ticks, receiver) = watch::channel(CacheSnapshot::of(&cache, &tombstones).await);
+            let sources = DeltaSources {
+                ticks: receiver,
+                decoded_event,
+                ..delta_tracker(&tombstones).sources
+            };
+            let (_stop, shutdown) = watch::channel(false);
+            let mut stream = Box::pin(sse_events(sources, &SseClients::default(), shutdown));
+
+            let update = stream.next().await.unwrap().unwrap();
+            let update: serde_json::Value = serde_json::from_str(&sse_data(&update)).unwrap();
+            let decoded: serde_json::Value = if decoded_event {
+                let event = stream.next().await.unwrap().unwrap();
+                assert!(event.to_string().contains("event:decoded\n"));
+                serde_json::from_str(&sse_data(&event)).unwrap()
+            } else {
+                update["updated"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|topic| (topic["key_expr"].clone(), topic["decoded_content"].clone()))
+                    .map(|(key, content)| (key.as_str().unwrap().to_string(), content))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into()
+            };
+            for (index, content) in contents.iter().enumerate() {
+                assert_eq!(decoded[format!("robot/{}", index)], content.as_str());
+            }
+        }
+    }
+
+    /// A subscriber whose channel has closed
+    struct ClosedReceiver;
+
+    impl SampleReceiver for ClosedReceiver {
+        async fn recv(&self) -> Result<Sample, String> {
+            Err("channel closed".to_string())
+        }
+    }
+
+    struct FakeSession {
+        closed: bool,
+        /// Declarations that fail before one succeeds
+        failures: AtomicU32,
+    }
+
+    impl SubscriberSession for FakeSession {
+        type Receiver = ClosedReceiver;
+
+        fn is_closed(&self) -> bool {
+            self.closed
+        }
+
+        async fn declare(&self, _key: &str) -> Result<ClosedReceiver, String> {
+            match self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            {
+                Ok(_) => Err("router busy".to_string()),
+                Err(_) => Ok(ClosedReceiver),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_receive_resubscribes_and_reports_each_attempt() {
+        let session = Arc::new(FakeSession {
+            closed: false,
+            failures: AtomicU32::new(1),
+        });
+        let status: SubscriberState =
+            Arc::new(RwLock::new(SubscriberStatus::Connected { since: 1 }));
+        let error = ClosedReceiver.recv().await.unwrap_err();
+        let recovery = tokio::spawn({
+            let (session, status) = (session.clone(), status.clone());
+            async move { recover(&*session, "robot/**", &status, &Config::default(), error).await }
+        });
+
+        let (min_backoff_ms, _) = Config::default().watchdog_backoff_ms;
+        time::sleep(Duration::from_millis(min_backoff_ms / 2)).await;
+        assert!(matches!(
+            *status.read().await,
+            SubscriberStatus::Resubscribing { attempt: 1, .. }
+        ));
+        time::sleep(Duration::from_millis(min_backoff_ms)).await;
+        assert!(matches!(
+            *status.read().await,
+            SubscriberStatus::Resubscribing { attempt: 2, .. }
+        ));
+        assert!(recovery.await.unwrap().is_ok());
+        assert!(matches!(
+            *status.read().await,
+            SubscriberStatus::Connected { since } if since > 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_receive_on_a_closed_session_is_left_to_the_watchdog() {
+        let session = FakeSession {
+            closed: true,
+            failures: AtomicU32::new(0),
+        };
+        let status: SubscriberState =
+            Arc::new(RwLock::new(SubscriberStatus::Connected { since: 1 }));
+        let error = ClosedReceiver.recv().await.unwrap_err();
+        let result = recover(&session, "robot/**", &status, &Config::default(), error).await;
+        assert_eq!(
+            result.err().as_deref(),
+            Some("Zenoh session closed: channel closed")
+        );
+        assert!(matches!(
+            *status.read().await,
+            SubscriberStatus::Connected { since: 1 }
+        ));
+    }
 
-    function rebuildTable() {{
-        tableBody.innerHTML = '';
-        sortTopics().forEach(topicData => createAndInsertRow(topicData));
-        applyFilter();
-    }}
+    #[tokio::test]
+    async fn topic_detail_takes_an_encoded_key_with_slashes() {
+        let cache: TopicCache = Arc::default();
+        cache.write().await.insert(
+            "robot/1/lidar".to_string(),
+            churned_topic("robot/1/lidar", 1),
+        );
+        let route = topic_detail_route(cache, MetricHistory::default());
 
-    function createAndInsertRow(topicData) {{
-        const timestampReadable = new Date(topicData.received_timestamp).toISOString().replace('T', ' ').replace('Z', ' UTC');
-        const decodedContent = hasDecoder && topicData.decoded_content
-            ? `<td class="decoded-cell">${{topicData.decoded_content}}</td>`
-            : (hasDecoder ? '<td class="decoded-cell">-</td>' : '');
+        let response = warp::test::request()
+            .path("/api/topics/robot%2F1%2Flidar/detail")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let detail: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(detail["key_expr"], "robot/1/lidar");
 
-        const row = document.createElement('tr');
-        row.dataset.key = topicData.key_expr;
-        row.dataset.timestamp = topicData.received_timestamp;
-        row.innerHTML = `
-            <td class="topic-cell">${{topicData.key_expr}}</td>
-            <td class="size-cell">${{topicData.last_data_size_bytes}}</td>
-            <td class="freq-cell">${{topicData.estimated_hz}}</td>
-            <td class="timestamp-cell">${{timestampReadable}}</td>
-            ${{decodedContent}}
-        `;
-        tableBody.appendChild(row);
-    }}
+        let response = warp::test::request()
+            .path("/api/topics/robot%2F2%2Flidar/detail")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let error: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(error["error"], "no topic 'robot/2/lidar'");
 
-    function updateRow(topicData) {{
-        const timestampReadable = new Date(topicData.received_timestamp).toISOString().replace('T', ' ').replace('Z', ' UTC');
-        let row = getRowByKey(topicData.key_expr);
+        // Unencoded, the slashes split the key into segments the route doesn't match
+        let response = warp::test::request()
+            .path("/api/topics/robot/1/lidar/detail")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
-        if (row) {{
-            row.querySelector('.size-cell').textContent = topicData.last_data_size_bytes ? topicData.last_data_size_bytes.toFixed(2) : "-";
-            row.querySelector('.freq-cell').textContent = topicData.estimated_hz ? topicData.estimated_hz.toFixed(2) : "-";
-            row.querySelector('.timestamp-cell').textContent = timestampReadable;
-            row.dataset.timestamp = topicData.received_timestamp;
+        let response = warp::test::request()
+            .path("/api/topics/%FF/detail")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-            if (hasDecoder) {{
-                const decodedCell = row.querySelector('.decoded-cell');
-                if (decodedCell) decodedCell.innerHTML = topicData.decoded_content || '-';
-            }}
+    #[test]
+    fn malformed_zenoh_settings_are_errors() {
+        let error =
+            |mode, endpoints: &[&str], namespace| match zenoh_config(mode, endpoints, namespace) {
+                Ok(_) => panic!("accepted {} {:?} {:?}", mode, endpoints, namespace),
+                Err(e) => e,
+            };
+        assert_eq!(
+            error("server", &["tcp/localhost:7447"], None),
+            "invalid zenoh_mode 'server', expected peer, client or router"
+        );
+        assert_eq!(
+            error("", &[], None),
+            "invalid zenoh_mode '', expected peer, client or router"
+        );
+        for endpoint in ["localhost:7447", "tcp/", "", "tcp", "/localhost:7447"] {
+            let message = error("client", &["tcp/localhost:7447", endpoint], None);
+            assert!(
+                message.starts_with(&format!(
+                    "invalid zenoh_connect_endpoints entry '{}': ",
+                    endpoint
+                )),
+                "{}",
+                message
+            );
+        }
+        for namespace in ["robots/*", "robots//1", ""] {
+            let message = error("client", &["tcp/localhost:7447"], Some(namespace));
+            assert!(
+                message.starts_with("cannot set zenoh_namespace to "),
+                "{}",
+                message
+            );
+        }
+        let missing = Config {
+            zenoh_config_file: Some("/nonexistent/zenoh.json5".to_string()),
+            ..Config::default()
+        };
+        assert!(
+            missing
+                .zenoh()
+                .unwrap_err()
+                .starts_with("invalid zenoh config file /nonexistent/zenoh.json5: ")
+        );
+    }
+
+    #[test]
+    fn well_formed_zenoh_settings_build_a_config() {
+        let config = zenoh_config(
+            "client",
+            &["tcp/10.0.0.1:7447", "udp/[::1]:7447"],
+            Some("fleet/robot1"),
+        )
+        .unwrap();
+        assert_eq!(config.get_json("mode").unwrap(), r#""client""#);
+        assert_eq!(
+            config.get_json("connect/endpoints").unwrap(),
+            r#"["tcp/10.0.0.1:7447","udp/[::1]:7447"]"#
+        );
+    }
 
-            row.classList.add('updated-row');
-            setTimeout(() => row.classList.remove('updated-row'), 500);
+    #[test]
+    fn page_escapes_server_strings_in_cells_and_titles() {
+        let page = generate_html(&Config::default(), Column::ALL, "");
+        // Quotes too, since titles are attributes
+        assert!(page.contains(
+            r#"{ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }"#
+        ));
+        for escaped in [
+            r#"title="${escapeHtml(topicData.key_expr)}">${escapeHtml(displayKey(topicData.key_expr))}"#,
+            "field('Key', escapeHtml(detail.key_expr))",
+            "field('Decoder', escapeHtml(detail.decoded_by || '-'))",
+            "Decoded by ${escapeHtml(topicData.decoded_by)}",
+            r#"<td class="instance-cell">${escapeHtml(topicData.instance || 'local')}</td>"#,
+        ] {
+            assert!(page.contains(escaped), "{} missing", escaped);
+        }
+    }
 
-            if (sortMode === 'timestamp') {{
-                row.remove();
-                insertRowInOrder(row, topicData);
+    /// The page script's function `name`, through its matching closing brace
+    fn page_function<'a>(page: &'a str, name: &str) -> &'a str {
+        let start = page
+            .find(&format!("function {}(", name))
+            .unwrap_or_else(|| panic!("no function {}", name));
+        let mut depth = 0;
+        for (offset, c) in page[start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 1 => return &page[start..=start + offset],
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        panic!("function {} never closes", name)
+    }
+
+    /// Applies a mixed delta to the page's own `applyFilter`, `removeRow` and
+    /// `checkFilteredCount` over a minimal table, in Node; run with
+    /// `cargo test -- --ignored` where Node is installed
+    #[test]
+    #[ignore = "needs node"]
+    fn filtered_count_matches_the_visible_rows_after_a_mixed_delta() {
+        let page = generate_html(&Config::default(), Column::ALL, "");
+        let script = format!(
+            r#"
+            const rows = [];
+            class Row {{
+                constructor(key) {{
+                    this.dataset = {{ key }};
+                    this.style = {{ display: '' }};
+                    this.classList = {{ toggle() {{}} }};
+                }}
+                querySelector() {{ return null; }}
+                remove() {{ rows.splice(rows.indexOf(this), 1); }}
             }}
-        }} else {{
-            createAndInsertRow(topicData);
+            const tableBody = {{
+                querySelectorAll: () => [...rows],
+                appendChild: row => rows.push(row),
+            }};
+            const filterInput = {{ value: 'lidar' }};
+            const filteredCount = {{ textContent: '' }};
+            const showMutedButton = {{ textContent: '' }};
+            const mutedTopics = new Set(['robot/3/lidar']);
+            const alertStates = new Map();
+            const watchlist = [];
+            const searchContent = false;
+            const showMuted = false;
+            const topics = new Map();
+            function placeDetailRows() {{}}
+            console.assert = (ok, message) => {{ if (!ok) throw new Error(message); }};
+            {apply_filter}
+            {remove_row}
+            {check_filtered_count}
 
-            if (sortMode === 'alphabetical') {{
-                row = getRowByKey(topicData.key_expr);
-                if (row) {{
-                    row.remove();
-                    insertRowInOrder(row, topicData);
+            function add(key) {{
+                topics.set(key, {{ key_expr: key }});
+                tableBody.appendChild(new Row(key));
+            }}
+            function expectCount(expected) {{
+                applyFilter();
+                checkFilteredCount();
+                if (filteredCount.textContent !== expected) {{
+                    throw new Error(`"${{filteredCount.textContent}}" instead of "${{expected}}"`);
                 }}
             }}
-        }}
 
-        // Reapply filter so new/updated row respects search
-        applyFilter();
-    }}
+            ['robot/1/lidar', 'robot/2/lidar', 'robot/3/lidar', 'robot/1/camera'].forEach(add);
+            expectCount('2 Topics');
+            // One delta: removals, an addition and a muted topic unmuted
+            ['robot/1/lidar', 'robot/1/camera'].forEach(key => {{
+                topics.delete(key);
+                removeRow(key);
+            }});
+            add('robot/4/lidar');
+            mutedTopics.delete('robot/3/lidar');
+            expectCount('3 Topics');
 
-    function insertRowInOrder(row, topicData) {{
-        const existingRows = tableBody.querySelectorAll('tr');
-        let inserted = false;
+            // A row left behind by a removal is caught
+            topics.delete('robot/2/lidar');
+            let caught = false;
+            try {{ expectCount('2 Topics'); }} catch (e) {{ caught = true; }}
+            if (!caught) throw new Error('a stale row went unnoticed');
+            "#,
+            apply_filter = page_function(&page, "applyFilter"),
+            remove_row = page_function(&page, "removeRow"),
+            check_filtered_count = page_function(&page, "checkFilteredCount"),
+        );
+        let output = std::process::Command::new("node")
+            .args(["-e", &script])
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run node: {}", e));
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
 
-        for (const existingRow of existingRows) {{
-            let shouldInsertBefore = false;
+    #[test]
+    fn size_delta_is_relative_to_the_previous_payload() {
+        assert_eq!(size_delta(None, 512), (0, 0.0));
+        assert_eq!(size_delta(Some(100), 150), (50, 50.0));
+        assert_eq!(size_delta(Some(100), 25), (-75, -75.0));
+        assert_eq!(size_delta(Some(100), 100), (0, 0.0));
+        // An empty previous payload counts as one byte
+        assert_eq!(size_delta(Some(0), 10), (10, 1000.0));
+        assert_eq!(size_delta(Some(0), 0), (0, 0.0));
+        assert_eq!(size_delta(Some(10), 0), (-10, -100.0));
+    }
 
-            if (sortMode === 'alphabetical') {{
-                const existingTopic = existingRow.querySelector('.topic-cell').textContent;
-                shouldInsertBefore = topicData.key_expr.localeCompare(existingTopic) < 0;
-            }} else {{
-                const existingTimestamp = parseInt(existingRow.dataset.timestamp || '0', 10);
-                shouldInsertBefore = topicData.received_timestamp > existingTimestamp;
-            }}
+    #[tokio::test]
+    async fn size_delta_only_travels_with_a_new_sample() {
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        let mut tracker = delta_tracker(&tombstones);
+        let sized = |time: u64, previous: Option<u64>, bytes: u64| {
+            let mut topic = churned_topic("robot/lidar", time);
+            (topic.data.size_delta_bytes, topic.data.size_delta_pct) = size_delta(previous, bytes);
+            topic.data.last_data_size_bytes = bytes;
+            topic
+        };
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), sized(1, Some(100), 150));
+        let first = tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await
+            .unwrap();
+        assert_eq!(first.updated[0].size_delta_bytes, 50);
 
-            if (shouldInsertBefore) {{
-                tableBody.insertBefore(row, existingRow);
-                inserted = true;
-                break;
-            }}
-        }}
+        // The delta stays until the next sample, without resending the row
+        assert!(
+            tracker
+                .next(CacheSnapshot::of(&cache, &tombstones).await)
+                .await
+                .is_none()
+        );
 
-        if (!inserted) {{
-            tableBody.appendChild(row);
-        }}
-    }}
+        // A same-sized sample goes out once, with its delta back to zero
+        cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), sized(2, Some(150), 150));
+        let next = tracker
+            .next(CacheSnapshot::of(&cache, &tombstones).await)
+            .await
+            .unwrap();
+        assert_eq!(next.updated.len(), 1);
+        assert_eq!(next.updated[0].size_delta_bytes, 0);
+        assert_eq!(next.updated[0].size_delta_pct, 0.0);
+        assert!(
+            tracker
+                .next(CacheSnapshot::of(&cache, &tombstones).await)
+                .await
+                .is_none()
+        );
+    }
 
-    function removeRow(topicKey) {{
-        const row = getRowByKey(topicKey);
-        if (row) row.remove();
-        applyFilter();
-    }}
+    #[tokio::test]
+    async fn sse_stream_paces_at_the_reload_period_under_constant_change() {
+        use futures::StreamExt;
 
-    function toggleSort() {{
-        if (sortMode === 'alphabetical') {{
-            sortMode = 'timestamp';
-            sortButton.textContent = 'Sort: Most Recent First';
-        }} else {{
-            sortMode = 'alphabetical';
-            sortButton.textContent = 'Sort: Alphabetical';
-        }}
+        const PERIOD_MS: u64 = 100;
+        let cache: TopicCache = Arc::default();
+        let tombstones = Tombstones::default();
+        let (ticks, receiver) = watch::channel(CacheSnapshot::default());
+        let ticker = tokio::spawn(run_delta_ticker(
+            cache.clone(),
+            tombstones.clone(),
+            ticks,
+            PERIOD_MS,
+        ));
+        // A new sample every few milliseconds, far faster than the period
+        let writer = tokio::spawn({
+            let cache = cache.clone();
+            async move {
+                for time in 1.. {
+                    cache.write().await.insert(
+                        "robot/lidar".to_string(),
+                        churned_topic("robot/lidar", time),
+                    );
+                    time::sleep(Duration::from_millis(5)).await;
+                }
+            }
+        });
+        let sources = DeltaSources {
+            ticks: receiver,
+            ..delta_tracker(&tombstones).sources
+        };
+        let clients = SseClients::default();
+        let (_stop, shutdown) = watch::channel(false);
+        let mut stream = Box::pin(sse_events(sources, &clients, shutdown));
 
-        rebuildTable();
-    }}
+        let mut events = 0;
+        let deadline = time::Instant::now() + Duration::from_millis(10 * PERIOD_MS);
+        while let Ok(Some(event)) = time::timeout_at(deadline, stream.next()).await {
+            event.unwrap();
+            events += 1;
+        }
+        writer.abort();
+        ticker.abort();
+        // One update per tick over ten periods, give or take the first
+        assert!(
+            (8..=12).contains(&events),
+            "{} events in ten periods",
+            events
+        );
+    }
 
-    function decayFrequencies() {{
-        const now = Date.now();
-        const rows = tableBody.querySelectorAll('tr');
-        rows.forEach(row => {{
-            const freqCell = row.querySelector('.freq-cell');
-            const lastTs = parseInt(row.dataset.timestamp || '0', 10);
-            if (freqCell && lastTs > 0) {{
-                const elapsed = now - lastTs;
-                if (elapsed > 5000) {{
-                    let currentHz = parseFloat(freqCell.textContent) || 0;
-                    // apply exponential decay factor for faster drop
-                    currentHz *= 0.5; // halve every tick (1s)
-                    freqCell.textContent = currentHz > 0.01 ? currentHz.toFixed(2) : "0.00";
-                }}
-            }}
-        }});
-    }}
-    setInterval(decayFrequencies, 1000);
+    #[test]
+    fn rounding_stops_a_steady_rate_changing_on_every_sample() {
+        let clock = ManualClock::new(1_000_000);
+        let mut window = IntervalWindow::default();
+        // 10 Hz with up to 20 µs of jitter either way
+        let mut jitter: u64 = 12_345;
+        let (mut raw_changes, mut stored_changes) = (0, 0);
+        let (mut last_raw, mut last_stored) = (None, None);
+        for message_count in 1..=200 {
+            jitter = (jitter * 1_103_515_245 + 12_345) % 2_147_483_648;
+            clock.advance_ns(100 * NANOS_PER_MS - 20_000 + jitter % 40_000);
+            let (raw, _, _) = arrive(&mut window, &clock);
+            let stored = measured_hz(message_count, raw);
+            // Counted once the window is full
+            if message_count > 20 {
+                raw_changes += usize::from(last_raw != Some(raw));
+                stored_changes += usize::from(last_stored != Some(stored));
+            }
+            (last_raw, last_stored) = (Some(raw), Some(stored));
+        }
+        assert!(raw_changes > 150, "raw rate changed {} times", raw_changes);
+        assert_eq!(stored_changes, 0);
+        assert_eq!(last_stored, Some(Some(10.0)));
+    }
 
-    // Event handlers
-    sortButton.addEventListener('click', toggleSort);
-    filterInput.addEventListener('input', applyFilter);
+    #[test]
+    fn an_invalid_configuration_exits_with_the_config_code() {
+        let config = Config {
+            zenoh_mode: "server",
+            ..Config::default()
+        };
+        let e = StartupError::Config(config.zenoh().unwrap_err());
+        assert_eq!(e.exit_code(), EXIT_CONFIG);
+        assert_eq!(
+            e.to_string(),
+            "Invalid configuration: invalid zenoh_mode 'server', expected peer, client or router"
+        );
+    }
 
-    eventSource.addEventListener("message", function(event) {{
-        try {{
-            const delta = JSON.parse(event.data);
-            const updated = delta.updated || [];
-            const removed = delta.removed || [];
+    #[test]
+    fn an_unreachable_router_exits_with_its_own_code_after_the_last_attempt() {
+        let config = Config {
+            zenoh_mode: "client",
+            zenoh_connect_endpoints: &["tcp/192.0.2.1:7447"],
+            zenoh_open_attempts: Some(3),
+            ..Config::default()
+        };
+        for open_failures in 1..3 {
+            assert!(StartupError::unreachable(&config, open_failures, "timed out").is_none());
+        }
+        let e = StartupError::unreachable(&config, 3, "timed out").unwrap();
+        assert_eq!(e.exit_code(), EXIT_ZENOH_UNREACHABLE);
+        assert_eq!(
+            e.to_string(),
+            "Could not open a Zenoh session to tcp/192.0.2.1:7447 in client mode in 3 attempts, \
+             giving up: timed out"
+        );
 
-            updated.forEach(topicData => {{
-                topics.set(topicData.key_expr, topicData);
-                updateRow(topicData);
-            }});
+        // Without a limit the watchdog keeps retrying
+        let config = Config {
+            zenoh_open_attempts: None,
+            ..config
+        };
+        assert!(StartupError::unreachable(&config, 1000, "timed out").is_none());
+    }
 
-            removed.forEach(topicKey => {{
-                topics.delete(topicKey);
-                removeRow(topicKey);
-            }});
+    /// The command line as given, over an empty settings file and environment
+    fn startup_config(args: &[&str]) -> Result<Config, String> {
+        use clap::Parser;
 
-            updateStats();
-        }} catch (error) {{
-            console.error("Error processing SSE message:", error);
-        }}
-    }});
+        let settings =
+            std::env::temp_dir().join(format!("ztm-{}-startup.toml", std::process::id()));
+        std::fs::write(&settings, "").unwrap();
+        let cli = Cli::parse_from(
+            ["monitor", "--config", settings.to_str().unwrap()]
+                .into_iter()
+                .chain(args.iter().copied()),
+        );
+        config::resolve(&cli, |_| None)
+    }
 
-    // initial render state
-    updateStats();
-    applyFilter();
-}});
-</script>
-</head>
-<body>
-<div class="header">
-    <h1>Zenoh DDS Monitor</h1>
-    <p>Real-time topic monitoring{decoder_subtitle}</p>
-</div>
-<div class="stats">
-    <div class="stat-item">
-        <span class="stat-value" id="topic-count">0</span>
-        <span class="stat-label">Topics</span>
-    </div>
+    #[test]
+    fn a_malformed_connect_flag_stops_startup_with_the_config_code() {
+        // The protocol is missing
+        let e = startup_config(&["--connect", "192.0.2.1:7447"])
+            .and_then(check_startup)
+            .map(|_| ())
+            .map_err(StartupError::Config)
+            .unwrap_err();
+        assert_eq!(e.exit_code(), EXIT_CONFIG);
+        assert!(
+            e.to_string().starts_with(
+                "Invalid configuration: invalid zenoh_connect_endpoints entry '192.0.2.1:7447'"
+            ),
+            "{}",
+            e
+        );
+    }
 
-    <div class="stat-item">
-        <button id="sort-toggle-btn" class="sort-toggle">Alphabetical</button>
-        <span class="stat-label">Sort Order</span>
-    </div>
+    #[tokio::test]
+    async fn an_unreachable_router_stops_startup_after_the_open_attempts() {
+        // Free a moment ago, so nothing listens on it
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let locator = format!("tcp/127.0.0.1:{}", port);
+        let config = startup_config(&["--mode", "client", "--connect", &locator]).unwrap();
+        let (config, session_config) = check_startup(Config {
+            zenoh_open_attempts: Some(2),
+            watchdog_backoff_ms: (10, 10),
+            ..config
+        })
+        .unwrap();
+        let (_stop, shutdown) = watch::channel(false);
 
-    <div class="stat-item">
-        <!-- Search box above the filtered count (no extra label) -->
-        <input
-            type="text"
-            id="filter-input"
-            class="filter-input"
-            placeholder="Filter topics..."
-        />
-        <span class="stat-label" id="filtered-count">0 Topics</span>
-    </div>
+        let watchdog = run_subscriber_watchdog(
+            TopicCache::default(),
+            Tombstones::default(),
+            ZenohSession::default(),
+            Arc::new(RwLock::new(SubscriberStatus::Connecting)),
+            Arc::new(watch::channel(config.subscribe_key_expr.to_string()).0),
+            &[],
+            DecodeLimiter::new(&config),
+            None,
+            Notifiers::start(&[], None),
+            Arc::new(config),
+            session_config,
+            Arc::new(SystemClock),
+            shutdown,
+        );
+        let e = time::timeout(Duration::from_secs(30), watchdog)
+            .await
+            .expect("the watchdog kept retrying")
+            .unwrap_err();
+        assert_eq!(e.exit_code(), EXIT_ZENOH_UNREACHABLE);
+        let message = e.to_string();
+        assert!(
+            message.starts_with(&format!(
+                "Could not open a Zenoh session to {} in client mode in 2 attempts, giving up: ",
+                locator
+            )),
+            "{}",
+            message
+        );
+    }
 
-    <div class="stat-item">
-        <span class="stat-value" id="last-updated-value"></span>
-        <span class="stat-label">Last Updated</span>
-    </div>
-</div>
-<div class="container">
-    <table>
-        <thead>
-            <tr>
-                <th>Topic</th>
-                <th>Message Size (B)</th>
-                <th>Frequency (Hz)</th>
-                <th>Received Timestamp</th>
-                {decoder_header}
-            </tr>
-        </thead>
-        <tbody></tbody>
-    </table>
-</div>
-<div class="refresh-info">📊 Updates every {}ms | Built with Zenoh + Rust + Warp</div>
-</body>
-</html>"#,
-        RELOAD_PERIOD_MS,
-        topic_width = topic_column_width,
-        size_width = size_column_width,
-        freq_width = freq_column_width,
-        timestamp_width = timestamp_column_width,
-        decoder_th_style = if has_decoder {
-            format!("th:nth-child(5) {{ width: {}; }}", decoder_column_width)
-        } else {
-            String::new()
-        },
-        decoder_td_style = if has_decoder {
-            format!("td:nth-child(5) {{ width: {}; }}", decoder_column_width)
-        } else {
-            String::new()
-        },
-        has_decoder_js = if has_decoder { "true" } else { "false" },
-        decoder_subtitle = if has_decoder {
-            " with custom decoder"
-        } else {
-            ""
-        },
-        decoder_header = decoder_column_header,
-    )
-}
+    #[test]
+    fn chart_windows_parse_with_units_and_cap_at_the_history() {
+        assert_eq!(parse_window("30s"), Ok(30_000));
+        assert_eq!(parse_window("10m"), Ok(600_000));
+        assert_eq!(parse_window("1500"), Ok(1500));
+        assert_eq!(parse_window("1000h"), Ok(METRIC_HISTORY_MS));
+        for window in ["", "0m", "ten", "10x", "-5s", "m"] {
+            assert_eq!(
+                parse_window(window),
+                Err(format!(
+                    "invalid window '{}', expected e.g. 30s, 10m or 1h",
+                    window
+                ))
+            );
+        }
+    }
 
-async fn sse_handler(
-    cache: TopicCache,
-    _has_decoder: bool,
-) -> Result<impl warp::Reply, warp::Rejection> {
-    let stream = futures::stream::unfold(
-        (cache, HashMap::<String, TopicData>::new()),
-        |(cache, mut last_snapshot)| async move {
-            let (updated, removed) = {
-                let mut interval = time::interval(Duration::from_millis(RELOAD_PERIOD_MS));
-                interval.tick().await;
-
-                let current_cache = cache.read().await;
-                let mut updated: Vec<TopicData> = Vec::new();
-                let mut removed: Vec<String> = Vec::new();
-
-                let current_keys: HashSet<_> = current_cache.keys().collect();
-                let last_keys: HashSet<_> = last_snapshot.keys().collect();
-
-                // for (key, value) in current_cache.iter() {
-                //     if !last_keys.contains(key) || last_snapshot.get(key) != Some(value) {
-                //         updated.push(value.clone());
-                //     }
-                // }
-
-                for (key, value) in current_cache.iter() {
-                    let changed = match last_snapshot.get(key) {
-                        Some(old) => {
-                            old.received_timestamp != value.received_timestamp || old != value
-                        }
-                        None => true,
-                    };
-                    if changed {
-                        updated.push(value.clone());
-                    }
-                }
+    #[tokio::test]
+    async fn chart_route_parses_its_query_and_draws_no_data_for_unknown_topics() {
+        let chart_cache = ChartCache::default();
+        let route = warp::path!("api" / "chart" / String)
+            .and(warp::query::<ChartQuery>())
+            .and(warp::any().map(MetricHistory::default))
+            .and(warp::any().map(move || chart_cache.clone()))
+            .and(warp::any().map(|| format::Units::Si))
+            .and_then(chart_handler);
 
-                for key in last_keys.difference(&current_keys) {
-                    removed.push(key.to_string());
-                }
+        let response = warp::test::request()
+            .path("/api/chart/robot%2Flidar.svg?metric=bandwidth&window=10m")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/svg+xml");
+        let svg = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(svg.contains(">robot/lidar bandwidth</text>"));
+        assert!(svg.contains(">no data</text>"));
 
-                last_snapshot.clear();
-                last_snapshot.extend(current_cache.clone());
+        // The metric defaults to the rate
+        let response = warp::test::request()
+            .path("/api/chart/robot%2Flidar.svg")
+            .reply(&route)
+            .await;
+        let svg = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(svg.contains(">robot/lidar rate</text>"));
 
-                (updated, removed)
-            };
+        let response = warp::test::request()
+            .path("/api/chart/robot%2Flidar.svg?window=10x")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
-            let delta = DeltaUpdate { updated, removed };
+        let response = warp::test::request()
+            .path("/api/chart/robot%2Flidar.svg?metric=latency")
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-            let event = sse::Event::default()
-                .event("message")
-                .data(serde_json::to_string(&delta).unwrap());
+    #[tokio::test]
+    async fn content_search_matches_escaped_characters() {
+        let cache: TopicCache = Arc::default();
+        for (key, decoded) in [("robot/cmp", "x<5 && y>2"), ("robot/plain", "x is 5")] {
+            let mut topic = churned_topic(key, 1);
+            topic.data.decoded_content = Some(html_escape_string(decoded));
+            cache.write().await.insert(key.to_string(), topic);
+        }
+        let route = warp::path!("api" / "topics")
+            .and(warp::any().map(move || cache.clone()))
+            .and(warp::any().map(|| DEFAULT_SORT))
+            .and(warp::query::<TopicsQuery>())
+            .and_then(topics_handler);
+        let keys = |query: &'static str| {
+            let route = route.clone();
+            async move {
+                let response = warp::test::request()
+                    .path(&format!("/api/topics?content={}", query))
+                    .reply(&route)
+                    .await;
+                let topics: Vec<serde_json::Value> =
+                    serde_json::from_slice(response.body()).unwrap();
+                topics
+                    .iter()
+                    .map(|topic| topic["key_expr"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(keys("x%3C5").await, ["robot/cmp"]);
+        assert_eq!(keys("%26%26%20Y%3E").await, ["robot/cmp"]);
+        assert_eq!(keys("x%20is").await, ["robot/plain"]);
+        // The escaped form itself is not what the payload said
+        assert!(keys("%26lt%3B").await.is_empty());
+    }
 
-            Some((Ok::<_, warp::Error>(event), (cache, last_snapshot)))
-        },
-    );
+    #[test]
+    fn decode_cache_hits_misses_and_keeps_the_most_recently_used() {
+        let mut decodes = DecodeCache::default();
+        assert_eq!(decodes.get(1), None);
+        decodes.insert(1, "hex", "one".to_string(), 3);
+        assert_eq!(decodes.get(1), Some(("hex", "one".to_string())));
+        assert_eq!(decodes.get(2), None);
 
-    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
-}
+        decodes.insert(2, "hex", "two".to_string(), 3);
+        decodes.insert(3, "hex", "three".to_string(), 3);
+        // A hit makes 1 the most recent, so 2 goes first
+        decodes.get(1);
+        decodes.insert(4, "hex", "four".to_string(), 3);
+        assert_eq!(decodes.entries.len(), 3);
+        assert_eq!(decodes.get(2), None);
+        assert!(decodes.get(1).is_some() && decodes.get(4).is_some());
 
-async fn start_web_server(cache: TopicCache, has_decoder: bool) {
-    let cache_filter = warp::any().map(move || cache.clone());
-    let decoder_filter = warp::any().map(move || has_decoder);
+        // Decoding a payload again replaces its entry rather than adding one
+        decodes.insert(4, "json", "four again".to_string(), 3);
+        assert_eq!(decodes.entries.len(), 3);
+        assert_eq!(decodes.get(4), Some(("json", "four again".to_string())));
+        assert_eq!(
+            decodes.content_bytes(),
+            "one".len() + "three".len() + "four again".len()
+        );
+    }
 
-    let index = warp::path::end()
-        .and(decoder_filter)
-        .map(|has_decoder| warp::reply::html(generate_html(has_decoder)))
-        .boxed();
+    static DECODER_RUNS: AtomicUsize = AtomicUsize::new(0);
 
-    let sse_route = warp::path("sse")
-        .and(cache_filter)
-        .and(decoder_filter)
-        .and_then(sse_handler)
-        .boxed();
+    fn counting_decoder(sample: Sample) -> Result<String, String> {
+        DECODER_RUNS.fetch_add(1, Ordering::SeqCst);
+        Ok(format!("{} bytes", sample.payload().len()))
+    }
 
-    let routes = index.or(sse_route);
+    static COUNTING_DECODERS: [NamedDecoder; 1] = [NamedDecoder::new("counting", counting_decoder)];
 
-    info!("Starting web server on http://localhost:{}", PORT);
-    warp::serve(routes).run(([127, 0, 0, 1], PORT)).await;
-}
+    /// Caches `payloads` on one topic through the subscribe loop's path, each
+    /// once the previous decode has landed, and counts the decoder's runs
+    async fn decoder_runs(cache_entries: usize, payloads: &[&[u8]]) -> (usize, u64) {
+        let topic_cache = TopicCache::default();
+        let tombstones = Tombstones::default();
+        let clock = Arc::new(ManualClock::new(1_000_000));
+        let shared_clock: SharedClock = clock.clone();
+        let config = Config {
+            decode_cache_entries: cache_entries,
+            duplicate_samples: DuplicateSamples::Keep,
+            skip_repeated_payloads: false,
+            ..Config::default()
+        };
+        let decode_limiter = DecodeLimiter::new(&config);
+        let before = DECODER_RUNS.load(Ordering::SeqCst);
+        for payload in payloads {
+            clock.advance(100);
+            let timestamp = clock.now_ms();
+            cache_sample(
+                sample("robot/lidar", payload, None),
+                &topic_cache,
+                &tombstones,
+                &COUNTING_DECODERS,
+                &decode_limiter,
+                None,
+                &config,
+                &shared_clock,
+            )
+            .await;
+            while topic_cache.read().await["robot/lidar"].decoded_timestamp != timestamp {
+                time::sleep(Duration::from_millis(1)).await;
+            }
+            assert_eq!(
+                topic_cache.read().await["robot/lidar"].decoded_content,
+                Some(format!("{} bytes", payload.len()))
+            );
+        }
+        (
+            DECODER_RUNS.load(Ordering::SeqCst) - before,
+            decode_limiter.cache_hits.load(Ordering::Relaxed),
+        )
+    }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    std::fs::create_dir_all("logs")?;
-    Ftail::new()
-        .console(LOG_LEVEL)
-        .daily_file(Path::new("logs"), LOG_LEVEL)
-        .retention_days(3)
-        .timezone(ftail::Tz::UTC)
-        .datetime_format("%Y-%m-%d_%H:%M:%S%.3f")
-        .init()
-        .unwrap_or_else(|e| {
-            error!("Failed to initialise logger: {}", e);
-            std::process::exit(1);
-        });
+    #[tokio::test]
+    async fn repeated_payloads_are_decoded_once_while_cached() {
+        let payloads: [&[u8]; 6] = [b"a", b"bb", b"a", b"bb", b"a", b"ccc"];
+        // Only each distinct payload's first sample runs the decoder
+        assert_eq!(decoder_runs(2, &payloads).await, (3, 3));
+        // Two payloads taking turns in one entry evict each other
+        assert_eq!(decoder_runs(1, &payloads).await, (6, 0));
+        assert_eq!(decoder_runs(0, &payloads).await, (6, 0));
+    }
 
-    info!("Starting Zenoh DDS Web Monitor...");
+    #[tokio::test]
+    async fn shedding_load_drops_every_decode_cache() {
+        let topic_cache = TopicCache::default();
+        let mut topic = churned_topic("robot/lidar", get_timestamp());
+        topic.pinned = true;
+        topic.decodes.insert(1, "hex", "x".repeat(4096), 8);
+        topic_cache
+            .write()
+            .await
+            .insert("robot/lidar".to_string(), topic);
+        let decode_limiter = DecodeLimiter::new(&Config {
+            decode_cache_entries: 8,
+            ..Config::default()
+        });
 
-    // Determine if decoder should be used
-    // Change this to Some(decoder) to enable the custom decoder
-    let custom_decoder: DecoderFn = DECODER; // or Some(decoder)
-    let has_decoder = custom_decoder.is_some();
+        let guard = tokio::spawn(memory::run_memory_guard(
+            topic_cache.clone(),
+            Tombstones::default(),
+            decode_limiter.clone(),
+            1024,
+            10,
+        ));
+        while !decode_limiter.suspended.load(Ordering::Relaxed) {
+            time::sleep(Duration::from_millis(1)).await;
+        }
+        guard.abort();
+        let cache = topic_cache.read().await;
+        assert!(cache["robot/lidar"].decodes.entries.is_empty());
+    }
 
-    if has_decoder {
-        info!("Custom decoder enabled");
-    } else {
-        info!("Running in standard mode (no custom decoder)");
+    fn echo_decoder(sample: Sample) -> Result<String, String> {
+        Ok(String::from_utf8_lossy(&sample.payload().to_bytes()).into_owned())
     }
 
-    let topic_cache: TopicCache = Arc::new(RwLock::new(HashMap::new()));
-    let interval_history: IntervalHistory = Arc::new(RwLock::new(HashMap::new()));
+    static ECHO_DECODERS: [NamedDecoder; 1] = [NamedDecoder::new("echo", echo_decoder)];
 
-    tokio::spawn({
-        let cache_clone = topic_cache.clone();
-        async move {
-            if let Err(e) =
-                start_zenoh_subscriber(cache_clone, interval_history, custom_decoder).await
-            {
-                error!("Zenoh subscriber error: {}", e);
-            }
+    #[tokio::test]
+    async fn hostile_keys_and_payloads_stay_inert_and_escaped_once() {
+        use futures::StreamExt;
+
+        let key = r#"robot/<script>alert(1)</script>/"onerror=alert(1)"#;
+        let payload = "<img src=x onerror=\"alert('x')\">&amp;\nevent: shutdown\n\ndata: {}";
+        let escaped =
+            "&lt;img src=x onerror=\"alert('x')\"&gt;&amp;amp;\nevent: shutdown\n\ndata: {}";
+
+        let topic_cache = TopicCache::default();
+        let tombstones = Tombstones::default();
+        let clock: SharedClock = Arc::new(ManualClock::new(1_000_000));
+        cache_sample(
+            sample(key, payload.as_bytes(), None),
+            &topic_cache,
+            &tombstones,
+            &ECHO_DECODERS,
+            &DecodeLimiter::new(&Config::default()),
+            None,
+            &Config::default(),
+            &clock,
+        )
+        .await;
+        while topic_cache.read().await[key].decoded_content.is_none() {
+            time::sleep(Duration::from_millis(1)).await;
         }
-    });
 
-    tokio::spawn(start_web_server(topic_cache.clone(), has_decoder));
+        // The key travels as sent, for the page to escape; the decoded
+        // content is escaped once, as text
+        let row = serde_json::to_string(&topic_cache.read().await[key].data).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&row).unwrap();
+        assert_eq!(parsed["key_expr"], key);
+        assert_eq!(parsed["decoded_content"], escaped);
+        assert!(!row.contains("<img") && !row.contains("&amp;lt;"));
+        assert!(!row.contains('\n'));
 
-    tokio::signal::ctrl_c().await?;
+        let (_ticks, receiver) = watch::channel(CacheSnapshot::of(&topic_cache, &tombstones).await);
+        let sources = DeltaSources {
+            ticks: receiver,
+            ..delta_tracker(&tombstones).sources
+        };
+        let (_stop, shutdown) = watch::channel(false);
+        let mut stream = Box::pin(sse_events(sources, &SseClients::default(), shutdown));
+        let event = stream.next().await.unwrap().unwrap();
+        // One data line, so the payload's own "event:" and "data:" lines are just text
+        let data = sse_data(&event);
+        assert!(!event.to_string().contains("event:shutdown"));
+        let update: serde_json::Value = serde_json::from_str(&data).unwrap();
+        assert_eq!(update["updated"][0]["key_expr"], key);
+        assert_eq!(update["updated"][0]["decoded_content"], escaped);
+        assert!(!data.contains("<img") && !data.contains("&amp;lt;"));
+    }
 
-    warn!("Zenoh DDS Web Monitor stopping.");
+    #[tokio::test]
+    async fn a_steady_topic_sends_its_age_instead_of_its_row() {
+        const TICKS: u64 = 50;
+        let topic_cache = TopicCache::default();
+        let tombstones = Tombstones::default();
+        let clock = Arc::new(ManualClock::new(1_000_000));
+        let shared_clock: SharedClock = clock.clone();
+        let config = Config {
+            duplicate_samples: DuplicateSamples::Keep,
+            ..Config::default()
+        };
+        let decode_limiter = DecodeLimiter::new(&config);
+        let mut tracker = delta_tracker(&tombstones);
 
-    Ok(())
+        // 10 Hz with a new payload of the same size every time, one sample per tick
+        let mut rows = Vec::new();
+        for n in 0..TICKS {
+            clock.advance(100);
+            let payload = format!("scan {:03}", n);
+            cache_sample(
+                sample("robot/lidar", payload.as_bytes(), None),
+                &topic_cache,
+                &tombstones,
+                &[],
+                &decode_limiter,
+                None,
+                &config,
+                &shared_clock,
+            )
+            .await;
+            let update = tracker
+                .next(CacheSnapshot::of(&topic_cache, &tombstones).await)
+                .await
+                .unwrap();
+            if update.updated.is_empty() {
+                assert!(update.ages.contains_key("robot/lidar"), "tick {}", n);
+            }
+            rows.push(update.updated.len());
+        }
+        let resent: usize = rows.iter().sum();
+        assert!(
+            resent < 10,
+            "{} rows in {} ticks: {:?}",
+            resent,
+            TICKS,
+            rows
+        );
+        // Once the rate settles, nothing but the age moves
+        assert!(
+            rows[TICKS as usize / 2..].iter().all(|&rows| rows == 0),
+            "{:?}",
+            rows
+        );
+    }
 }
@@ -0,0 +1,110 @@
+use log::{info, warn};
+use std::sync::atomic::Ordering;
+use tokio::time::{self, Duration};
+
+use crate::*;
+
+/// Fraction of the limit at which load shedding starts
+const PRESSURE_FRACTION: f64 = 0.8;
+/// Under pressure, topics silent for this long are evicted
+const PRESSURE_STALE_MS: u64 = 30_000;
+
+/// Rough heap and inline size of one topic's cache entry, interval window and
+/// fingerprints included
+fn estimate_topic_bytes(topic: &TopicState) -> usize {
+    size_of::<TopicState>()
+        // The key is stored as the map key and in the struct
+        + 2 * topic.key_expr.len()
+        + topic.decoded_content.as_ref().map_or(0, String::len)
+        + topic.last_decode_error.as_ref().map_or(0, String::len)
+        + topic.highlight.as_ref().map_or(0, String::len)
+        + topic.size_display.len()
+        + topic.hz_display.len()
+        + topic.bandwidth_display.len()
+        + topic.intervals.intervals.len() * size_of::<(u64, u64)>()
+        + topic.decodes.entries.len() * size_of::<(u64, &str, String)>()
+        + topic.decodes.content_bytes()
+        + topic.seen.entries.len() * size_of::<(Fingerprint, u64)>()
+}
+
+/// Watches the estimated cache size and sheds load as it nears `limit_bytes`.
+///
+/// Above `PRESSURE_FRACTION` of the limit, decoding is suspended, decoded
+/// content is dropped and topics silent for `PRESSURE_STALE_MS` are evicted.
+/// Above the limit itself, the least recently updated topics are evicted
+/// until the estimate is back under the pressure threshold. Pinned topics
+/// are exempt from both.
+pub async fn run_memory_guard(
+    topic_cache: TopicCache,
+    tombstones: Tombstones,
+    decode_limiter: DecodeLimiter,
+    limit_bytes: usize,
+    period_ms: u64,
+) {
+    let threshold = (limit_bytes as f64 * PRESSURE_FRACTION) as usize;
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    loop {
+        interval.tick().await;
+
+        let mut cache = topic_cache.write().await;
+        let mut estimate: usize = cache.values().map(estimate_topic_bytes).sum();
+
+        let under_pressure = estimate >= threshold;
+        let was_shedding = decode_limiter
+            .suspended
+            .swap(under_pressure, Ordering::Relaxed);
+        if !under_pressure {
+            if was_shedding {
+                info!(
+                    "Memory estimate {} bytes is below {} bytes, no longer shedding load",
+                    estimate, threshold
+                );
+            }
+            continue;
+        }
+        if !was_shedding {
+            warn!(
+                "Memory estimate {} bytes is near the {} byte limit, shedding load",
+                estimate, limit_bytes
+            );
+        }
+
+        let over_limit = estimate >= limit_bytes;
+        for topic in cache.values_mut() {
+            if let Some(content) = topic.decoded_content.take() {
+                estimate = estimate.saturating_sub(content.len());
+            }
+            estimate = estimate.saturating_sub(topic.decodes.content_bytes());
+            topic.decodes = DecodeCache::default();
+        }
+
+        let now = get_timestamp();
+        // Pinned topics are never evicted
+        let mut by_age: Vec<(u64, String)> = cache
+            .values()
+            .filter(|topic| !topic.pinned)
+            .map(|topic| (topic.received_timestamp, topic.key_expr.clone()))
+            .collect();
+        by_age.sort();
+
+        let mut evicted = 0;
+        let mut tombstones = tombstones.write().await;
+        for (received_timestamp, key_expr) in by_age {
+            let stale = now.saturating_sub(received_timestamp) >= PRESSURE_STALE_MS;
+            // Oldest first, so once a fresh topic may stay, every later one may too
+            if !stale && (!over_limit || estimate < threshold) {
+                break;
+            }
+            if let Some(topic) = remove_topic(&mut cache, &mut tombstones, &key_expr, now) {
+                estimate = estimate.saturating_sub(estimate_topic_bytes(&topic));
+            }
+            evicted += 1;
+        }
+        if evicted > 0 {
+            warn!(
+                "Evicted {} topic(s) under memory pressure, estimate now {} bytes",
+                evicted, estimate
+            );
+        }
+    }
+}
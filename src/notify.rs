@@ -0,0 +1,858 @@
+use log::{debug, error, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{RwLock, Semaphore, mpsc};
+use tokio::time::{self, Duration, timeout};
+use zenoh::key_expr::keyexpr;
+
+use crate::alerts::{AlertTransition, Severity, Transition};
+use crate::config::redacted;
+use crate::{Tombstones, TopicCache, get_timestamp};
+
+/// Alerts queued per destination before new ones are dropped
+const QUEUE_CAPACITY: usize = 256;
+/// Timeout for a single HTTP notification
+const HTTP_TIMEOUT_MS: u64 = 10_000;
+/// Version of the JSON event log schema, bumped on incompatible field changes
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A significant monitor event, delivered to the destinations whose `events`
+/// list its type
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    AlertTransition(AlertTransition),
+    TopicAppeared {
+        key_expr: String,
+        /// The topic had been removed earlier this session, e.g. by eviction
+        reappeared: bool,
+        /// When it was last removed, for a reappeared topic
+        #[serde(skip_serializing_if = "Option::is_none")]
+        removed_at: Option<u64>,
+        timestamp: u64,
+    },
+    TopicRemoved {
+        key_expr: String,
+        timestamp: u64,
+    },
+    SubscriberRestarting {
+        restarts: u32,
+        retry_at: u64,
+        timestamp: u64,
+    },
+}
+
+/// The kinds of `Event`, for choosing which ones a destination receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    AlertTransition,
+    TopicAppeared,
+    TopicRemoved,
+    SubscriberRestarting,
+}
+
+impl EventType {
+    #[allow(dead_code)]
+    pub const ALL: &[EventType] = &[
+        EventType::AlertTransition,
+        EventType::TopicAppeared,
+        EventType::TopicRemoved,
+        EventType::SubscriberRestarting,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            EventType::AlertTransition => "Alert",
+            EventType::TopicAppeared => "Topic appeared",
+            EventType::TopicRemoved => "Topic removed",
+            EventType::SubscriberRestarting => "Subscriber restarting",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EventType::AlertTransition => "alert_transition",
+            EventType::TopicAppeared => "topic_appeared",
+            EventType::TopicRemoved => "topic_removed",
+            EventType::SubscriberRestarting => "subscriber_restarting",
+        }
+    }
+}
+
+impl Event {
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Event::AlertTransition(_) => EventType::AlertTransition,
+            Event::TopicAppeared { .. } => EventType::TopicAppeared,
+            Event::TopicRemoved { .. } => EventType::TopicRemoved,
+            Event::SubscriberRestarting { .. } => EventType::SubscriberRestarting,
+        }
+    }
+
+    /// One line describing an event other than an alert transition, which
+    /// destinations render through their templates instead
+    fn summary(&self, instance: Option<&str>) -> String {
+        let text = match self {
+            Event::AlertTransition(t) => {
+                format!(
+                    "{} on {} is {}",
+                    t.rule.name(),
+                    t.key_expr,
+                    state_name(t.transition)
+                )
+            }
+            Event::TopicAppeared {
+                key_expr,
+                reappeared,
+                ..
+            } => format!(
+                "Topic {} {}",
+                key_expr,
+                if *reappeared {
+                    "reappeared"
+                } else {
+                    "appeared"
+                }
+            ),
+            Event::TopicRemoved { key_expr, .. } => format!("Topic {} was removed", key_expr),
+            Event::SubscriberRestarting { restarts, .. } => {
+                format!("Zenoh subscriber restarting (restart {})", restarts)
+            }
+        };
+        match instance {
+            Some(instance) => format!("[{}] {}", instance, text),
+            None => text,
+        }
+    }
+
+    fn key_expr(&self) -> Option<&str> {
+        match self {
+            Event::AlertTransition(t) => Some(&t.key_expr),
+            Event::TopicAppeared { key_expr, .. } | Event::TopicRemoved { key_expr, .. } => {
+                Some(key_expr)
+            }
+            Event::SubscriberRestarting { .. } => None,
+        }
+    }
+}
+
+/// One line of the JSON event log
+#[derive(Serialize)]
+struct EventRecord<'a> {
+    schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<&'static str>,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Where and how a notification is delivered.
+///
+/// Templates may use `{rule}`, `{topic}`, `{state}`, `{value}`, `{severity}`, `{error}`
+/// (the topic's last decode error, if any) and `{instance}` (`INSTANCE_NAME`).
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierKind {
+    /// ntfy.sh-style publish: the message is POSTed as plain text to `url`
+    Ntfy {
+        #[serde(serialize_with = "redacted")]
+        url: &'static str,
+        title_template: &'static str,
+        message_template: &'static str,
+    },
+    /// Slack incoming webhook: `{"text": ...}` is POSTed to `webhook_url`
+    Slack {
+        #[serde(serialize_with = "redacted")]
+        webhook_url: &'static str,
+        template: &'static str,
+    },
+    /// Runs `program` with the alert JSON on stdin
+    Exec {
+        program: &'static str,
+        args: &'static [&'static str],
+        timeout_ms: u64,
+        max_concurrent: usize,
+    },
+    /// Writes every event as one JSON object per line to `path` (stdout if `None`),
+    /// rotating the file at `max_file_bytes` and keeping `max_files` rotated files
+    JsonLog {
+        path: Option<&'static str>,
+        max_file_bytes: u64,
+        max_files: usize,
+    },
+}
+
+/// A notification destination and the events it should receive
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct NotifierConfig {
+    pub name: &'static str,
+    pub kind: NotifierKind,
+    /// Event types delivered, e.g. `&[EventType::AlertTransition]`, or
+    /// `EventType::ALL` for a log sink
+    pub events: &'static [EventType],
+    /// Only alerts of at least this severity are delivered
+    pub min_severity: Severity,
+    /// Only events on topics matching this key expression are delivered
+    pub key_expr: &'static str,
+}
+
+/// Delivery counters for one destination, as reported by `/api/notifications`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DestinationStatus {
+    pub sent: u64,
+    pub failed: u64,
+    /// Alerts dropped because the destination's queue was full
+    pub dropped: u64,
+    pub last_success: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DestinationReport {
+    pub name: &'static str,
+    pub kind: NotifierKind,
+    #[serde(flatten)]
+    pub status: DestinationStatus,
+}
+
+struct Destination {
+    config: NotifierConfig,
+    queue: mpsc::Sender<Event>,
+    status: Arc<RwLock<DestinationStatus>>,
+}
+
+/// Fans events out to every configured destination.
+/// Each destination has its own queue and worker, so a slow or failing one
+/// never delays the others.
+pub struct Notifiers {
+    destinations: Vec<Destination>,
+}
+
+pub type NotifierHub = Arc<Notifiers>;
+
+impl Notifiers {
+    /// Spawns one delivery worker per valid destination; `instance` prefixes
+    /// every summary
+    pub fn start(configs: &[NotifierConfig], instance: Option<&'static str>) -> NotifierHub {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(HTTP_TIMEOUT_MS))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!("Failed to build HTTP client, using defaults: {}", e);
+                reqwest::Client::new()
+            });
+
+        let destinations = configs
+            .iter()
+            .filter(|config| {
+                let valid = keyexpr::new(config.key_expr).is_ok();
+                if !valid {
+                    warn!(
+                        "Ignoring notifier '{}' with invalid key expression '{}'",
+                        config.name, config.key_expr
+                    );
+                }
+                valid
+            })
+            .map(|config| {
+                let (queue, rx) = mpsc::channel(QUEUE_CAPACITY);
+                let status = Arc::new(RwLock::new(DestinationStatus::default()));
+                tokio::spawn(run_destination(
+                    *config,
+                    rx,
+                    status.clone(),
+                    client.clone(),
+                    instance,
+                ));
+                Destination {
+                    config: *config,
+                    queue,
+                    status,
+                }
+            })
+            .collect();
+
+        Arc::new(Self { destinations })
+    }
+
+    /// Queues the event on every destination whose filters match
+    pub async fn dispatch(&self, event: &Event) {
+        for destination in &self.destinations {
+            if !destination.accepts(event) {
+                continue;
+            }
+            if destination.queue.try_send(event.clone()).is_err() {
+                warn!(
+                    "Notifier '{}' queue full, dropping event for '{}'",
+                    destination.config.name,
+                    event.key_expr().unwrap_or("-")
+                );
+                destination.status.write().await.dropped += 1;
+            }
+        }
+    }
+
+    pub async fn report(&self) -> Vec<DestinationReport> {
+        let mut reports = Vec::with_capacity(self.destinations.len());
+        for destination in &self.destinations {
+            reports.push(DestinationReport {
+                name: destination.config.name,
+                kind: destination.config.kind,
+                status: destination.status.read().await.clone(),
+            });
+        }
+        reports
+    }
+}
+
+impl Destination {
+    fn accepts(&self, event: &Event) -> bool {
+        let wanted = self.config.events.contains(&event.event_type())
+            && match event {
+                Event::AlertTransition(t) => t.rule.severity() >= self.config.min_severity,
+                _ => true,
+            };
+        wanted
+            && event.key_expr().is_none_or(|key| {
+                match (keyexpr::new(self.config.key_expr), keyexpr::new(key)) {
+                    (Ok(pattern), Ok(key)) => pattern.includes(key),
+                    _ => false,
+                }
+            })
+    }
+}
+
+/// An alert through `template`, or any other event as its summary
+fn render(template: &str, event: &Event, instance: Option<&str>) -> String {
+    match event {
+        Event::AlertTransition(t) => render_template(template, t),
+        _ => event.summary(instance),
+    }
+}
+
+/// Fills in the `{placeholders}` of a message template
+fn render_template(template: &str, t: &AlertTransition) -> String {
+    template
+        .replace("{rule}", t.rule.name())
+        .replace("{topic}", &t.key_expr)
+        .replace("{state}", state_name(t.transition))
+        .replace("{value}", &format!("{:.2}", t.value))
+        .replace("{severity}", t.rule.severity().as_str())
+        .replace("{instance}", t.instance.unwrap_or(""))
+        .replace(
+            "{error}",
+            t.stats
+                .as_ref()
+                .and_then(|stats| stats.last_decode_error.as_deref())
+                .unwrap_or(""),
+        )
+}
+
+fn state_name(transition: Transition) -> &'static str {
+    match transition {
+        Transition::Fired => "firing",
+        Transition::Resolved => "ok",
+    }
+}
+
+async fn run_destination(
+    config: NotifierConfig,
+    mut rx: mpsc::Receiver<Event>,
+    status: Arc<RwLock<DestinationStatus>>,
+    client: reqwest::Client,
+    instance: Option<&'static str>,
+) {
+    if let NotifierKind::JsonLog {
+        path,
+        max_file_bytes,
+        max_files,
+    } = config.kind
+    {
+        let mut writer = JsonLogWriter::new(path, max_file_bytes, max_files, instance);
+        while let Some(event) = rx.recv().await {
+            let result = writer.write(&event).await;
+            record(&config, &status, result).await;
+        }
+        return;
+    }
+
+    // Exec notifiers run concurrently up to their limit; HTTP ones are sent in order
+    let exec_permits = match config.kind {
+        NotifierKind::Exec { max_concurrent, .. } => {
+            Some(Arc::new(Semaphore::new(max_concurrent.max(1))))
+        }
+        _ => None,
+    };
+
+    while let Some(event) = rx.recv().await {
+        match &exec_permits {
+            Some(permits) => {
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    break;
+                };
+                let status = status.clone();
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let result = send(&config, &client, &event, instance).await;
+                    record(&config, &status, result).await;
+                    drop(permit);
+                });
+            }
+            None => {
+                let result = send(&config, &client, &event, instance).await;
+                record(&config, &status, result).await;
+            }
+        }
+    }
+}
+
+async fn record(
+    config: &NotifierConfig,
+    status: &RwLock<DestinationStatus>,
+    result: Result<(), String>,
+) {
+    let mut status = status.write().await;
+    match result {
+        Ok(()) => {
+            debug!("Notifier '{}' delivered event", config.name);
+            status.sent += 1;
+            status.last_success = Some(get_timestamp());
+        }
+        Err(e) => {
+            error!("Notifier '{}' failed: {}", config.name, e);
+            status.failed += 1;
+            status.last_error = Some(e);
+            status.last_error_at = Some(get_timestamp());
+        }
+    }
+}
+
+async fn send(
+    config: &NotifierConfig,
+    client: &reqwest::Client,
+    event: &Event,
+    instance: Option<&str>,
+) -> Result<(), String> {
+    match config.kind {
+        NotifierKind::Ntfy {
+            url,
+            title_template,
+            message_template,
+        } => {
+            let (title, priority, tag) = match event {
+                Event::AlertTransition(t) => (
+                    render_template(title_template, t),
+                    match t.rule.severity() {
+                        Severity::Info => "default",
+                        Severity::Warning => "high",
+                        Severity::Critical => "urgent",
+                    },
+                    state_name(t.transition),
+                ),
+                _ => (
+                    event.event_type().title().to_string(),
+                    "default",
+                    event.event_type().as_str(),
+                ),
+            };
+            let response = client
+                .post(url)
+                .header("Title", title)
+                .header("Priority", priority)
+                .header("Tags", tag)
+                .body(render(message_template, event, instance))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            check_status(response)
+        }
+        NotifierKind::Slack {
+            webhook_url,
+            template,
+        } => {
+            let response = client
+                .post(webhook_url)
+                .json(&serde_json::json!({ "text": render(template, event, instance) }))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            check_status(response)
+        }
+        NotifierKind::Exec {
+            program,
+            args,
+            timeout_ms,
+            ..
+        } => {
+            // Alerts keep their original shape; other events carry their `type`
+            let payload = match event {
+                Event::AlertTransition(t) => serde_json::to_vec(t),
+                _ => serde_json::to_vec(event),
+            }
+            .map_err(|e| e.to_string())?;
+            let mut child = tokio::process::Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| format!("failed to run '{}': {}", program, e))?;
+
+            let run = async {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(&payload).await?;
+                }
+                child.wait().await
+            };
+
+            match timeout(Duration::from_millis(timeout_ms), run).await {
+                Ok(Ok(exit)) if exit.success() => Ok(()),
+                Ok(Ok(exit)) => Err(format!("'{}' exited with {}", program, exit)),
+                Ok(Err(e)) => Err(format!("'{}' failed: {}", program, e)),
+                Err(_) => Err(format!("'{}' timed out after {}ms", program, timeout_ms)),
+            }
+        }
+        NotifierKind::JsonLog { .. } => Ok(()),
+    }
+}
+
+/// Appends JSON lines to a size-rotated file, or to stdout
+struct JsonLogWriter {
+    path: Option<PathBuf>,
+    file: Option<tokio::fs::File>,
+    written: u64,
+    max_file_bytes: u64,
+    max_files: usize,
+    instance: Option<&'static str>,
+}
+
+impl JsonLogWriter {
+    fn new(
+        path: Option<&str>,
+        max_file_bytes: u64,
+        max_files: usize,
+        instance: Option<&'static str>,
+    ) -> Self {
+        Self {
+            path: path.map(PathBuf::from),
+            file: None,
+            written: 0,
+            max_file_bytes,
+            max_files,
+            instance,
+        }
+    }
+
+    async fn write(&mut self, event: &Event) -> Result<(), String> {
+        let mut line = serde_json::to_vec(&EventRecord {
+            schema_version: EVENT_SCHEMA_VERSION,
+            instance: self.instance,
+            event,
+        })
+        .map_err(|e| e.to_string())?;
+        line.push(b'\n');
+
+        let Some(path) = self.path.clone() else {
+            let mut stdout = tokio::io::stdout();
+            stdout.write_all(&line).await.map_err(|e| e.to_string())?;
+            return stdout.flush().await.map_err(|e| e.to_string());
+        };
+
+        if self.file.is_some() && self.written + line.len() as u64 > self.max_file_bytes {
+            self.rotate(&path).await;
+        }
+        if self.file.is_none() {
+            let file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+            self.written = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+            self.file = Some(file);
+        }
+
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.write_all(&line).await.map_err(|e| e.to_string())?;
+        file.flush().await.map_err(|e| e.to_string())?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shifts `path` to `path.1`, `path.1` to `path.2` and so on, dropping the oldest
+    async fn rotate(&mut self, path: &std::path::Path) {
+        self.file = None;
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", path.display(), index));
+        if self.max_files == 0 {
+            let _ = tokio::fs::remove_file(path).await;
+            return;
+        }
+        for index in (1..self.max_files).rev() {
+            let _ = tokio::fs::rename(rotated(index), rotated(index + 1)).await;
+        }
+        if let Err(e) = tokio::fs::rename(path, rotated(1)).await {
+            warn!("Failed to rotate {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Emits `TopicAppeared`/`TopicRemoved` events by diffing the cache's keys every
+/// `period_ms`. Reappearances come from the `tombstones` the removal sites write,
+/// so a topic removed and re-added between two polls is reported too.
+pub async fn run_topic_events(
+    cache: TopicCache,
+    notifiers: NotifierHub,
+    tombstones: Tombstones,
+    period_ms: u64,
+) {
+    let mut known: HashSet<String> = HashSet::new();
+    // `TombstoneStore::removals` at the previous poll
+    let mut last_removals = 0;
+    let mut interval = time::interval(Duration::from_millis(period_ms));
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let mut events = Vec::new();
+        {
+            let topics = cache.read().await;
+            let tombstones = tombstones.read().await;
+            known.retain(|key| {
+                let present = topics.contains_key(key);
+                if !present {
+                    events.push(Event::TopicRemoved {
+                        key_expr: key.clone(),
+                        timestamp: now,
+                    });
+                }
+                present
+            });
+            // A pinned placeholder has not appeared until a sample arrives on it
+            for (key, _) in topics.iter().filter(|(_, topic)| !topic.is_placeholder()) {
+                let removed_at = tombstones.get(key);
+                if known.contains(key) {
+                    // Unless it was removed and came back since the last poll
+                    if !tombstones.removed_since(key, last_removals) {
+                        continue;
+                    }
+                    events.push(Event::TopicRemoved {
+                        key_expr: key.clone(),
+                        timestamp: now,
+                    });
+                }
+                known.insert(key.clone());
+                events.push(Event::TopicAppeared {
+                    key_expr: key.clone(),
+                    reappeared: removed_at.is_some(),
+                    removed_at,
+                    timestamp: now,
+                });
+            }
+            last_removals = tombstones.removals;
+        }
+
+        for event in &events {
+            notifiers.dispatch(event).await;
+        }
+    }
+}
+
+fn check_status(response: reqwest::Response) -> Result<(), String> {
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{AlertCondition, AlertRule, AnyRule};
+
+    fn destination(kind: NotifierKind, events: &'static [EventType]) -> Destination {
+        Destination {
+            config: NotifierConfig {
+                name: "test",
+                kind,
+                events,
+                min_severity: Severity::Info,
+                key_expr: "robot/**",
+            },
+            queue: mpsc::channel(1).0,
+            status: Arc::default(),
+        }
+    }
+
+    fn appeared(key_expr: &str) -> Event {
+        Event::TopicAppeared {
+            key_expr: key_expr.to_string(),
+            reappeared: false,
+            removed_at: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn destinations_opt_in_to_topic_events() {
+        let slack = NotifierKind::Slack {
+            webhook_url: "https://hooks.slack.com/services/x",
+            template: "{rule}",
+        };
+        let alerts_only = destination(slack, &[EventType::AlertTransition]);
+        assert!(!alerts_only.accepts(&appeared("robot/1/lidar")));
+
+        let with_topics = destination(slack, &[EventType::TopicAppeared]);
+        assert!(with_topics.accepts(&appeared("robot/1/lidar")));
+        assert!(!with_topics.accepts(&appeared("camera/front")));
+        assert!(!with_topics.accepts(&Event::TopicRemoved {
+            key_expr: "robot/1/lidar".to_string(),
+            timestamp: 0,
+        }));
+    }
+
+    #[test]
+    fn topic_events_render_as_a_summary() {
+        let event = Event::TopicAppeared {
+            key_expr: "robot/1/lidar".to_string(),
+            reappeared: true,
+            removed_at: Some(1),
+            timestamp: 2,
+        };
+        assert_eq!(
+            render("{rule}", &event, None),
+            "Topic robot/1/lidar reappeared"
+        );
+        assert_eq!(
+            render("{rule}", &event, Some("robot-7")),
+            "[robot-7] Topic robot/1/lidar reappeared"
+        );
+    }
+
+    #[test]
+    fn alert_transitions_serialize_with_their_rule() {
+        let event = Event::AlertTransition(AlertTransition {
+            instance: None,
+            rule: AnyRule::Topic(AlertRule {
+                name: "lidar-slow",
+                key_expr: "robot/*/lidar",
+                severity: Severity::Warning,
+                condition: AlertCondition::RateBelow {
+                    assert_hz: 5.0,
+                    clear_hz: 8.0,
+                },
+                assert_dwell_ms: 2000,
+                clear_dwell_ms: 5000,
+            }),
+            key_expr: "robot/1/lidar".to_string(),
+            transition: Transition::Fired,
+            value: 2.5,
+            stats: None,
+            members: Vec::new(),
+            timestamp: 1_718_000_000_000,
+            suppressed: true,
+        });
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "type": "alert_transition",
+                "rule": {
+                    "name": "lidar-slow",
+                    "key_expr": "robot/*/lidar",
+                    "severity": "warning",
+                    "condition": { "type": "rate_below", "assert_hz": 5.0, "clear_hz": 8.0 },
+                    "assert_dwell_ms": 2000,
+                    "clear_dwell_ms": 5000,
+                },
+                "key_expr": "robot/1/lidar",
+                "transition": "fired",
+                "value": 2.5,
+                "timestamp": 1_718_000_000_000u64,
+            })
+        );
+    }
+
+    #[test]
+    fn topic_events_serialize_flat() {
+        assert_eq!(
+            serde_json::to_value(appeared("robot/1/lidar")).unwrap(),
+            serde_json::json!({
+                "type": "topic_appeared",
+                "key_expr": "robot/1/lidar",
+                "reappeared": false,
+                "timestamp": 0,
+            })
+        );
+        let reappeared = Event::TopicAppeared {
+            key_expr: "robot/1/lidar".to_string(),
+            reappeared: true,
+            removed_at: Some(1_718_000_000_000),
+            timestamp: 1_718_000_005_000,
+        };
+        assert_eq!(
+            serde_json::to_value(reappeared).unwrap(),
+            serde_json::json!({
+                "type": "topic_appeared",
+                "key_expr": "robot/1/lidar",
+                "reappeared": true,
+                "removed_at": 1_718_000_000_000u64,
+                "timestamp": 1_718_000_005_000u64,
+            })
+        );
+        let removed = Event::TopicRemoved {
+            key_expr: "robot/1/lidar".to_string(),
+            timestamp: 1_718_000_000_000,
+        };
+        assert_eq!(
+            serde_json::to_value(removed).unwrap(),
+            serde_json::json!({
+                "type": "topic_removed",
+                "key_expr": "robot/1/lidar",
+                "timestamp": 1_718_000_000_000u64,
+            })
+        );
+    }
+
+    #[test]
+    fn subscriber_events_serialize_flat() {
+        let restarting = Event::SubscriberRestarting {
+            restarts: 3,
+            retry_at: 1_718_000_004_000,
+            timestamp: 1_718_000_000_000,
+        };
+        assert_eq!(
+            serde_json::to_value(&restarting).unwrap(),
+            serde_json::json!({
+                "type": "subscriber_restarting",
+                "restarts": 3,
+                "retry_at": 1_718_000_004_000u64,
+                "timestamp": 1_718_000_000_000u64,
+            })
+        );
+        // Log lines add the schema version around the same fields
+        let record = EventRecord {
+            schema_version: EVENT_SCHEMA_VERSION,
+            instance: None,
+            event: &restarting,
+        };
+        assert_eq!(
+            serde_json::to_value(record).unwrap(),
+            serde_json::json!({
+                "schema_version": EVENT_SCHEMA_VERSION,
+                "type": "subscriber_restarting",
+                "restarts": 3,
+                "retry_at": 1_718_000_004_000u64,
+                "timestamp": 1_718_000_000_000u64,
+            })
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use log::warn;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Reads the value saved at `path`, or the default if there is none. An
+/// unreadable file is ignored with a warning naming `what` it holds.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring unreadable {} file {}: {}",
+                what,
+                path.display(),
+                e
+            );
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Writes `json` to `path` through a temporary file and a rename, so a crash
+/// never leaves half a file behind
+fn save_json(path: &Path, json: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// The JSON file a store keeps its state in across restarts. Stores save
+/// while their lock is held in a handler, so the file is written on the
+/// blocking pool; a save that gets there after a newer one is dropped.
+pub struct JsonFile {
+    path: PathBuf,
+    what: &'static str,
+    /// Number of the latest save requested
+    requested: AtomicU64,
+    /// Number of the latest save written, held while writing
+    written: Arc<Mutex<u64>>,
+}
+
+impl JsonFile {
+    pub fn new(path: impl Into<PathBuf>, what: &'static str) -> Self {
+        Self {
+            path: path.into(),
+            what,
+            requested: AtomicU64::new(0),
+            written: Arc::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load<T: DeserializeOwned + Default>(&self) -> T {
+        load_json(&self.path, self.what)
+    }
+
+    /// Serialises `value` now and writes it in the background, or right away
+    /// outside a runtime
+    pub fn save<T: Serialize>(&self, value: &T) {
+        let json = match serde_json::to_string_pretty(value) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialise {}: {}", self.what, e);
+                return;
+            }
+        };
+        let save = self.requested.fetch_add(1, Ordering::Relaxed) + 1;
+        let (path, what, written) = (self.path.clone(), self.what, self.written.clone());
+        let write = move || {
+            let mut written = written.lock().unwrap_or_else(PoisonError::into_inner);
+            if *written > save {
+                return;
+            }
+            if let Err(e) = save_json(&path, &json) {
+                warn!("Failed to save {} to {}: {}", what, path.display(), e);
+            }
+            *written = save;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(write)),
+            Err(_) => write(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{self, Duration};
+
+    /// A file unique to this test process
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ztm-{}-{}.json", std::process::id(), name))
+    }
+
+    #[test]
+    fn missing_or_unreadable_files_load_the_default() {
+        let missing = path("missing");
+        let _ = std::fs::remove_file(&missing);
+        assert_eq!(
+            load_json::<Vec<u64>>(&missing, "numbers"),
+            Vec::<u64>::new()
+        );
+
+        let unreadable = path("unreadable");
+        std::fs::write(&unreadable, "{ not json").unwrap();
+        assert_eq!(
+            load_json::<Vec<u64>>(&unreadable, "numbers"),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn the_latest_save_is_the_one_left_on_disk() {
+        let file = JsonFile::new(path("latest"), "numbers");
+        for last in 1..=50u64 {
+            file.save(&(1..=last).collect::<Vec<_>>());
+        }
+        let expected: Vec<u64> = (1..=50).collect();
+        let deadline = time::Instant::now() + Duration::from_secs(5);
+        while file.load::<Vec<u64>>() != expected {
+            assert!(
+                time::Instant::now() < deadline,
+                "{:?}",
+                file.load::<Vec<u64>>()
+            );
+            time::sleep(Duration::from_millis(1)).await;
+        }
+        assert!(!file.path().with_extension("tmp").exists());
+    }
+}
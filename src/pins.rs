@@ -0,0 +1,84 @@
+use log::info;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use zenoh::key_expr::keyexpr;
+
+use crate::persist::JsonFile;
+use crate::*;
+
+/// Body of `POST` and `DELETE /api/pins`
+#[derive(Debug, Deserialize)]
+pub struct PinRequest {
+    pub key_expr: String,
+}
+
+/// Pinned topic keys, persisted to a JSON file so they survive restarts
+pub struct Pins {
+    file: JsonFile,
+    keys: BTreeSet<String>,
+}
+
+impl Pins {
+    /// Loads the pins saved at `path`, starting empty if there are none
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let file = JsonFile::new(path, "pins");
+        let keys: BTreeSet<String> = file.load();
+        if !keys.is_empty() {
+            info!(
+                "Loaded {} pin(s) from {}",
+                keys.len(),
+                file.path().display()
+            );
+        }
+        Self { file, keys }
+    }
+
+    fn save(&self) {
+        self.file.save(&self.keys);
+    }
+
+    /// Pins are for single topics, so wildcards are rejected.
+    /// Returns false if the key was already pinned.
+    pub fn add(&mut self, key: &str) -> Result<bool, String> {
+        let key_expr = keyexpr::new(key).map_err(|e| format!("invalid key_expr: {}", e))?;
+        if key_expr.is_wild() {
+            return Err("only a single topic can be pinned, not a wildcard".to_string());
+        }
+        let added = self.keys.insert(key.to_string());
+        if added {
+            self.save();
+        }
+        Ok(added)
+    }
+
+    /// Returns false if the key was not pinned
+    pub fn remove(&mut self, key: &str) -> bool {
+        let removed = self.keys.remove(key);
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    pub fn list(&self) -> Vec<&String> {
+        self.keys.iter().collect()
+    }
+}
+
+/// Marks the topic pinned or unpinned in the cache. Pinning a key with no topic
+/// adds a placeholder row so its absence shows; unpinning drops the placeholder.
+pub async fn apply(cache: &TopicCache, key: &str, pinned: bool) {
+    let mut cache = cache.write().await;
+    match cache.get_mut(key) {
+        // Not through `remove_topic`: a placeholder has nothing to tombstone
+        Some(topic) if !pinned && topic.is_placeholder() => {
+            cache.remove(key);
+        }
+        Some(topic) => topic.pinned = pinned,
+        None if pinned => {
+            cache.insert(key.to_string(), TopicData::placeholder(key).into());
+        }
+        None => {}
+    }
+}
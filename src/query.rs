@@ -0,0 +1,283 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::time::{self, Duration, Instant};
+use zenoh::sample::Sample;
+
+use crate::decoder::{self, NamedDecoder};
+use crate::*;
+
+/// Timeout used when the request doesn't give one, and the most it may ask for
+const DEFAULT_TIMEOUT_MS: u64 = 2_000;
+const MAX_TIMEOUT_MS: u64 = 10_000;
+/// Replies beyond this are dropped and the response is marked truncated
+const MAX_REPLIES: usize = 100;
+
+/// Body of `POST /api/query`
+#[derive(Debug, Deserialize)]
+pub struct QueryRequest {
+    /// Key expression, optionally with parameters (`robot/1/config?verbose=true`)
+    pub selector: String,
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryReply {
+    /// Empty for error replies, which carry no key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_expr: Option<String>,
+    size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoded_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoded_by: Option<&'static str>,
+    /// Why decoding failed, or the error a queryable replied with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Startup `get` on the subscribed key expression that fills the table with
+/// the last values a storage or queryable holds
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SeedQuery {
+    /// How long to wait for replies before carrying on with live samples only
+    pub timeout_ms: u64,
+    /// Replies beyond this are ignored, so a large storage can't flood the cache
+    pub max_replies: usize,
+}
+
+impl SeedQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_TIMEOUT_MS).contains(&self.timeout_ms) {
+            return Err(format!(
+                "the seed query timeout must be between 1 and {} ms",
+                MAX_TIMEOUT_MS
+            ));
+        }
+        if self.max_replies == 0 {
+            return Err("the seed query must accept at least one reply".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryResult {
+    selector: String,
+    replies: Vec<QueryReply>,
+    /// More than `MAX_REPLIES` replies arrived
+    truncated: bool,
+    elapsed_ms: u64,
+}
+
+/// Without a decoder chain, replies are shown as text if they are UTF-8, else as hex
+fn describe_sample(sample: Sample, decoders: &'static [NamedDecoder]) -> QueryReply {
+    let key_expr = Some(sample.key_expr().as_str().to_string());
+    let size_bytes = sample.payload().len() as u64;
+    let result = if decoders.is_empty() {
+        match sample.payload().try_to_string() {
+            Ok(text) => Ok(("text", text.into_owned())),
+            Err(_) => decoder::hex_decoder(sample).map(|hex| ("hex", hex)),
+        }
+    } else {
+        decoder::decode_chain(decoders, sample)
+    };
+    match result {
+        Ok((name, text)) => QueryReply {
+            key_expr,
+            size_bytes,
+            decoded_content: Some(html_escape_string(&text)),
+            decoded_by: Some(name),
+            error: None,
+        },
+        Err(e) => QueryReply {
+            key_expr,
+            size_bytes,
+            decoded_content: None,
+            decoded_by: None,
+            error: Some(html_escape_string(&e)),
+        },
+    }
+}
+
+/// A topic row for a stored value: its size and decoded content, stamped with
+/// the sample's Zenoh timestamp when it has one. The rate is unknown until
+/// live samples arrive.
+fn seeded_topic(
+    sample: Sample,
+    decoders: &'static [NamedDecoder],
+    units: format::Units,
+    now: u64,
+) -> TopicData {
+    let key_expr = sample.key_expr().as_str().to_string();
+    let size_bytes = sample.payload().len() as u64;
+    let received_timestamp = sample.timestamp().map_or(now, |timestamp| {
+        timestamp.get_time().to_duration().as_millis() as u64
+    });
+    let decoded = if decoders.is_empty() {
+        None
+    } else {
+        decoder::decode_chain(decoders, sample).ok()
+    };
+    TopicData {
+        key_expr,
+        last_data_size_bytes: size_bytes,
+        received_timestamp,
+        decoded_content: decoded.as_ref().map(|(_, text)| html_escape_string(text)),
+        decoded_by: decoded.map(|(name, _)| name.to_string()),
+        avg_data_size_bytes: size_bytes as f64,
+        empty_payload: size_bytes == 0,
+        size_display: format::size(size_bytes as f64, units),
+        hz_display: format::hz_pending(0),
+        bandwidth_display: format::bandwidth(0.0, units),
+        jitter_display: "-".to_string(),
+        latency_display: "-".to_string(),
+        ..TopicData::default()
+    }
+}
+
+/// Queries `key_expr` once and adds a topic for every reply whose key has no
+/// samples yet, so the table starts with the last stored values. Gives up
+/// waiting after `seed.timeout_ms`, so a network without queryables only
+/// delays nothing; live samples are received meanwhile and always win.
+pub async fn seed(
+    session: &zenoh::Session,
+    key_expr: &str,
+    seed: SeedQuery,
+    cache: &TopicCache,
+    decoders: &'static [NamedDecoder],
+    units: format::Units,
+) {
+    let timeout = Duration::from_millis(seed.timeout_ms);
+    let replies = match session.get(key_expr).timeout(timeout).await {
+        Ok(replies) => replies,
+        Err(e) => {
+            warn!("Failed to send the seed query on '{}': {}", key_expr, e);
+            return;
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    let mut samples = Vec::new();
+    let mut truncated = false;
+    while let Ok(Ok(reply)) = time::timeout_at(deadline, replies.recv_async()).await {
+        if samples.len() >= seed.max_replies {
+            truncated = true;
+            break;
+        }
+        // Error replies carry no key, so there is nothing to seed from them
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    if truncated {
+        warn!(
+            "Seed query on '{}' returned more than {} replies, ignoring the rest",
+            key_expr, seed.max_replies
+        );
+    }
+
+    let now = get_timestamp();
+    let replied = samples.len();
+    let topics = match tokio::task::spawn_blocking(move || {
+        samples
+            .into_iter()
+            .map(|sample| seeded_topic(sample, decoders, units, now))
+            .collect::<Vec<_>>()
+    })
+    .await
+    {
+        Ok(topics) => topics,
+        Err(e) => {
+            warn!("Decoding the seed replies failed: {}", e);
+            return;
+        }
+    };
+
+    let mut seeded = 0;
+    let mut cache = cache.write().await;
+    for mut topic in topics {
+        match cache.get(&topic.key_expr) {
+            Some(existing) if !existing.is_placeholder() => continue,
+            Some(_) => topic.pinned = true,
+            None => {}
+        }
+        cache.insert(topic.key_expr.clone(), topic.into());
+        seeded += 1;
+    }
+    info!(
+        "Seeded {} topic(s) from {} replies to '{}'",
+        seeded, replied, key_expr
+    );
+}
+
+/// Sends one `get` on the monitor's session and collects the replies until the
+/// timeout, or until `MAX_REPLIES` have arrived.
+pub async fn run(
+    session: &zenoh::Session,
+    request: QueryRequest,
+    decoders: &'static [NamedDecoder],
+) -> Result<QueryResult, String> {
+    let timeout = Duration::from_millis(
+        request
+            .timeout_ms
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .min(MAX_TIMEOUT_MS),
+    );
+    let started = Instant::now();
+    info!("Querying '{}'", request.selector);
+    let replies = session
+        .get(request.selector.as_str())
+        .timeout(timeout)
+        .await
+        .map_err(|e| format!("invalid selector '{}': {}", request.selector, e))?;
+
+    let mut samples = Vec::new();
+    let mut errors = Vec::new();
+    let mut truncated = false;
+    let deadline = started + timeout;
+    while let Ok(Ok(reply)) = time::timeout_at(deadline, replies.recv_async()).await {
+        if samples.len() + errors.len() >= MAX_REPLIES {
+            truncated = true;
+            break;
+        }
+        match reply.into_result() {
+            Ok(sample) => samples.push(sample),
+            Err(e) => errors.push(
+                e.payload()
+                    .try_to_string()
+                    .map(|text| text.into_owned())
+                    .unwrap_or_else(|_| "error reply".to_string()),
+            ),
+        }
+    }
+    if truncated {
+        warn!(
+            "Query '{}' returned more than {} replies, truncating",
+            request.selector, MAX_REPLIES
+        );
+    }
+
+    // Decoders may be slow, so they run off the async workers as for subscriptions
+    let mut replies = tokio::task::spawn_blocking(move || {
+        samples
+            .into_iter()
+            .map(|sample| describe_sample(sample, decoders))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("decoding replies failed: {}", e))?;
+    replies.extend(errors.into_iter().map(|error| QueryReply {
+        key_expr: None,
+        size_bytes: 0,
+        decoded_content: None,
+        decoded_by: None,
+        error: Some(html_escape_string(&error)),
+    }));
+
+    Ok(QueryResult {
+        selector: request.selector,
+        replies,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
@@ -0,0 +1,463 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::alerts::{AlertState, AlertView};
+use crate::throughput::ThroughputPoint;
+use crate::views::{Sort, SortDirection, SortField};
+use crate::*;
+
+/// Query of `/api/report.html`
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    /// Comma-separated column names; the configured columns when absent
+    pub columns: Option<String>,
+    /// Case-insensitive substring of the key expression
+    pub key: Option<String>,
+    /// Order of the table, as for `/api/topics`
+    pub sort: Option<SortField>,
+    pub direction: Option<SortDirection>,
+}
+
+impl ReportQuery {
+    /// The requested columns, which must include the key
+    pub fn columns(&self, configured: Vec<Column>) -> Result<Vec<Column>, String> {
+        let Some(names) = &self.columns else {
+            return Ok(configured);
+        };
+        let columns = names
+            .split(',')
+            .map(|name| Column::parse(name.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !columns.contains(&Column::Key) {
+            return Err("the report's columns must include 'key'".to_string());
+        }
+        Ok(columns)
+    }
+
+    pub fn sort(&self, default_sort: Sort) -> Sort {
+        match self.sort {
+            Some(field) => Sort {
+                field,
+                direction: self.direction.unwrap_or(field.natural_direction()),
+            },
+            None => Sort {
+                direction: self.direction.unwrap_or(default_sort.direction),
+                ..default_sort
+            },
+        }
+    }
+
+    pub fn matches(&self, topic: &TopicData) -> bool {
+        self.key
+            .as_deref()
+            .is_none_or(|key| topic.key_expr.to_lowercase().contains(&key.to_lowercase()))
+    }
+}
+
+/// Alert state of one topic, summarised the way the status column shows it
+#[derive(Default)]
+struct TopicAlert {
+    firing: bool,
+    silenced: bool,
+    acked: bool,
+}
+
+impl TopicAlert {
+    fn status(&self) -> &'static str {
+        match self {
+            TopicAlert { silenced: true, .. } => "silenced",
+            TopicAlert {
+                firing: true,
+                acked: true,
+                ..
+            } => "firing (acked)",
+            TopicAlert { firing: true, .. } => "firing",
+            _ => "pending",
+        }
+    }
+}
+
+fn is_firing(state: &AlertState) -> bool {
+    matches!(
+        state,
+        AlertState::Firing { .. } | AlertState::Resolving { .. }
+    )
+}
+
+fn state_name(state: &AlertState) -> &'static str {
+    match state {
+        AlertState::Ok => "ok",
+        AlertState::Pending { .. } => "pending",
+        AlertState::Firing { .. } => "firing",
+        AlertState::Resolving { .. } => "resolving",
+    }
+}
+
+fn load_of(topic: &TopicData, metric: LoadMetric) -> f64 {
+    match metric {
+        LoadMetric::Rate => topic.estimated_hz.unwrap_or(0.0),
+        LoadMetric::Bandwidth => topic.estimated_hz.unwrap_or(0.0) * topic.avg_data_size_bytes,
+    }
+}
+
+fn cell(
+    column: Column,
+    topic: &TopicData,
+    alert: Option<&TopicAlert>,
+    max_load: f64,
+    load_metric: LoadMetric,
+    now: u64,
+) -> String {
+    let never = topic.is_placeholder();
+    match column {
+        Column::Key => format!(
+            r#"<td class="topic-cell"><span class="topic-key">{}</span></td>"#,
+            html_escape_string(&topic.key_expr)
+        ),
+        Column::Size => format!(
+            r#"<td class="size-cell{}">{}</td>"#,
+            if topic.empty_payload { " signal" } else { "" },
+            topic.size_display
+        ),
+        Column::Hz => format!(
+            r#"<td class="freq-cell"><span class="freq-value">{}</span></td>"#,
+            topic.hz_display
+        ),
+        Column::Bandwidth => format!(
+            r#"<td class="bandwidth-cell">{}</td>"#,
+            topic.bandwidth_display
+        ),
+        Column::Timestamp => format!(
+            r#"<td class="timestamp-cell">{}</td>"#,
+            if never {
+                "never received".to_string()
+            } else {
+                format::timestamp(topic.received_timestamp)
+            }
+        ),
+        Column::Age => format!(
+            r#"<td class="age-cell">{}</td>"#,
+            if never {
+                "-".to_string()
+            } else {
+                format::age(now.saturating_sub(topic.received_timestamp))
+            }
+        ),
+        Column::Load => {
+            let load = load_of(topic, load_metric);
+            let width = if max_load > 0.0 {
+                load / max_load * 100.0
+            } else {
+                0.0
+            };
+            format!(
+                r#"<td class="load-cell"><div class="load-bar"><div class="load-fill" style="width: {:.1}%"></div></div></td>"#,
+                width
+            )
+        }
+        Column::Stability => {
+            let class = match topic.stability {
+                80.. => "stability-cell",
+                50.. => "stability-cell stability-fair",
+                _ => "stability-cell stability-poor",
+            };
+            format!(r#"<td class="{}">{}</td>"#, class, topic.stability)
+        }
+        Column::Jitter => format!(
+            r#"<td class="jitter-cell{}">{}</td>"#,
+            if topic.jitter_exceeded {
+                " timing-exceeded"
+            } else {
+                ""
+            },
+            topic.jitter_display
+        ),
+        Column::Latency => format!(
+            r#"<td class="latency-cell{}">{}</td>"#,
+            if topic.latency_exceeded {
+                " timing-exceeded"
+            } else {
+                ""
+            },
+            topic.latency_display
+        ),
+        Column::Source => format!(
+            r#"<td class="source-cell{}">{}</td>"#,
+            if topic.source_count > 1 {
+                " source-conflict"
+            } else {
+                ""
+            },
+            html_escape_string(topic.last_source.as_deref().unwrap_or("-"))
+        ),
+        Column::Publishers => format!(
+            r#"<td class="publishers-cell{}">{}</td>"#,
+            if topic.publisher_conflict {
+                " source-conflict"
+            } else {
+                ""
+            },
+            topic
+                .publisher_count
+                .map_or_else(|| "-".to_string(), |count| count.to_string())
+        ),
+        Column::Instance => format!(
+            r#"<td class="instance-cell">{}</td>"#,
+            html_escape_string(topic.instance.as_deref().unwrap_or("local"))
+        ),
+        // Decoded content is escaped when it is decoded
+        Column::Decoded => format!(
+            r#"<td class="decoded-cell">{}</td>"#,
+            topic
+                .decoded_content
+                .as_deref()
+                .unwrap_or(if topic.empty_payload { "(signal)" } else { "-" })
+        ),
+        Column::Status => format!(
+            r#"<td class="status-cell">{}</td>"#,
+            alert.map_or("ok", TopicAlert::status)
+        ),
+    }
+}
+
+fn alerts_section(alerts: &[AlertView]) -> String {
+    if alerts.is_empty() {
+        return r#"<p class="report-note">No active alerts.</p>"#.to_string();
+    }
+    let rows: String = alerts
+        .iter()
+        .map(|alert| {
+            let mut state = state_name(&alert.state).to_string();
+            if alert.silenced_by.is_some() {
+                state.push_str(" (silenced)");
+            } else if alert.acknowledged.is_some() {
+                state.push_str(" (acked)");
+            }
+            format!(
+                r#"<tr class="{}"><td>{}</td><td>{}</td><td class="topic-cell">{}</td><td>{}</td><td>{:.2} {}</td></tr>"#,
+                if is_firing(&alert.state) && alert.silenced_by.is_none() {
+                    "alerting-row"
+                } else {
+                    ""
+                },
+                html_escape_string(alert.rule.name()),
+                alert.rule.severity().as_str(),
+                html_escape_string(&alert.key_expr),
+                state,
+                alert.value,
+                alert.rule.metric()
+            )
+        })
+        .collect();
+    format!(
+        r#"<table class="report-alerts">
+        <thead><tr><th>Rule</th><th>Severity</th><th>Topic</th><th>State</th><th>Value</th></tr></thead>
+        <tbody>{}</tbody>
+    </table>"#,
+        rows
+    )
+}
+
+/// A standalone page of the topics and alerts at `now`, for attaching to a
+/// ticket. The styles are the live page's, inlined; there is no script, so it
+/// works offline and never changes.
+#[allow(clippy::too_many_arguments)]
+pub fn render(
+    config: &Config,
+    extra_css: &str,
+    columns: &[Column],
+    topics: &[&TopicData],
+    alerts: &[AlertView],
+    throughput: Option<ThroughputPoint>,
+    sort: Sort,
+    now: u64,
+) -> String {
+    let mut by_topic: HashMap<&str, TopicAlert> = HashMap::new();
+    for alert in alerts {
+        let entry = by_topic.entry(alert.key_expr.as_str()).or_default();
+        if alert.silenced_by.is_some() {
+            entry.silenced = true;
+        } else if is_firing(&alert.state) {
+            entry.firing = true;
+        }
+        entry.acked |= alert.acknowledged.is_some();
+    }
+
+    let max_load = topics
+        .iter()
+        .map(|topic| load_of(topic, config.load_bar_metric))
+        .fold(0.0, f64::max);
+    let rows: String = topics
+        .iter()
+        .map(|topic| {
+            let alert = by_topic.get(topic.key_expr.as_str());
+            let mut classes = Vec::new();
+            if topic.pinned {
+                classes.push("pinned-row".to_string());
+            }
+            if topic.is_placeholder() {
+                classes.push("missing-row".to_string());
+            } else if now.saturating_sub(topic.received_timestamp) > STALE_AFTER_MS {
+                classes.push("stale-row".to_string());
+            }
+            if alert.is_some_and(|alert| alert.firing) {
+                classes.push("alerting-row".to_string());
+            }
+            if let Some(highlight) = &topic.highlight {
+                classes.push(format!(
+                    "highlight-{}",
+                    html_escape::encode_double_quoted_attribute(highlight)
+                ));
+            }
+            let cells: String = columns
+                .iter()
+                .map(|column| cell(*column, topic, alert, max_load, config.load_bar_metric, now))
+                .collect();
+            format!(r#"<tr class="{}">{}</tr>"#, classes.join(" "), cells)
+        })
+        .collect::<Vec<_>>()
+        .join("\n            ");
+    let header_cells: String = columns
+        .iter()
+        .map(|column| format!("<th>{}</th>", column.title()))
+        .collect();
+    let firing = by_topic.values().filter(|alert| alert.firing).count();
+    let (rate, bandwidth) = throughput.map_or_else(
+        || ("-".to_string(), "-".to_string()),
+        |point| {
+            (
+                format::hz(f64::from(point.messages_per_sec)),
+                format::bandwidth(f64::from(point.bytes_per_sec), config.display_units),
+            )
+        },
+    );
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<title>{page_title} report, {generated}</title>
+<style>
+    {page_css}
+</style>
+<style>
+{highlight_css}
+    {branding_css}
+</style>
+<style>
+{extra_css}
+</style>
+<style>
+    body {{
+        height: auto;
+    }}
+    .container, tbody {{
+        overflow: visible;
+    }}
+    .report-alerts th, .report-alerts td {{
+        width: auto;
+    }}
+    .report-note {{
+        color: var(--subtle);
+    }}
+</style>
+</head>
+<body>
+<div class="header">
+    <h1>{heading}</h1>
+    <p>{subtitle}</p>
+    <p class="report-note">Report generated {generated}, sorted by {sort_field} ({sort_direction})</p>
+</div>
+<div class="stats">
+    <div class="stat-item">
+        <span class="stat-value">{topic_count}</span>
+        <span class="stat-label">Topics</span>
+    </div>
+    <div class="stat-item">
+        <span class="stat-value">{rate}</span>
+        <span class="stat-label">Messages</span>
+    </div>
+    <div class="stat-item">
+        <span class="stat-value">{bandwidth}</span>
+        <span class="stat-label">Bandwidth</span>
+    </div>
+    <div class="stat-item">
+        <span class="stat-value">{firing}</span>
+        <span class="stat-label">Firing Alerts</span>
+    </div>
+</div>
+<h2>Alerts</h2>
+{alerts}
+<h2>Topics</h2>
+<div class="container">
+    <table>
+        <thead>
+            <tr>{header_cells}</tr>
+        </thead>
+        <tbody>
+            {rows}
+        </tbody>
+    </table>
+</div>
+</body>
+</html>"#,
+        page_title = html_escape_string(config.branding.page_title),
+        heading = html_escape_string(config.branding.heading),
+        subtitle = html_escape_string(config.branding.subtitle),
+        generated = format::timestamp(now),
+        page_css = page_css(config.theme, columns),
+        highlight_css = highlight::css(config.highlight_rules),
+        branding_css = config.branding.css(),
+        extra_css = extra_css,
+        sort_field = sort.field.column().name(),
+        sort_direction = match sort.direction {
+            SortDirection::Asc => "ascending",
+            SortDirection::Desc => "descending",
+        },
+        topic_count = topics.len(),
+        rate = rate,
+        bandwidth = bandwidth,
+        firing = firing,
+        alerts = alerts_section(alerts),
+        header_cells = header_cells,
+        rows = rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostile_strings_are_escaped_in_cells_and_attributes() {
+        let topic = TopicData {
+            key_expr: r#"a/<script>alert(1)</script>/"q'"#.to_string(),
+            received_timestamp: 1,
+            last_source: Some(r#""><img src=x onerror=alert(1)>"#.to_string()),
+            instance: Some("<b>vehicle</b>".to_string()),
+            highlight: Some(r#"x" onmouseover="alert(1)"#.to_string()),
+            ..TopicData::default()
+        };
+        let page = render(
+            &Config::default(),
+            "",
+            Column::ALL,
+            &[&topic],
+            &[],
+            None,
+            Sort {
+                field: SortField::Key,
+                direction: SortDirection::Asc,
+            },
+            1,
+        );
+        // Keys only appear as text, where their quotes are harmless
+        for raw in ["<script>alert", "<img", "<b>vehicle", r#"" onmouseover"#] {
+            assert!(!page.contains(raw), "{} left unescaped", raw);
+        }
+        assert!(page.contains("a/&lt;script&gt;alert(1)&lt;/script&gt;/"));
+        assert!(page.contains("&lt;b&gt;vehicle&lt;/b&gt;"));
+        assert!(page.contains("highlight-x&quot; onmouseover=&quot;alert(1)"));
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::cli::leaked;
+use crate::*;
+
+/// Read at startup when `--config` doesn't name another file
+pub const DEFAULT_SETTINGS_PATH: &str = "config.toml";
+/// Read from the binary's directory when the working directory has no
+/// `DEFAULT_SETTINGS_PATH`, so each deployment can keep one beside the binary
+pub const BESIDE_BINARY_SETTINGS_FILE: &str = "monitor.toml";
+
+/// Runtime overrides of the constants in main.rs, read from a TOML file at
+/// startup, e.g.
+///
+/// ```toml
+/// port = 9090
+/// reload_period_ms = 500
+/// window_size = 50
+/// log_level = "info"
+/// mode = "client"
+/// connect_endpoints = ["tcp/192.168.1.5:7447"]
+/// subscribe_key_expr = "robot/**"
+/// decoder_enabled = false
+/// measure_processing_latency = true
+/// min_payload_bytes = 16
+/// expected_topics_manifest = "expected.toml"
+/// zenoh_config_file = "zenoh.json5"
+/// ```
+///
+/// Every key is optional; one left out keeps its constant. Each has a `ZTM_*`
+/// environment variable, see `from_env`, and the flags cover the same settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub port: Option<u16>,
+    pub reload_period_ms: Option<u64>,
+    /// Samples in the rate and rolling-average windows (`WINDOW_SIZE`)
+    pub window_size: Option<usize>,
+    /// off, error, warn, info, debug or trace
+    pub log_level: Option<String>,
+    /// Zenoh mode: peer, client or router
+    pub mode: Option<String>,
+    pub connect_endpoints: Option<Vec<String>>,
+    pub subscribe_key_expr: Option<String>,
+    /// `false` runs in standard mode without the `DECODERS` chain
+    pub decoder_enabled: Option<bool>,
+    /// Reports the subscribe loop's per-sample time at `/api/diagnostics`
+    pub measure_processing_latency: Option<bool>,
+    /// Samples with smaller payloads are dropped on arrival (`MIN_PAYLOAD_BYTES`)
+    pub min_payload_bytes: Option<u64>,
+    /// Manifest of the expected rates (`EXPECTED_TOPICS_MANIFEST`)
+    pub expected_topics_manifest: Option<String>,
+    /// Zenoh JSON5 configuration file, used as is instead of the mode and endpoints
+    pub zenoh_config_file: Option<String>,
+}
+
+/// The settings file read without `--config`, if there is one
+pub fn default_path() -> Option<PathBuf> {
+    let beside_binary = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(BESIDE_BINARY_SETTINGS_FILE)));
+    std::iter::once(PathBuf::from(DEFAULT_SETTINGS_PATH))
+        .chain(beside_binary)
+        .find(|path| path.is_file())
+}
+
+impl Settings {
+    /// Reads the `ZTM_*` environment variables through `var`. Lists are
+    /// comma-separated.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Settings, String> {
+        fn parsed<T: FromStr>(
+            var: &impl Fn(&str) -> Option<String>,
+            name: &str,
+        ) -> Result<Option<T>, String>
+        where
+            T::Err: std::fmt::Display,
+        {
+            var(name)
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("invalid {} '{}': {}", name, value, e))
+                })
+                .transpose()
+        }
+
+        Ok(Settings {
+            port: parsed(&var, "ZTM_PORT")?,
+            reload_period_ms: parsed(&var, "ZTM_RELOAD_MS")?,
+            window_size: parsed(&var, "ZTM_WINDOW_SIZE")?,
+            log_level: var("ZTM_LOG_LEVEL"),
+            mode: var("ZTM_MODE"),
+            connect_endpoints: var("ZTM_ENDPOINTS").map(|endpoints| {
+                endpoints
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|endpoint| !endpoint.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }),
+            subscribe_key_expr: var("ZTM_KEY_EXPR"),
+            decoder_enabled: parsed(&var, "ZTM_DECODER_ENABLED")?,
+            measure_processing_latency: parsed(&var, "ZTM_MEASURE_PROCESSING_LATENCY")?,
+            min_payload_bytes: parsed(&var, "ZTM_MIN_SIZE")?,
+            expected_topics_manifest: var("ZTM_MANIFEST"),
+            zenoh_config_file: var("ZTM_ZENOH_CONFIG"),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Settings, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&text)
+            .map_err(|e| format!("invalid settings file {}: {}", path.display(), e))
+    }
+
+    /// Overrides `config` with the settings given; values are checked later by
+    /// `Config::validate`, except the log level, which is needed first
+    pub fn apply(self, config: &mut Config) -> Result<(), String> {
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(period_ms) = self.reload_period_ms {
+            config.reload_period_ms = period_ms;
+        }
+        if let Some(size) = self.window_size {
+            config.window_size = size;
+            if let RateWindow::Samples(_) = config.rate_window {
+                config.rate_window = RateWindow::Samples(size);
+            }
+        }
+        if let Some(level) = self.log_level {
+            config.log_level = LevelFilter::from_str(&level)
+                .map_err(|_| {
+                    format!(
+                        "invalid log_level '{}', expected off, error, warn, info, debug or trace",
+                        level
+                    )
+                })?
+                .as_str();
+        }
+        if let Some(mode) = self.mode {
+            config.zenoh_mode = mode.leak();
+        }
+        if let Some(endpoints) = self.connect_endpoints {
+            config.zenoh_connect_endpoints = leaked(&endpoints);
+        }
+        if let Some(key_expr) = self.subscribe_key_expr {
+            config.subscribe_key_expr = key_expr.leak();
+        }
+        if let Some(enabled) = self.decoder_enabled {
+            config.decoder_enabled = enabled;
+            if !enabled {
+                config.decoders.clear();
+            }
+        }
+        if let Some(measure) = self.measure_processing_latency {
+            config.measure_processing_latency = measure;
+        }
+        if let Some(min_size) = self.min_payload_bytes {
+            config.min_payload_bytes = min_size;
+        }
+        if let Some(path) = self.expected_topics_manifest {
+            config.expected_topics_manifest = Some(path);
+        }
+        if let Some(path) = self.zenoh_config_file {
+            config.zenoh_config_file = Some(path);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use log::{info, warn};
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+
+use crate::*;
+
+/// Resolves once the shutdown signal is sent, or its sender is gone
+pub async fn stopping(shutdown: &mut ShutdownSignal) {
+    let _ = shutdown.wait_for(|&stopping| stopping).await;
+}
+
+/// The monitor's long-running tasks, stopped together on Ctrl-C
+pub struct Tasks {
+    shutdown: ShutdownSignal,
+    handles: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Tasks {
+    pub fn new(shutdown: ShutdownSignal) -> Self {
+        Self {
+            shutdown,
+            handles: Vec::new(),
+        }
+    }
+
+    /// Spawns a task with nothing to flush; it is dropped at the shutdown signal
+    pub fn spawn(&mut self, name: &'static str, task: impl Future<Output = ()> + Send + 'static) {
+        let mut shutdown = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                () = task => {}
+                () = stopping(&mut shutdown) => {}
+            }
+        });
+        self.handles.push((name, handle));
+    }
+
+    /// Spawns a task that watches the signal itself, to finish its work and
+    /// release what it holds before returning
+    pub fn spawn_graceful<F, Fut>(&mut self, name: &'static str, task: F)
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(task(self.shutdown.clone()));
+        self.handles.push((name, handle));
+    }
+
+    /// Waits up to `timeout` for every task to return once the signal is sent,
+    /// in the order they were spawned, logging which stopped and which were
+    /// cut off. A second Ctrl-C stops waiting. Returns the tasks that stopped.
+    pub async fn join(self, timeout: Duration) -> Vec<&'static str> {
+        let deadline = Instant::now() + timeout;
+        let mut second_ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
+        let mut stopped = Vec::new();
+        let mut abandoned = Vec::new();
+        for (name, handle) in self.handles {
+            if !abandoned.is_empty() {
+                abandoned.push(name);
+                continue;
+            }
+            tokio::select! {
+                result = time::timeout_at(deadline, handle) => match result {
+                    Ok(Ok(())) => {
+                        info!("Stopped {}", name);
+                        stopped.push(name);
+                    }
+                    Ok(Err(e)) => warn!("{} failed while stopping: {}", name, e),
+                    Err(_) => warn!("{} did not stop within {} ms", name, timeout.as_millis()),
+                },
+                _ = &mut second_ctrl_c => abandoned.push(name),
+            }
+        }
+        if !abandoned.is_empty() {
+            warn!("Second Ctrl-C, not waiting for: {}", abandoned.join(", "));
+        }
+        stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn every_task_stops_and_is_awaited_in_order() {
+        let (stop, shutdown) = watch::channel(false);
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Tasks::new(shutdown);
+        tasks.spawn("ticker", std::future::pending());
+        for (name, flush_ms) in [("audit log", 100), ("web server", 10)] {
+            let finished = finished.clone();
+            tasks.spawn_graceful(name, move |mut shutdown| async move {
+                stopping(&mut shutdown).await;
+                time::sleep(Duration::from_millis(flush_ms)).await;
+                finished.lock().unwrap().push(name);
+            });
+        }
+        tasks.spawn_graceful("stuck", |_| std::future::pending());
+
+        stop.send_replace(true);
+        let stopped = tasks.join(Duration::from_millis(500)).await;
+        // Spawn order, whichever finished first; the stuck task is cut off
+        assert_eq!(stopped, ["ticker", "audit log", "web server"]);
+        assert_eq!(*finished.lock().unwrap(), ["web server", "audit log"]);
+    }
+
+    #[tokio::test]
+    async fn a_dropped_signal_stops_tasks_too() {
+        let (stop, shutdown) = watch::channel(false);
+        let mut tasks = Tasks::new(shutdown);
+        tasks.spawn("ticker", std::future::pending());
+        tasks.spawn_graceful("web server", |mut shutdown| async move {
+            stopping(&mut shutdown).await;
+        });
+        drop(stop);
+        let stopped = tasks.join(Duration::from_millis(500)).await;
+        assert_eq!(stopped, ["ticker", "web server"]);
+    }
+}
@@ -0,0 +1,159 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use zenoh::key_expr::keyexpr;
+
+use crate::persist::JsonFile;
+
+/// A time-bounded silence: matching alerts neither notify nor colour their rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: u64,
+    /// Key expression matched against the alert's topic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_expr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl Silence {
+    /// A silence with both a key expression and a rule only matches alerts satisfying both
+    fn matches(&self, rule: &str, key: &str) -> bool {
+        let key_matches = self.key_expr.as_deref().is_none_or(|pattern| {
+            match (keyexpr::new(pattern), keyexpr::new(key)) {
+                (Ok(pattern), Ok(key)) => pattern.includes(key),
+                _ => pattern == key,
+            }
+        });
+        key_matches && self.rule.as_deref().is_none_or(|name| name == rule)
+    }
+}
+
+/// Body of `POST /api/silences`; one of `duration_ms` or `expires_at` is required
+#[derive(Debug, Deserialize)]
+pub struct SilenceRequest {
+    pub key_expr: Option<String>,
+    pub rule: Option<String>,
+    pub created_by: Option<String>,
+    pub comment: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub expires_at: Option<u64>,
+}
+
+/// Active silences, persisted to a JSON file so they survive restarts
+pub struct Silences {
+    /// `None` keeps the silences in memory only
+    file: Option<JsonFile>,
+    silences: Vec<Silence>,
+    next_id: u64,
+}
+
+impl Silences {
+    /// Loads the silences saved at `path`, starting empty if there are none
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let file = JsonFile::new(path, "silences");
+        let silences: Vec<Silence> = file.load();
+        if !silences.is_empty() {
+            info!(
+                "Loaded {} silence(s) from {}",
+                silences.len(),
+                file.path().display()
+            );
+        }
+
+        let next_id = silences.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        Self {
+            file: Some(file),
+            silences,
+            next_id,
+        }
+    }
+
+    /// No silences and nothing persisted, for one-shot runs
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            silences: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    fn save(&self) {
+        if let Some(file) = &self.file {
+            file.save(&self.silences);
+        }
+    }
+
+    pub fn create(&mut self, request: SilenceRequest, now: u64) -> Result<Silence, String> {
+        if request.key_expr.is_none() && request.rule.is_none() {
+            return Err("a silence needs a key_expr, a rule or both".to_string());
+        }
+        if let Some(pattern) = &request.key_expr {
+            keyexpr::new(pattern.as_str()).map_err(|e| format!("invalid key_expr: {}", e))?;
+        }
+        let expires_at = match (request.duration_ms, request.expires_at) {
+            (Some(duration), None) => now.saturating_add(duration),
+            (None, Some(expires_at)) => expires_at,
+            _ => return Err("exactly one of duration_ms or expires_at is required".to_string()),
+        };
+        if expires_at <= now {
+            return Err("the silence would already have expired".to_string());
+        }
+
+        let silence = Silence {
+            id: self.next_id,
+            key_expr: request.key_expr,
+            rule: request.rule,
+            created_by: request.created_by,
+            comment: request.comment,
+            created_at: now,
+            expires_at,
+        };
+        self.next_id += 1;
+        self.silences.push(silence.clone());
+        self.save();
+        Ok(silence)
+    }
+
+    /// Returns false if no silence has the given id
+    pub fn delete(&mut self, id: u64) -> bool {
+        let before = self.silences.len();
+        self.silences.retain(|s| s.id != id);
+        let deleted = self.silences.len() != before;
+        if deleted {
+            self.save();
+        }
+        deleted
+    }
+
+    /// Drops expired silences
+    pub fn expire(&mut self, now: u64) {
+        let before = self.silences.len();
+        self.silences.retain(|s| s.expires_at > now);
+        if self.silences.len() != before {
+            info!("{} silence(s) expired", before - self.silences.len());
+            self.save();
+        }
+    }
+
+    pub fn list(&self, now: u64) -> Vec<&Silence> {
+        self.silences
+            .iter()
+            .filter(|s| s.expires_at > now)
+            .collect()
+    }
+
+    /// The id of the first active silence covering this alert, if any
+    pub fn matching(&self, rule: &str, key: &str, now: u64) -> Option<u64> {
+        self.silences
+            .iter()
+            .find(|s| s.expires_at > now && s.matches(rule, key))
+            .map(|s| s.id)
+    }
+}
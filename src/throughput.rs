@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration};
+
+use crate::*;
+
+/// Global totals are sampled this often
+pub const SAMPLE_PERIOD_MS: u64 = 1000;
+/// Samples kept, i.e. ten minutes at one per second
+const CAPACITY: usize = 600;
+/// Window of `/api/throughput` when none is given, in seconds
+const DEFAULT_WINDOW_S: u64 = 300;
+
+/// Totals over all topics at one instant
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ThroughputPoint {
+    pub at: u64,
+    pub messages_per_sec: f32,
+    pub bytes_per_sec: f32,
+    pub topics: u32,
+}
+
+/// Fixed-size ring of the latest samples; recording never allocates
+#[derive(Debug)]
+pub struct ThroughputHistory {
+    points: [ThroughputPoint; CAPACITY],
+    /// Slot the next sample is written to
+    next: usize,
+    len: usize,
+}
+
+impl Default for ThroughputHistory {
+    fn default() -> Self {
+        Self {
+            points: [ThroughputPoint::default(); CAPACITY],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+impl ThroughputHistory {
+    fn record(&mut self, point: ThroughputPoint) {
+        self.points[self.next] = point;
+        self.next = (self.next + 1) % CAPACITY;
+        self.len = (self.len + 1).min(CAPACITY);
+    }
+
+    pub fn latest(&self) -> Option<ThroughputPoint> {
+        (self.len > 0).then(|| self.points[(self.next + CAPACITY - 1) % CAPACITY])
+    }
+
+    /// Samples no older than `window_ms` before `now`, oldest first
+    pub fn window(&self, window_ms: u64, now: u64) -> Vec<ThroughputPoint> {
+        let cutoff = now.saturating_sub(window_ms);
+        (0..self.len)
+            .map(|age| self.points[(self.next + CAPACITY - self.len + age) % CAPACITY])
+            .filter(|point| point.at >= cutoff)
+            .collect()
+    }
+}
+
+pub type Throughput = Arc<RwLock<ThroughputHistory>>;
+
+/// The latest sample as sent with every SSE update, with display strings
+#[derive(Debug, Serialize)]
+pub struct LatestThroughput {
+    #[serde(flatten)]
+    pub point: ThroughputPoint,
+    pub rate_display: String,
+    pub bandwidth_display: String,
+}
+
+impl LatestThroughput {
+    pub fn new(point: ThroughputPoint, units: format::Units) -> Self {
+        Self {
+            point,
+            rate_display: format::hz(f64::from(point.messages_per_sec)),
+            bandwidth_display: format::bandwidth(f64::from(point.bytes_per_sec), units),
+        }
+    }
+}
+
+/// Query of `/api/throughput`
+#[derive(Debug, Deserialize)]
+pub struct ThroughputQuery {
+    /// Seconds of history, capped at what is kept
+    pub window: Option<u64>,
+}
+
+impl ThroughputQuery {
+    pub fn window_ms(&self) -> u64 {
+        self.window
+            .unwrap_or(DEFAULT_WINDOW_S)
+            .min(CAPACITY as u64 * SAMPLE_PERIOD_MS / 1000)
+            * 1000
+    }
+}
+
+/// Samples the summed rate and bandwidth of every cached topic.
+///
+/// Totals come from the cache rather than from counting samples, so a
+/// subscriber restart neither resets nor spikes them: silent topics decay the
+/// same way they do in the table.
+pub async fn run_throughput_sampler(cache: TopicCache, history: Throughput) {
+    let mut interval = time::interval(Duration::from_millis(SAMPLE_PERIOD_MS));
+    loop {
+        interval.tick().await;
+
+        let now = get_timestamp();
+        let point = {
+            let topics = cache.read().await;
+            let (messages_per_sec, bytes_per_sec) =
+                topics
+                    .values()
+                    .fold((0.0, 0.0), |(messages, bytes), topic| {
+                        let hz = alerts::effective_hz(topic, now);
+                        (messages + hz, bytes + hz * topic.avg_data_size_bytes)
+                    });
+            ThroughputPoint {
+                at: now,
+                messages_per_sec: messages_per_sec as f32,
+                bytes_per_sec: bytes_per_sec as f32,
+                topics: u32::try_from(topics.len()).unwrap_or(u32::MAX),
+            }
+        };
+        history.write().await.record(point);
+    }
+}
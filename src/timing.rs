@@ -0,0 +1,67 @@
+use serde::Serialize;
+use zenoh::key_expr::keyexpr;
+
+use crate::*;
+
+/// Jitter and latency bounds for topics matching `key_expr`. A value beyond its
+/// bound colours the cell; like highlights, nobody is alerted.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TimingThreshold {
+    pub key_expr: &'static str,
+    /// Standard deviation of the inter-arrival intervals, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<f64>,
+    /// Receive time minus the sample's Zenoh timestamp, in milliseconds. Only
+    /// meaningful when the publisher's clock is synchronised with this host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+}
+
+impl TimingThreshold {
+    pub fn validate(&self) -> Result<(), String> {
+        keyexpr::new(self.key_expr)
+            .map_err(|e| format!("invalid timing key_expr '{}': {}", self.key_expr, e))?;
+        for (name, bound) in [
+            ("jitter_ms", self.jitter_ms),
+            ("latency_ms", self.latency_ms),
+        ] {
+            if bound.is_some_and(|bound| !bound.is_finite() || bound < 0.0) {
+                return Err(format!(
+                    "timing threshold {} for '{}' must be a non-negative number",
+                    name, self.key_expr
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the jitter and the latency exceed the bounds of the first threshold
+/// matching `key`
+pub fn exceeded(
+    thresholds: &[TimingThreshold],
+    key: &str,
+    jitter_ms: f64,
+    latency_ms: Option<f64>,
+) -> (bool, bool) {
+    thresholds
+        .iter()
+        .find(|threshold| alerts::key_matches(threshold.key_expr, key))
+        .map_or((false, false), |threshold| {
+            (
+                threshold.jitter_ms.is_some_and(|bound| jitter_ms > bound),
+                threshold
+                    .latency_ms
+                    .zip(latency_ms)
+                    .is_some_and(|(bound, latency)| latency > bound),
+            )
+        })
+}
+
+/// Milliseconds between the sample's Zenoh timestamp and `received_at`, never negative
+pub fn latency_ms(sample: &zenoh::sample::Sample, received_at: u64) -> Option<f64> {
+    sample.timestamp().map(|timestamp| {
+        let sent_ms = timestamp.get_time().to_duration().as_secs_f64() * 1000.0;
+        (received_at as f64 - sent_ms).max(0.0)
+    })
+}
@@ -0,0 +1,210 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use zenoh::key_expr::keyexpr;
+
+use crate::persist::JsonFile;
+use crate::{Column, TopicData};
+
+/// Longest accepted view name and filter text
+const MAX_NAME_LEN: usize = 64;
+const MAX_FILTER_LEN: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Key,
+    Timestamp,
+    Hz,
+    Size,
+    Stability,
+    /// Time since the last sample, so the stalest topics can be put first
+    Age,
+}
+
+impl SortField {
+    /// The column showing this field; sorting by a hidden column would look random
+    pub fn column(&self) -> Column {
+        match self {
+            SortField::Key => Column::Key,
+            SortField::Timestamp => Column::Timestamp,
+            SortField::Hz => Column::Hz,
+            SortField::Size => Column::Size,
+            SortField::Stability => Column::Stability,
+            SortField::Age => Column::Age,
+        }
+    }
+
+    /// Keys read best A-Z and the least stable topics are the interesting ones;
+    /// for the other numbers, the most recent, largest or stalest come first
+    pub fn natural_direction(&self) -> SortDirection {
+        match self {
+            SortField::Key | SortField::Stability => SortDirection::Asc,
+            _ => SortDirection::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Sort {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+impl Sort {
+    /// Orders topics the way the table does, ties broken by key
+    pub fn compare(&self, a: &TopicData, b: &TopicData) -> Ordering {
+        let order = match self.field {
+            SortField::Key => a.key_expr.cmp(&b.key_expr),
+            SortField::Timestamp => a.received_timestamp.cmp(&b.received_timestamp),
+            SortField::Hz => {
+                let (a_hz, b_hz) = (a.estimated_hz.unwrap_or(0.0), b.estimated_hz.unwrap_or(0.0));
+                a_hz.total_cmp(&b_hz)
+            }
+            SortField::Size => a.last_data_size_bytes.cmp(&b.last_data_size_bytes),
+            SortField::Stability => a.stability.cmp(&b.stability),
+            // Older samples are older for everyone, so no clock is needed;
+            // never-received pinned topics are the oldest of all
+            SortField::Age => b.received_timestamp.cmp(&a.received_timestamp),
+        };
+        let order = match self.direction {
+            SortDirection::Asc => order,
+            SortDirection::Desc => order.reverse(),
+        };
+        order.then_with(|| a.key_expr.cmp(&b.key_expr))
+    }
+}
+
+/// A named dashboard layout, applied by opening `/?view=<name>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct View {
+    pub name: String,
+    /// Text for the filter box
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Sort>,
+    /// Columns to show, by name; all configured columns when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+    /// Key expressions; when given, only matching topics are shown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watchlist: Vec<String>,
+}
+
+impl View {
+    /// Checks the view against the columns the dashboard was started with
+    fn validate(&self, configured: &[Column]) -> Result<(), String> {
+        let name_ok = !self.name.is_empty()
+            && self.name.len() <= MAX_NAME_LEN
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !name_ok {
+            return Err(format!(
+                "view names must be 1-{} letters, digits, '-' or '_'",
+                MAX_NAME_LEN
+            ));
+        }
+        if let Some(filter) = &self.filter
+            && (filter.len() > MAX_FILTER_LEN || filter.chars().any(char::is_control))
+        {
+            return Err(format!(
+                "the filter must be at most {} printable characters",
+                MAX_FILTER_LEN
+            ));
+        }
+        if let Some(sort) = &self.sort
+            && !configured.contains(&sort.field.column())
+        {
+            return Err(format!(
+                "cannot sort by '{}', its column is not shown by this monitor",
+                sort.field.column().name()
+            ));
+        }
+        if let Some(columns) = &self.columns {
+            for name in columns {
+                let column = Column::parse(name)?;
+                if !configured.contains(&column) {
+                    return Err(format!("column '{}' is not shown by this monitor", name));
+                }
+            }
+            if !columns.iter().any(|name| name == "key") {
+                return Err("a view's columns must include 'key'".to_string());
+            }
+        }
+        for pattern in &self.watchlist {
+            keyexpr::new(pattern.as_str())
+                .map_err(|e| format!("invalid watchlist key_expr '{}': {}", pattern, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Saved views, persisted to a JSON file so they survive restarts
+pub struct Views {
+    file: JsonFile,
+    views: BTreeMap<String, View>,
+}
+
+impl Views {
+    /// Loads the views saved at `path`, starting empty if there are none
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let file = JsonFile::new(path, "views");
+        let views: Vec<View> = file.load();
+        if !views.is_empty() {
+            info!(
+                "Loaded {} view(s) from {}",
+                views.len(),
+                file.path().display()
+            );
+        }
+        Self {
+            file,
+            views: views
+                .into_iter()
+                .map(|view| (view.name.clone(), view))
+                .collect(),
+        }
+    }
+
+    fn save(&self) {
+        let views: Vec<&View> = self.views.values().collect();
+        self.file.save(&views);
+    }
+
+    /// Creates the view, replacing any existing one with the same name
+    pub fn put(&mut self, view: View, configured: &[Column]) -> Result<View, String> {
+        view.validate(configured)?;
+        self.views.insert(view.name.clone(), view.clone());
+        self.save();
+        Ok(view)
+    }
+
+    /// Returns false if there is no view with this name
+    pub fn delete(&mut self, name: &str) -> bool {
+        let deleted = self.views.remove(name).is_some();
+        if deleted {
+            self.save();
+        }
+        deleted
+    }
+
+    pub fn get(&self, name: &str) -> Option<&View> {
+        self.views.get(name)
+    }
+
+    pub fn list(&self) -> Vec<&View> {
+        self.views.values().collect()
+    }
+}